@@ -129,6 +129,14 @@ pub const UNABLE_TO_CONNECT: OdbcState<'static> = OdbcState {
     odbc_2_state: "08001",
     odbc_3_state: "08001",
 };
+pub const COMMUNICATION_LINK_FAILURE: OdbcState<'static> = OdbcState {
+    odbc_2_state: "08S01",
+    odbc_3_state: "08S01",
+};
+pub const INVALID_AUTHORIZATION_SPECIFICATION: OdbcState<'static> = OdbcState {
+    odbc_2_state: "28000",
+    odbc_3_state: "28000",
+};
 pub const INVALID_DESCRIPTOR_INDEX: OdbcState<'static> = OdbcState {
     odbc_2_state: "S1002",
     odbc_3_state: "07009",
@@ -154,6 +162,10 @@ pub const INVALID_CURSOR_STATE: OdbcState<'static> = OdbcState {
     odbc_2_state: "24000",
     odbc_3_state: "24000",
 };
+pub const INVALID_USE_OF_NULL_POINTER: OdbcState<'static> = OdbcState {
+    odbc_2_state: "S1009",
+    odbc_3_state: "HY009",
+};
 pub const FUNCTION_SEQUENCE_ERROR: OdbcState<'static> = OdbcState {
     odbc_2_state: "S1010",
     odbc_3_state: "HY010",
@@ -186,6 +198,10 @@ pub const INVALID_CHARACTER_VALUE: OdbcState<'static> = OdbcState {
     odbc_2_state: "22005",
     odbc_3_state: "22018",
 };
+pub const INTERVAL_FIELD_OVERFLOW: OdbcState<'static> = OdbcState {
+    odbc_2_state: "22015",
+    odbc_3_state: "22015",
+};
 pub const CONNECTION_NOT_OPEN: OdbcState<'static> = OdbcState {
     odbc_2_state: "08003",
     odbc_3_state: "08003",
@@ -196,12 +212,18 @@ pub const SQL_ALL_CATALOGS: &str = "%";
 pub const SQL_ALL_SCHEMAS: &str = "%";
 
 pub const SQL_CB_NULL: u16 = 0x0000;
+// SQL_IDENTIFIER_CASE and SQL_QUOTED_IDENTIFIER_CASE values
+pub const SQL_IC_UPPER: u16 = 1;
+pub const SQL_IC_LOWER: u16 = 2;
+pub const SQL_IC_SENSITIVE: u16 = 3;
+pub const SQL_IC_MIXED: u16 = 4;
 pub const MAX_COLUMNS_U16_ZERO: u16 = 0x0000;
 pub const SQL_CL_START: u16 = 0x0001;
 pub const MAX_COLUMNS_U32_ZERO: u32 = 0x0;
 pub const SQL_OIC_CORE: u32 = 0x00000001;
 pub const SQL_SC_SQL92_ENTRY: u32 = 0x00000001;
 pub const COLUMN_ALIAS_INFO_Y: &str = "Y";
+pub const COLUMN_ALIAS_INFO_N: &str = "N";
 pub const SQL_GB_GROUP_BY_CONTAINS_SELECT: u16 = 0x0002;
 pub const SQL_CB_PRESERVE: u16 = 2;
 pub const SQL_CA1_NEXT: u32 = 0x00000001;
@@ -362,6 +384,9 @@ pub const SQL_FN_TD_EXTRACT: u32 = 0x00100000;
 // SQL_CATALOG_USAGE bitmasks
 pub const SQL_CU_DML_STATEMENTS: u32 = 0x00000001;
 
+// SQL_SCHEMA_USAGE bitmasks
+pub const SQL_SU_DML_STATEMENTS: u32 = 0x00000001;
+
 // SQL_GETDATA_EXTENSIONS bitmasks
 pub const SQL_GD_ANY_COLUMN: u32 = 0x00000001;
 pub const SQL_GD_ANY_ORDER: u32 = 0x00000002;
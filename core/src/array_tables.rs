@@ -0,0 +1,498 @@
+use crate::json_schema::{
+    simplified::{Atomic, ObjectSchema, Schema},
+    BsonTypeName,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Keywords which may follow a table reference in a FROM clause, and so are not table aliases.
+const NON_ALIAS_KEYWORDS: &[&str] = &[
+    "CROSS",
+    "EXCEPT",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "INNER",
+    "INTERSECT",
+    "JOIN",
+    "LEFT",
+    "LIMIT",
+    "NATURAL",
+    "OFFSET",
+    "ON",
+    "ORDER",
+    "OUTER",
+    "RIGHT",
+    "UNION",
+    "USING",
+    "WHERE",
+];
+
+// Keywords which end the list of table references of a FROM clause.
+const FROM_CLAUSE_END_KEYWORDS: &[&str] = &[
+    "EXCEPT",
+    "GROUP",
+    "HAVING",
+    "INTERSECT",
+    "LIMIT",
+    "OFFSET",
+    "ON",
+    "ORDER",
+    "UNION",
+    "USING",
+    "WHERE",
+];
+
+/// An ArrayTable is a virtual table exposing the elements of an array field of a collection,
+/// one row per element. Its columns are the `_id` of the parent document, the index of the
+/// element in the array (`<field>_idx`) and the element itself (`<field>`). Queries reference
+/// it by name, and are rewritten to unwind the array of the parent collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayTable {
+    // The name of the virtual table: the collection name and the field path, joined by
+    // underscores (`orders_items`).
+    pub name: String,
+    // The collection holding the array.
+    pub collection: String,
+    // The path of the array field in the documents of the collection.
+    pub path: Vec<String>,
+    // The schema of the rows of the virtual table.
+    pub schema: Schema,
+}
+
+impl ArrayTable {
+    /// Lists the array tables of a collection, in field path order. Arrays are found in the
+    /// top-level fields and in nested documents, but not inside the elements of other arrays.
+    pub fn from_collection_schema(collection: &str, collection_schema: &Schema) -> Vec<Self> {
+        let mut tables = vec![];
+        if let Ok(object_schema) = collection_schema.assert_object_schema() {
+            let id_schema = object_schema
+                .properties
+                .get("_id")
+                .cloned()
+                .unwrap_or(Schema::Atomic(Atomic::Scalar(BsonTypeName::Any)));
+            Self::find_arrays(collection, object_schema, &[], &id_schema, &mut tables);
+        }
+        tables
+    }
+
+    fn find_arrays(
+        collection: &str,
+        object_schema: &ObjectSchema,
+        parent_path: &[String],
+        id_schema: &Schema,
+        tables: &mut Vec<Self>,
+    ) {
+        for (name, schema) in object_schema.properties.iter() {
+            let path = parent_path
+                .iter()
+                .cloned()
+                .chain(std::iter::once(name.clone()))
+                .collect::<Vec<_>>();
+            if let Schema::Atomic(Atomic::Object(nested)) = schema {
+                Self::find_arrays(collection, nested, &path, id_schema, tables);
+            } else if let Some(element_schema) = array_element_schema(schema) {
+                tables.push(Self::new(collection, path, id_schema, element_schema));
+            }
+        }
+    }
+
+    fn new(collection: &str, path: Vec<String>, id_schema: &Schema, element: &Schema) -> Self {
+        let field = path.last().unwrap().clone();
+        let index = format!("{field}_idx");
+        let schema = Schema::Atomic(Atomic::Object(ObjectSchema {
+            properties: BTreeMap::from([
+                ("_id".to_string(), id_schema.clone()),
+                (
+                    index.clone(),
+                    Schema::Atomic(Atomic::Scalar(BsonTypeName::Int)),
+                ),
+                (field.clone(), element.clone()),
+            ]),
+            required: BTreeSet::from(["_id".to_string(), index, field]),
+            additional_properties: false,
+        }));
+        ArrayTable {
+            name: format!("{collection}_{}", path.join("_")),
+            collection: collection.to_string(),
+            path,
+            schema,
+        }
+    }
+
+    /// Returns the derived table which replaces a reference to this table in a query. It
+    /// unwinds the array of the collection in the given database, or in the current database
+    /// when there is none, and selects the columns of this table.
+    pub fn derived_table(&self, db: Option<&str>) -> String {
+        let field = self.path.last().unwrap();
+        let index = format!("{field}_idx");
+        let collection = quote(&self.collection);
+        let datasource = match db {
+            Some(db) => format!("{}.{collection}", quote(db)),
+            None => collection.clone(),
+        };
+        let path = self
+            .path
+            .iter()
+            .map(String::as_str)
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(".");
+        format!(
+            "(SELECT {collection}.`_id` AS `_id`, {collection}.{index} AS {index}, \
+             {collection}.{path} AS {field} FROM UNWIND({datasource} WITH PATH => {path}, \
+             INDEX => {index}))",
+            index = quote(&index),
+            field = quote(field),
+        )
+    }
+}
+
+// Returns the schema of the elements of an array field, including a nullable or optional one.
+fn array_element_schema(schema: &Schema) -> Option<&Schema> {
+    match schema {
+        Schema::Atomic(Atomic::Array(element)) => Some(element),
+        Schema::AnyOf(any_of) => {
+            let mut arrays = any_of.iter().filter_map(|atomic| match atomic {
+                Atomic::Array(element) => Some(element.as_ref()),
+                _ => None,
+            });
+            let element = arrays.next()?;
+            let only_nulls_besides = any_of.iter().all(|atomic| {
+                matches!(
+                    atomic,
+                    Atomic::Array(_) | Atomic::Scalar(BsonTypeName::Null | BsonTypeName::Undefined)
+                )
+            });
+            (arrays.next().is_none() && only_nulls_besides).then_some(element)
+        }
+        _ => None,
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("`{}`", identifier.replace('`', "``"))
+}
+
+/// A table referenced by name in the FROM clause of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableReference {
+    // The byte range of the (possibly qualified) table name in the query.
+    pub span: std::ops::Range<usize>,
+    // The database qualifying the table name, if any.
+    pub db: Option<String>,
+    pub name: String,
+    // Whether the reference is followed by an alias.
+    pub aliased: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    QuotedIdentifier(String),
+    Punct(char),
+    Literal,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: std::ops::Range<usize>,
+}
+
+impl Token {
+    fn is_keyword(&self, keywords: &[&str]) -> bool {
+        matches!(&self.kind, TokenKind::Word(w) if keywords.iter().any(|k| k.eq_ignore_ascii_case(w)))
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Word(w) | TokenKind::QuotedIdentifier(w) => Some(w),
+            _ => None,
+        }
+    }
+}
+
+// Splits a query into words, quoted identifiers, punctuation and literals, skipping whitespace
+// and comments.
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = query.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '-' if chars.peek().is_some_and(|&(_, n)| n == '-') => {
+                while chars.next_if(|&(_, n)| n != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.peek().is_some_and(|&(_, n)| n == '*') => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, n) in chars.by_ref() {
+                    if previous == '*' && n == '/' {
+                        break;
+                    }
+                    previous = n;
+                }
+                continue;
+            }
+            '\'' | '`' | '"' => {
+                let mut content = String::new();
+                while let Some((_, n)) = chars.next() {
+                    if n == c {
+                        // A doubled quote is an escaped quote.
+                        if chars.next_if(|&(_, n)| n == c).is_none() {
+                            break;
+                        }
+                    }
+                    content.push(n);
+                }
+                if c == '\'' {
+                    TokenKind::Literal
+                } else {
+                    TokenKind::QuotedIdentifier(content)
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let mut word = c.to_string();
+                while let Some((_, n)) =
+                    chars.next_if(|&(_, n)| n.is_alphanumeric() || n == '_' || n == '$')
+                {
+                    word.push(n);
+                }
+                TokenKind::Word(word)
+            }
+            c => TokenKind::Punct(c),
+        };
+        let end = chars.peek().map_or(query.len(), |&(i, _)| i);
+        tokens.push(Token {
+            kind,
+            span: start..end,
+        });
+    }
+    tokens
+}
+
+/// Lists the tables referenced by name in the FROM clauses of a query, including those of
+/// subqueries.
+pub fn table_references(query: &str) -> Vec<TableReference> {
+    let tokens = tokenize(query);
+    let mut references = vec![];
+    // Whether the tokens are in the table list of a FROM clause, saved for each open
+    // parenthesis.
+    let mut in_from = false;
+    let mut expect_table = false;
+    let mut saved = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match &token.kind {
+            TokenKind::Punct('(') => {
+                saved.push(in_from);
+                in_from = false;
+                expect_table = false;
+            }
+            TokenKind::Punct(')') => {
+                in_from = saved.pop().unwrap_or(false);
+            }
+            TokenKind::Punct(',') if in_from => expect_table = true,
+            _ if token.is_keyword(&["FROM"]) => {
+                in_from = true;
+                expect_table = true;
+            }
+            _ if token.is_keyword(&["JOIN"]) => {
+                in_from = true;
+                expect_table = true;
+            }
+            _ if token.is_keyword(FROM_CLAUSE_END_KEYWORDS) => {
+                in_from = false;
+                expect_table = false;
+            }
+            TokenKind::Word(_) | TokenKind::QuotedIdentifier(_) if expect_table => {
+                expect_table = false;
+                // UNWIND and FLATTEN datasources are followed by their parenthesized arguments.
+                if tokens
+                    .get(i + 1)
+                    .is_some_and(|next| next.kind == TokenKind::Punct('('))
+                {
+                    i += 1;
+                    continue;
+                }
+                let qualified = tokens
+                    .get(i + 1)
+                    .is_some_and(|t| t.kind == TokenKind::Punct('.'))
+                    && tokens.get(i + 2).and_then(Token::identifier).is_some();
+                let (db, name, last) = if qualified {
+                    (
+                        token.identifier().map(str::to_string),
+                        tokens[i + 2].identifier().unwrap().to_string(),
+                        i + 2,
+                    )
+                } else {
+                    (None, token.identifier().unwrap().to_string(), i)
+                };
+                let aliased = tokens.get(last + 1).is_some_and(|next| {
+                    matches!(next.kind, TokenKind::QuotedIdentifier(_))
+                        || matches!(next.kind, TokenKind::Word(_))
+                            && !next.is_keyword(NON_ALIAS_KEYWORDS)
+                });
+                references.push(TableReference {
+                    span: token.span.start..tokens[last].span.end,
+                    db,
+                    name,
+                    aliased,
+                });
+                i = last;
+            }
+            _ => expect_table = false,
+        }
+        i += 1;
+    }
+    references
+}
+
+/// Replaces each table reference with the derived table of its array table. References which
+/// are not aliased are aliased with the name of the array table, so that the result set
+/// columns keep the table name reported by SQLColumns.
+pub fn rewrite_query(query: &str, replacements: &[(TableReference, ArrayTable)]) -> String {
+    let mut replacements = replacements.iter().collect::<Vec<_>>();
+    replacements.sort_by_key(|(reference, _)| reference.span.start);
+    let mut rewritten = String::with_capacity(query.len());
+    let mut copied = 0;
+    for (reference, table) in replacements {
+        rewritten.push_str(&query[copied..reference.span.start]);
+        rewritten.push_str(&table.derived_table(reference.db.as_deref()));
+        if !reference.aliased {
+            rewritten.push_str(&format!(" AS {}", quote(&table.name)));
+        }
+        copied = reference.span.end;
+    }
+    rewritten.push_str(&query[copied..]);
+    rewritten
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::json_schema;
+
+    fn schema(json: serde_json::Value) -> Schema {
+        serde_json::from_value::<json_schema::Schema>(json)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn orders_schema() -> Schema {
+        schema(serde_json::json!({
+            "bsonType": "object",
+            "properties": {
+                "_id": {"bsonType": "objectId"},
+                "items": {
+                    "bsonType": "array",
+                    "items": {
+                        "bsonType": "object",
+                        "properties": {"sku": {"bsonType": "string"}}
+                    }
+                },
+                "customer": {
+                    "bsonType": "object",
+                    "properties": {
+                        "tags": {"anyOf": [
+                            {"bsonType": "array", "items": {"bsonType": "string"}},
+                            {"bsonType": "null"}
+                        ]},
+                        "name": {"bsonType": "string"}
+                    }
+                },
+                "mixed": {"anyOf": [
+                    {"bsonType": "array", "items": {"bsonType": "int"}},
+                    {"bsonType": "string"}
+                ]}
+            }
+        }))
+    }
+
+    #[test]
+    fn array_tables_of_a_collection() {
+        let tables = ArrayTable::from_collection_schema("orders", &orders_schema());
+        assert_eq!(
+            vec![
+                ("orders_customer_tags", vec!["customer", "tags"]),
+                ("orders_items", vec!["items"]),
+            ],
+            tables
+                .iter()
+                .map(|t| (t.name.as_str(), t.path.iter().map(String::as_str).collect()))
+                .collect::<Vec<(&str, Vec<&str>)>>()
+        );
+        assert_eq!(
+            schema(serde_json::json!({
+                "bsonType": "object",
+                "properties": {
+                    "_id": {"bsonType": "objectId"},
+                    "items_idx": {"bsonType": "int"},
+                    "items": {
+                        "bsonType": "object",
+                        "properties": {"sku": {"bsonType": "string"}}
+                    }
+                },
+                "required": ["_id", "items", "items_idx"],
+                "additionalProperties": false
+            })),
+            tables[1].schema
+        );
+    }
+
+    #[test]
+    fn table_references_in_from_clauses() {
+        let query = "SELECT * FROM orders_items JOIN db2.`orders_x` AS x ON a = b, \
+                     (SELECT 1 FROM \"t\" t2 WHERE c IN (SELECT d FROM u)) AS s \
+                     WHERE e = 'FROM fake' -- FROM comment";
+        let references = table_references(query);
+        assert_eq!(
+            vec![
+                (None, "orders_items", false),
+                (Some("db2"), "orders_x", true),
+                (None, "t", true),
+                (None, "u", false),
+            ],
+            references
+                .iter()
+                .map(|r| (r.db.as_deref(), r.name.as_str(), r.aliased))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("db2.`orders_x`", &query[references[1].span.clone()]);
+        // Neither an UNWIND datasource nor the collection it unwinds is a table reference.
+        assert_eq!(
+            vec![(None, "orders_items", true)],
+            table_references(
+                "SELECT * FROM UNWIND(orders WITH PATH => items) JOIN orders_items AS i ON a = b"
+            )
+            .iter()
+            .map(|r| (r.db.as_deref(), r.name.as_str(), r.aliased))
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rewrite_references_to_array_tables() {
+        let tables = ArrayTable::from_collection_schema("orders", &orders_schema());
+        let query = "SELECT * FROM orders_items WHERE items_idx = 0";
+        let reference = table_references(query).remove(0);
+        assert_eq!(
+            "SELECT * FROM (SELECT `orders`.`_id` AS `_id`, `orders`.`items_idx` AS `items_idx`, \
+             `orders`.`items` AS `items` FROM UNWIND(`orders` WITH PATH => `items`, \
+             INDEX => `items_idx`)) AS `orders_items` WHERE items_idx = 0",
+            rewrite_query(query, &[(reference, tables[1].clone())])
+        );
+
+        let query = "SELECT t.tags FROM mydb.orders_customer_tags t";
+        let reference = table_references(query).remove(0);
+        assert_eq!(
+            "SELECT t.tags FROM (SELECT `orders`.`_id` AS `_id`, `orders`.`tags_idx` AS \
+             `tags_idx`, `orders`.`customer`.`tags` AS `tags` FROM UNWIND(`mydb`.`orders` WITH \
+             PATH => `customer`.`tags`, INDEX => `tags_idx`)) t",
+            rewrite_query(query, &[(reference, tables[0].clone())])
+        );
+    }
+}
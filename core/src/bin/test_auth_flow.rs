@@ -1,5 +1,6 @@
 use mongo_odbc_core::{oidc_auth::do_auth_flow, test_config::*};
 use mongodb::options::oidc::{CallbackContext, IdpServerInfo};
+use rfc8252_http_server::RedirectOptions;
 
 #[tokio::main]
 async fn main() {
@@ -13,7 +14,7 @@ async fn main() {
         )
         .version(1u32)
         .build();
-    let res = do_auth_flow(c).await.unwrap();
+    let res = do_auth_flow(c, &RedirectOptions::default()).await.unwrap();
     println!(
         "{:?}, {:?}, {:?}",
         res.access_token, res.expires, res.refresh_token
@@ -3,6 +3,7 @@ use mongo_odbc_core::{
     test_config::*,
 };
 use mongodb::options::oidc::{CallbackContext, IdpServerInfo};
+use rfc8252_http_server::RedirectOptions;
 
 #[tokio::main]
 async fn main() {
@@ -17,7 +18,7 @@ async fn main() {
         .version(1u32)
         .build();
     let mut refresh_c = c.clone();
-    let res = do_auth_flow(c).await.unwrap();
+    let res = do_auth_flow(c, &RedirectOptions::default()).await.unwrap();
     println!(
         "{:?}, {:?}, {:?}",
         res.access_token, res.expires, res.refresh_token
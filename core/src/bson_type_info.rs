@@ -7,6 +7,24 @@ pub const MAX_STRING_SIZE: u16 = u16::MAX;
 pub enum TypeMode {
     Standard,
     Simple,
+    /// Simple types, with the fields of nested documents reported as dotted columns
+    /// (`address.city`) down to the given depth.
+    Flatten(u16),
+}
+
+impl TypeMode {
+    /// Whether BsonTypeInfo.simple_type_info is used to report column types.
+    pub fn is_simple(&self) -> bool {
+        matches!(self, TypeMode::Simple | TypeMode::Flatten(_))
+    }
+
+    /// How many levels of nested documents are reported as dotted columns.
+    pub fn flatten_depth(&self) -> u16 {
+        match self {
+            TypeMode::Flatten(depth) => *depth,
+            _ => 0,
+        }
+    }
 }
 
 /// make_default_attr_func creates an anonymous function that takes a single
@@ -141,6 +159,9 @@ impl SimpleTypeInfo {
     }
 }
 
+// The number of significant decimal digits of a Decimal128.
+pub const DECIMAL128_PRECISION: u16 = 34;
+
 pub const SQL_SEARCHABLE: i32 = 3;
 pub const SQL_PRED_BASIC: i32 = 2;
 pub const SQL_PRED_NONE: i32 = 0;
@@ -868,6 +889,34 @@ impl BsonTypeInfo {
         column_size: make_default_attr_func!(None),
         simple_type_info: SimpleTypeInfo::default(),
     };
+    // DECIMAL is the type of the columns whose schema is decimal, possibly nullable. Decimal128
+    // holds up to 34 significant decimal digits, so we report them as SQL_DECIMAL with that
+    // precision. The scale is carried by each value rather than by the column, so neither SCALE nor
+    // DECIMAL_DIGITS is reported. In simple type mode it is still rendered as a json string.
+    pub const DECIMAL: BsonTypeInfo = BsonTypeInfo {
+        type_name: "decimal",
+        sql_type: SqlDataType::SQL_DECIMAL,
+        non_concise_type: None,
+        searchable: SQL_PRED_BASIC,
+        is_case_sensitive: false,
+        fixed_prec_scale: false,
+        scale: None,
+        length: make_default_attr_func!(None),
+        precision: Some(DECIMAL128_PRECISION),
+        char_octet_length: make_default_attr_func!(None),
+        // precision + 2, to account for the sign and the decimal point
+        transfer_octet_length: Some(DECIMAL128_PRECISION + 2),
+        display_size: make_default_attr_func!(Some(DECIMAL128_PRECISION + 2)),
+        literal_prefix: None,
+        literal_suffix: None,
+        sql_code: None,
+        is_auto_unique_value: Some(false),
+        is_unsigned: Some(false),
+        num_prec_radix: Some(10),
+        decimal_digit: None,
+        column_size: make_default_attr_func!(Some(DECIMAL128_PRECISION)),
+        simple_type_info: SimpleTypeInfo::default(),
+    };
     pub const MINKEY: BsonTypeInfo = BsonTypeInfo {
         type_name: "minKey",
         sql_type: SqlDataType::SQL_UNKNOWN_TYPE,
@@ -939,7 +988,7 @@ impl BsonTypeInfo {
     };
 
     pub fn sql_type(&self, type_mode: TypeMode) -> SqlDataType {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             self.simple_type_info.clone().unwrap().sql_type
         } else {
             self.sql_type
@@ -947,7 +996,7 @@ impl BsonTypeInfo {
     }
 
     pub fn non_concise_type(&self, type_mode: TypeMode) -> SqlDataType {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             let simple_type_info = self.simple_type_info.clone().unwrap();
             simple_type_info
                 .non_concise_type
@@ -958,7 +1007,7 @@ impl BsonTypeInfo {
     }
 
     pub fn precision(&self, type_mode: TypeMode) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             None
         } else {
             self.precision
@@ -966,7 +1015,7 @@ impl BsonTypeInfo {
     }
 
     pub fn length(&self, type_mode: TypeMode, max_string_length: Option<u16>) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             (self.simple_type_info.clone().unwrap().length)(max_string_length)
         } else {
             (self.length)(max_string_length)
@@ -974,7 +1023,7 @@ impl BsonTypeInfo {
     }
 
     pub fn transfer_octet_length(&self, type_mode: TypeMode) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             self.simple_type_info.clone().unwrap().transfer_octet_length
         } else {
             self.transfer_octet_length
@@ -986,7 +1035,7 @@ impl BsonTypeInfo {
         type_mode: TypeMode,
         max_string_length: Option<u16>,
     ) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             (self.simple_type_info.clone().unwrap().length)(max_string_length)
         } else {
             (self.char_octet_length)(max_string_length)
@@ -994,7 +1043,7 @@ impl BsonTypeInfo {
     }
 
     pub fn display_size(&self, type_mode: TypeMode, max_string_length: Option<u16>) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             (self.simple_type_info.clone().unwrap().display_size)(max_string_length)
         } else {
             (self.display_size)(max_string_length)
@@ -1002,7 +1051,7 @@ impl BsonTypeInfo {
     }
 
     pub fn decimal_digit(&self, type_mode: TypeMode) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             None
         } else {
             self.decimal_digit
@@ -1010,7 +1059,7 @@ impl BsonTypeInfo {
     }
 
     pub fn column_size(&self, type_mode: TypeMode, max_string_length: Option<u16>) -> Option<u16> {
-        if type_mode == TypeMode::Simple && self.simple_type_info.is_some() {
+        if type_mode.is_simple() && self.simple_type_info.is_some() {
             (self.simple_type_info.clone().unwrap().length)(max_string_length)
         } else {
             (self.column_size)(max_string_length)
@@ -0,0 +1,154 @@
+use crate::util::to_name_regex;
+use regex::Regex;
+
+// Collections hidden from the catalog functions unless the exclude_collections option is set.
+// Underscores are escaped so that they are matched literally rather than as single-character
+// wildcards.
+pub const DEFAULT_EXCLUDED_COLLECTIONS: &str = r"\_\_sql\_schemas,system.%";
+
+// A comma-separated list of ODBC search patterns (`%` and `_` wildcards, `\` escape).
+#[derive(Debug, Clone)]
+struct PatternList(Vec<Option<Regex>>);
+
+impl PatternList {
+    fn new(patterns: &str) -> Self {
+        PatternList(
+            patterns
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                // to_name_regex returns None for '%', which matches every name.
+                .map(to_name_regex)
+                .collect(),
+        )
+    }
+
+    fn is_match(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|regex| regex.as_ref().is_none_or(|regex| regex.is_match(name)))
+    }
+}
+
+/// CatalogFilter holds the databases and collections which are visible through the catalog
+/// functions (SQLTables, SQLColumns). It is built from the include_databases, exclude_databases,
+/// include_collections and exclude_collections connection string/DSN options.
+/// A name is visible if it matches the include list (when one is set) and does not match the
+/// exclude list.
+#[derive(Debug, Clone)]
+pub struct CatalogFilter {
+    include_databases: Option<PatternList>,
+    exclude_databases: PatternList,
+    include_collections: Option<PatternList>,
+    exclude_collections: PatternList,
+}
+
+impl Default for CatalogFilter {
+    fn default() -> Self {
+        CatalogFilter::new(None, None, None, None)
+    }
+}
+
+impl CatalogFilter {
+    // Build a CatalogFilter from the raw option values. A missing exclude_collections option
+    // falls back to DEFAULT_EXCLUDED_COLLECTIONS; setting it (even to an empty value) replaces
+    // the default list.
+    pub fn new(
+        include_databases: Option<&str>,
+        exclude_databases: Option<&str>,
+        include_collections: Option<&str>,
+        exclude_collections: Option<&str>,
+    ) -> Self {
+        CatalogFilter {
+            include_databases: include_databases.map(PatternList::new),
+            exclude_databases: PatternList::new(exclude_databases.unwrap_or_default()),
+            include_collections: include_collections.map(PatternList::new),
+            exclude_collections: PatternList::new(
+                exclude_collections.unwrap_or(DEFAULT_EXCLUDED_COLLECTIONS),
+            ),
+        }
+    }
+
+    pub fn is_database_visible(&self, db_name: &str) -> bool {
+        // MHOUSE-7119 - admin database and empty strings are showing in list_database_names
+        !db_name.is_empty()
+            && db_name != "admin"
+            && self
+                .include_databases
+                .as_ref()
+                .is_none_or(|include| include.is_match(db_name))
+            && !self.exclude_databases.is_match(db_name)
+    }
+
+    pub fn is_collection_visible(&self, collection_name: &str) -> bool {
+        self.include_collections
+            .as_ref()
+            .is_none_or(|include| include.is_match(collection_name))
+            && !self.exclude_collections.is_match(collection_name)
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::CatalogFilter;
+
+    #[test]
+    fn default_hides_admin_and_system_collections() {
+        let filter = CatalogFilter::default();
+        assert!(filter.is_database_visible("sales"));
+        assert!(!filter.is_database_visible("admin"));
+        assert!(!filter.is_database_visible(""));
+        assert!(filter.is_collection_visible("orders"));
+        assert!(filter.is_collection_visible("xxsqlxschemas"));
+        assert!(!filter.is_collection_visible("__sql_schemas"));
+        assert!(!filter.is_collection_visible("system.views"));
+    }
+
+    #[test]
+    fn include_databases() {
+        let filter = CatalogFilter::new(Some("sales, hr_%"), None, None, None);
+        assert!(filter.is_database_visible("sales"));
+        assert!(filter.is_database_visible("hr_2024"));
+        assert!(!filter.is_database_visible("staging"));
+        assert!(!filter.is_database_visible("admin"));
+    }
+
+    #[test]
+    fn exclude_databases() {
+        let filter = CatalogFilter::new(None, Some("staging%,archive,internal"), None, None);
+        assert!(filter.is_database_visible("sales"));
+        assert!(!filter.is_database_visible("staging"));
+        assert!(!filter.is_database_visible("staging_eu"));
+        assert!(!filter.is_database_visible("archive"));
+        assert!(filter.is_database_visible("archives"));
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let filter = CatalogFilter::new(Some("%"), Some("internal"), None, None);
+        assert!(filter.is_database_visible("sales"));
+        assert!(!filter.is_database_visible("internal"));
+    }
+
+    #[test]
+    fn exclude_collections_replaces_default() {
+        let filter = CatalogFilter::new(None, None, None, Some(r"tmp\_%"));
+        assert!(!filter.is_collection_visible("tmp_orders"));
+        assert!(filter.is_collection_visible("tmporders"));
+        assert!(filter.is_collection_visible("__sql_schemas"));
+
+        let filter = CatalogFilter::new(None, None, None, Some(""));
+        assert!(filter.is_collection_visible("system.views"));
+    }
+
+    #[test]
+    fn include_collections() {
+        let filter = CatalogFilter::new(None, None, Some("orders,customers"), None);
+        assert!(filter.is_collection_visible("orders"));
+        assert!(!filter.is_collection_visible("orders_archive"));
+
+        // An empty include list hides every collection.
+        let filter = CatalogFilter::new(None, None, Some(""), None);
+        assert!(!filter.is_collection_visible("orders"));
+    }
+}
@@ -0,0 +1,704 @@
+use crate::odbc_uri::UserOptions;
+use crate::{err::Result, Error};
+use definitions::{AttrConnectionPooling, AttrCpMatch};
+use lazy_static::lazy_static;
+use mongodb::Client;
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// The default maximum number of Clients kept in a pool.
+pub const DEFAULT_MAX_POOLED_CLIENTS: usize = 32;
+/// The default time a Client no connection uses stays in the pool before it is shut down.
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+// How often the idle Clients are looked for.
+const REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// PoolScope is the set of connections sharing their Clients, from SQL_ATTR_CONNECTION_POOLING.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PoolScope {
+    /// Every connection has its own Client, which is shut down with the connection.
+    #[default]
+    Off,
+    /// The connections of all the environments share their Clients.
+    Driver,
+    /// The connections of the environment with the given id share their Clients.
+    Env(usize),
+}
+
+/// PoolOptions are how the Client of a connection is taken from, and kept in, the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOptions {
+    pub scope: PoolScope,
+    /// With SQL_CP_STRICT_MATCH, a pooled Client is only reused for the same connection string
+    /// keywords and connection attributes, like the login timeout. With SQL_CP_RELAXED_MATCH, the
+    /// connection string keywords are enough.
+    pub cp_match: AttrCpMatch,
+    /// The number of Clients in the pool, beyond which the least recently used idle Client is
+    /// evicted. When none is idle, the new Client is not pooled.
+    pub max_clients: usize,
+    /// The time a Client no connection uses stays in the pool.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            scope: PoolScope::Off,
+            cp_match: AttrCpMatch::SQL_CP_STRICT_MATCH,
+            max_clients: DEFAULT_MAX_POOLED_CLIENTS,
+            idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Creates the PoolOptions of the SQL_ATTR_CONNECTION_POOLING and SQL_ATTR_CP_MATCH
+    /// attributes of the environment with the given id. The driver has no connection-pool
+    /// awareness, so SQL_CP_DRIVER_AWARE is SQL_CP_ONE_PER_HENV.
+    pub fn new(pooling: AttrConnectionPooling, cp_match: AttrCpMatch, env_id: usize) -> Self {
+        let scope = match pooling {
+            AttrConnectionPooling::SQL_CP_OFF => PoolScope::Off,
+            AttrConnectionPooling::SQL_CP_ONE_PER_DRIVER => PoolScope::Driver,
+            AttrConnectionPooling::SQL_CP_ONE_PER_HENV
+            | AttrConnectionPooling::SQL_CP_DRIVER_AWARE => PoolScope::Env(env_id),
+        };
+        PoolOptions {
+            scope,
+            cp_match,
+            ..Default::default()
+        }
+    }
+}
+
+/// PoolMetrics are the counters of the Client pool since the driver was loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// The connections which reused a pooled Client.
+    pub hits: u64,
+    /// The pooled connections which created a Client.
+    pub misses: u64,
+    /// The Clients shut down because they were idle, or to make room for another Client.
+    pub evictions: u64,
+    /// The Clients in the pool.
+    pub pooled_clients: usize,
+    /// The pooled Clients used by at least one connection.
+    pub clients_in_use: usize,
+}
+
+// PoolKey is the scope and the connection string options of a pooled Client. The connection
+// attributes, like the login timeout, are left out so that relaxed matching is a lookup.
+struct PoolKey {
+    scope: PoolScope,
+    options: UserOptions,
+}
+
+impl PoolKey {
+    fn new(scope: PoolScope, options: &UserOptions) -> Self {
+        let mut options = options.clone();
+        options.client_options.connect_timeout = None;
+        PoolKey { scope, options }
+    }
+}
+
+// Only the options with a Hash implementation are hashed, equal options still have equal hashes.
+impl Hash for PoolKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let client_options = &self.options.client_options;
+        self.scope.hash(state);
+        client_options.hosts.hash(state);
+        client_options.app_name.hash(state);
+        if let Some(credential) = &client_options.credential {
+            credential.username.hash(state);
+            credential.source.hash(state);
+        }
+    }
+}
+
+impl PartialEq for PoolKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.scope == other.scope && self.options == other.options
+    }
+}
+
+impl Eq for PoolKey {}
+
+struct PooledClient {
+    // The options the Client was created with, including the connection attributes.
+    options: UserOptions,
+    client: Client,
+    // The pool keeps the Runtime running the Client alive, each connection using the Client holds
+    // another reference to it.
+    runtime: Arc<Runtime>,
+    last_used: Instant,
+    idle_timeout: Duration,
+}
+
+impl PooledClient {
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.runtime) > 1
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        !self.in_use() && now.saturating_duration_since(self.last_used) >= self.idle_timeout
+    }
+
+    // shutdown shuts the Client down on the Runtime its tasks run on.
+    fn shutdown(self) {
+        self.runtime
+            .block_on(async { self.client.shutdown().await });
+    }
+}
+
+// Reaper is the thread evicting the idle Clients, and the channel telling it to stop.
+struct Reaper {
+    stop: mpsc::Sender<()>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl Reaper {
+    // stop stops the thread and waits for it to exit.
+    fn stop(self) {
+        let _ = self.stop.send(());
+        if self.thread.join().is_err() {
+            log::error!("the Client pool reaper panicked");
+        }
+    }
+}
+
+#[derive(Default)]
+struct ClientPool {
+    clients: HashMap<PoolKey, Vec<PooledClient>>,
+    metrics: PoolMetrics,
+    // The number of allocated environments. The reaper is stopped when the last one is freed.
+    environments: usize,
+    // The last reaper started, which may have exited already if it is not running.
+    reaper: Option<Reaper>,
+    reaper_running: bool,
+}
+
+impl ClientPool {
+    // get returns a pooled Client matching the options, the most recently used one first.
+    fn get(
+        &mut self,
+        key: &PoolKey,
+        options: &UserOptions,
+        cp_match: AttrCpMatch,
+    ) -> Option<(Client, Arc<Runtime>)> {
+        let pooled = self
+            .clients
+            .get_mut(key)?
+            .iter_mut()
+            .filter(|pooled| {
+                cp_match == AttrCpMatch::SQL_CP_RELAXED_MATCH || pooled.options == *options
+            })
+            .max_by_key(|pooled| pooled.last_used)?;
+        pooled.last_used = Instant::now();
+        self.metrics.hits += 1;
+        Some((pooled.client.clone(), pooled.runtime.clone()))
+    }
+
+    // insert adds the Client to the pool, evicting the least recently used idle Client when the
+    // pool is full. The evicted Client is returned to be shut down once the pool is unlocked.
+    // The Client is not pooled when the pool is full of Clients in use.
+    fn insert(
+        &mut self,
+        key: PoolKey,
+        pooled: PooledClient,
+        max_clients: usize,
+    ) -> (bool, Option<PooledClient>) {
+        let mut evicted = None;
+        if self.len() >= max_clients {
+            evicted = self.remove_least_recently_used();
+            if evicted.is_none() {
+                return (false, None);
+            }
+        }
+        self.clients.entry(key).or_default().push(pooled);
+        (true, evicted)
+    }
+
+    fn remove_least_recently_used(&mut self) -> Option<PooledClient> {
+        let runtime = self
+            .clients
+            .values()
+            .flatten()
+            .filter(|pooled| !pooled.in_use())
+            .min_by_key(|pooled| pooled.last_used)?
+            .runtime
+            .clone();
+        self.remove(|_, pooled| Arc::ptr_eq(&pooled.runtime, &runtime))
+            .pop()
+    }
+
+    // remove takes the Clients for which the predicate holds out of the pool.
+    fn remove(&mut self, predicate: impl Fn(&PoolKey, &PooledClient) -> bool) -> Vec<PooledClient> {
+        let mut removed = Vec::new();
+        for (key, clients) in self.clients.iter_mut() {
+            let (matching, kept) = clients
+                .drain(..)
+                .partition::<Vec<_>, _>(|pooled| predicate(key, pooled));
+            removed.extend(matching);
+            *clients = kept;
+        }
+        self.clients.retain(|_, clients| !clients.is_empty());
+        self.metrics.evictions += removed.len() as u64;
+        removed
+    }
+
+    // remove_idle takes the Clients unused for longer than their idle timeout out of the pool.
+    fn remove_idle(&mut self, now: Instant) -> Vec<PooledClient> {
+        self.remove(|_, pooled| pooled.expired(now))
+    }
+
+    // release marks the Client running on the Runtime as used now, so that its idle timeout
+    // starts when its last connection is closed.
+    fn release(&mut self, runtime: &Arc<Runtime>) {
+        if let Some(pooled) = self
+            .clients
+            .values_mut()
+            .flatten()
+            .find(|pooled| Arc::ptr_eq(&pooled.runtime, runtime))
+        {
+            pooled.last_used = Instant::now();
+        }
+    }
+
+    // remove_released takes the Clients no connection uses out of the pool when a connection is
+    // closed, unless the reaper evicts them after their idle timeout: with the garbage_collect
+    // feature, or once the last environment is freed and the reaper is stopped.
+    fn remove_released(&mut self) -> Vec<PooledClient> {
+        if cfg!(feature = "garbage_collect") || self.environments == 0 {
+            self.remove(|_, pooled| !pooled.in_use())
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.clients.values().map(Vec::len).sum()
+    }
+
+    fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            pooled_clients: self.len(),
+            clients_in_use: self
+                .clients
+                .values()
+                .flatten()
+                .filter(|pooled| pooled.in_use())
+                .count(),
+            ..self.metrics
+        }
+    }
+}
+
+lazy_static! {
+    static ref CLIENT_POOL: Mutex<ClientPool> = Mutex::new(ClientPool::default());
+}
+
+fn create_client(options: UserOptions, runtime: &Runtime) -> Result<Client> {
+    // the Client Topology uses tokio::spawn, so we need a guard here.
+    let _guard = runtime.enter();
+    runtime.block_on(async {
+        Client::with_options(options.client_options).map_err(Error::InvalidClientOptions)
+    })
+}
+
+/// Returns the Client of a new connection with the given options, and the Runtime it runs on.
+/// Depending on the PoolOptions, it is a pooled Client and its Runtime, or a new Client running
+/// on the given Runtime.
+pub(crate) fn get_client(
+    options: UserOptions,
+    runtime: Arc<Runtime>,
+    pool_options: &PoolOptions,
+) -> Result<(Client, Arc<Runtime>)> {
+    if pool_options.scope == PoolScope::Off {
+        log::info!("creating new Client, connection pooling is off");
+        let client = create_client(options, &runtime)?;
+        return Ok((client, runtime));
+    }
+
+    let key = PoolKey::new(pool_options.scope, &options);
+    if let Some(cv) = reuse_client(&key, &options, pool_options.cp_match) {
+        return Ok(cv);
+    }
+    // The Client is created with the pool unlocked, so that other connections are not held up.
+    log::info!("creating new Client");
+    let client = create_client(options.clone(), &runtime)?;
+    let mut pool = CLIENT_POOL.lock().unwrap();
+    // Another connection may have pooled a matching Client in the meantime.
+    if let Some(cv) = pool.get(&key, &options, pool_options.cp_match) {
+        log::info!("reusing Client, {:?}", pool.metrics());
+        drop(pool);
+        runtime.block_on(async { client.shutdown().await });
+        return Ok(cv);
+    }
+    pool.metrics.misses += 1;
+    let (pooled, evicted) = pool.insert(
+        key,
+        PooledClient {
+            options,
+            client: client.clone(),
+            runtime: runtime.clone(),
+            last_used: Instant::now(),
+            idle_timeout: pool_options.idle_timeout,
+        },
+        pool_options.max_clients,
+    );
+    if !pooled {
+        log::warn!(
+            "the Client is not pooled, the {} pooled Clients are in use",
+            pool_options.max_clients
+        );
+    }
+    let exited_reaper = start_reaper(&mut pool);
+    log::info!("{:?}", pool.metrics());
+    // Shutting the evicted Client down waits on its Runtime, so the pool is unlocked first.
+    drop(pool);
+    if let Some(evicted) = evicted {
+        evicted.shutdown();
+    }
+    if let Some(exited_reaper) = exited_reaper {
+        exited_reaper.stop();
+    }
+    Ok((client, runtime))
+}
+
+// reuse_client returns a pooled Client matching the options, if any.
+fn reuse_client(
+    key: &PoolKey,
+    options: &UserOptions,
+    cp_match: AttrCpMatch,
+) -> Option<(Client, Arc<Runtime>)> {
+    let mut pool = CLIENT_POOL.lock().unwrap();
+    let cv = pool.get(key, options, cp_match)?;
+    log::info!("reusing Client, {:?}", pool.metrics());
+    Some(cv)
+}
+
+/// Marks the pooled Client running on the Runtime of a closed connection as used now. With the
+/// garbage_collect feature, or when no environment is left, the Clients no connection uses are
+/// shut down right away instead of after their idle timeout. The Runtime of the connection has to
+/// be dropped before.
+pub(crate) fn release_client(runtime: Arc<Runtime>) {
+    let mut pool = CLIENT_POOL.lock().unwrap();
+    pool.release(&runtime);
+    drop(runtime);
+    let idle = pool.remove_released();
+    drop(pool);
+    idle.into_iter().for_each(PooledClient::shutdown);
+}
+
+/// Counts an environment which is allocated.
+pub fn env_allocated() {
+    CLIENT_POOL.lock().unwrap().environments += 1;
+}
+
+/// Shuts down the idle Clients pooled for the environment with the given id, which is freed.
+/// When it is the last environment, all the idle Clients are shut down and the reaper thread is
+/// stopped, so that no thread is left behind when the driver is unloaded.
+pub fn env_freed(env_id: usize) {
+    let mut pool = CLIENT_POOL.lock().unwrap();
+    pool.environments = pool.environments.saturating_sub(1);
+    let last_env = pool.environments == 0;
+    let removed = pool.remove(|key, pooled| {
+        (last_env || key.scope == PoolScope::Env(env_id)) && !pooled.in_use()
+    });
+    let reaper = if last_env {
+        pool.reaper_running = false;
+        pool.reaper.take()
+    } else {
+        None
+    };
+    drop(pool);
+    removed.into_iter().for_each(PooledClient::shutdown);
+    if let Some(reaper) = reaper {
+        reaper.stop();
+    }
+}
+
+/// Returns the metrics of the Client pool.
+pub fn pool_metrics() -> PoolMetrics {
+    CLIENT_POOL.lock().unwrap().metrics()
+}
+
+// start_reaper starts the thread evicting the idle Clients, unless it is running. The thread
+// exits once the pool is empty, or it is stopped when the last environment is freed. The previous
+// reaper, which exited, is returned to be joined once the pool is unlocked.
+fn start_reaper(pool: &mut ClientPool) -> Option<Reaper> {
+    if pool.reaper_running {
+        return None;
+    }
+    let (stop, stopped) = mpsc::channel();
+    match thread::Builder::new()
+        .name("mongo-odbc-client-pool".to_string())
+        .spawn(move || reap(stopped))
+    {
+        Ok(thread) => {
+            pool.reaper_running = true;
+            pool.reaper.replace(Reaper { stop, thread })
+        }
+        Err(e) => {
+            log::error!("starting the Client pool reaper failed: {e}");
+            None
+        }
+    }
+}
+
+fn reap(stopped: mpsc::Receiver<()>) {
+    loop {
+        if stopped.recv_timeout(REAP_INTERVAL) != Err(RecvTimeoutError::Timeout) {
+            return;
+        }
+        let (idle, empty) = {
+            let mut pool = CLIENT_POOL.lock().unwrap();
+            let idle = pool.remove_idle(Instant::now());
+            let empty = pool.clients.is_empty();
+            if empty {
+                pool.reaper_running = false;
+            }
+            if !idle.is_empty() {
+                log::info!("evicted {} idle Clients, {:?}", idle.len(), pool.metrics());
+            }
+            (idle, empty)
+        };
+        idle.into_iter().for_each(PooledClient::shutdown);
+        if empty {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use mongodb::options::ClientOptions;
+
+    fn user_options(host: &str, connect_timeout: Option<Duration>) -> UserOptions {
+        let mut client_options = ClientOptions::default();
+        client_options.hosts = vec![host.parse().unwrap()];
+        client_options.connect_timeout = connect_timeout;
+        UserOptions {
+            client_options,
+            uuid_representation: None,
+            oidc_notices: Default::default(),
+        }
+    }
+
+    fn runtime() -> Arc<Runtime> {
+        Arc::new(
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+        )
+    }
+
+    // pooled creates a Client, which does not connect until it is used.
+    fn pooled(options: &UserOptions, idle_timeout: Duration) -> PooledClient {
+        let runtime = runtime();
+        PooledClient {
+            options: options.clone(),
+            client: create_client(options.clone(), &runtime).unwrap(),
+            runtime,
+            last_used: Instant::now(),
+            idle_timeout,
+        }
+    }
+
+    #[test]
+    fn pool_options_from_env_attributes() {
+        assert_eq!(PoolOptions::default(), {
+            PoolOptions::new(
+                AttrConnectionPooling::SQL_CP_OFF,
+                AttrCpMatch::SQL_CP_STRICT_MATCH,
+                7,
+            )
+        });
+        assert_eq!(
+            PoolScope::Driver,
+            PoolOptions::new(
+                AttrConnectionPooling::SQL_CP_ONE_PER_DRIVER,
+                AttrCpMatch::SQL_CP_STRICT_MATCH,
+                7
+            )
+            .scope
+        );
+        for pooling in [
+            AttrConnectionPooling::SQL_CP_ONE_PER_HENV,
+            AttrConnectionPooling::SQL_CP_DRIVER_AWARE,
+        ] {
+            assert_eq!(
+                PoolScope::Env(7),
+                PoolOptions::new(pooling, AttrCpMatch::SQL_CP_RELAXED_MATCH, 7).scope
+            );
+        }
+    }
+
+    #[test]
+    fn strict_and_relaxed_matching() {
+        let mut pool = ClientPool::default();
+        let options = user_options("localhost:27017", Some(Duration::from_secs(30)));
+        let other_timeout = user_options("localhost:27017", Some(Duration::from_secs(5)));
+        let other_host = user_options("localhost:27018", Some(Duration::from_secs(30)));
+        let key = |options| PoolKey::new(PoolScope::Driver, options);
+
+        let (inserted, evicted) = pool.insert(
+            key(&options),
+            pooled(&options, DEFAULT_POOL_IDLE_TIMEOUT),
+            DEFAULT_MAX_POOLED_CLIENTS,
+        );
+        assert!(inserted && evicted.is_none());
+
+        let strict = AttrCpMatch::SQL_CP_STRICT_MATCH;
+        let relaxed = AttrCpMatch::SQL_CP_RELAXED_MATCH;
+        assert!(pool.get(&key(&options), &options, strict).is_some());
+        // The login timeout is a connection attribute, which only strict matching compares.
+        assert!(pool
+            .get(&key(&other_timeout), &other_timeout, strict)
+            .is_none());
+        let (_, runtime) = pool
+            .get(&key(&other_timeout), &other_timeout, relaxed)
+            .unwrap();
+        assert!(pool.get(&key(&other_host), &other_host, relaxed).is_none());
+        // The scope is part of the key.
+        assert!(pool
+            .get(
+                &PoolKey::new(PoolScope::Env(1), &options),
+                &options,
+                relaxed
+            )
+            .is_none());
+
+        assert_eq!(
+            PoolMetrics {
+                hits: 2,
+                misses: 0,
+                evictions: 0,
+                pooled_clients: 1,
+                clients_in_use: 1,
+            },
+            pool.metrics()
+        );
+        drop(runtime);
+        assert_eq!(0, pool.metrics().clients_in_use);
+    }
+
+    #[test]
+    fn max_clients_evicts_least_recently_used_idle_client() {
+        let mut pool = ClientPool::default();
+        let first = user_options("localhost:27017", None);
+        let second = user_options("localhost:27018", None);
+        let third = user_options("localhost:27019", None);
+        let key = |options| PoolKey::new(PoolScope::Driver, options);
+        let strict = AttrCpMatch::SQL_CP_STRICT_MATCH;
+
+        pool.insert(key(&first), pooled(&first, DEFAULT_POOL_IDLE_TIMEOUT), 2);
+        pool.insert(key(&second), pooled(&second, DEFAULT_POOL_IDLE_TIMEOUT), 2);
+        let (_, in_use) = pool.get(&key(&second), &second, strict).unwrap();
+
+        let (inserted, evicted) =
+            pool.insert(key(&third), pooled(&third, DEFAULT_POOL_IDLE_TIMEOUT), 2);
+        assert!(inserted);
+        assert_eq!(first, evicted.unwrap().options);
+        assert!(pool.get(&key(&first), &first, strict).is_none());
+        let (_, third_in_use) = pool.get(&key(&third), &third, strict).unwrap();
+
+        // All the pooled Clients are in use, so the new one is not pooled.
+        let (inserted, evicted) =
+            pool.insert(key(&first), pooled(&first, DEFAULT_POOL_IDLE_TIMEOUT), 2);
+        assert!(!inserted && evicted.is_none());
+        assert_eq!(2, pool.metrics().pooled_clients);
+        assert_eq!(1, pool.metrics().evictions);
+        drop((in_use, third_in_use));
+    }
+
+    #[test]
+    fn idle_clients_are_removed_after_their_idle_timeout() {
+        let mut pool = ClientPool::default();
+        let options = user_options("localhost:27017", None);
+        let key = PoolKey::new(PoolScope::Env(1), &options);
+        pool.insert(
+            key,
+            pooled(&options, Duration::from_secs(60)),
+            DEFAULT_MAX_POOLED_CLIENTS,
+        );
+        let key = PoolKey::new(PoolScope::Env(1), &options);
+        let (_, runtime) = pool
+            .get(&key, &options, AttrCpMatch::SQL_CP_STRICT_MATCH)
+            .unwrap();
+
+        let later = Instant::now() + Duration::from_secs(61);
+        // A Client in use is never idle.
+        assert!(pool.remove_idle(later).is_empty());
+        pool.release(&runtime);
+        drop(runtime);
+        assert!(pool.remove_idle(Instant::now()).is_empty());
+        let removed = pool.remove_idle(later);
+        assert_eq!(1, removed.len());
+        removed.into_iter().for_each(PooledClient::shutdown);
+        assert_eq!(
+            PoolMetrics {
+                hits: 1,
+                evictions: 1,
+                ..Default::default()
+            },
+            pool.metrics()
+        );
+    }
+
+    #[test]
+    fn released_clients_are_removed_without_environments() {
+        let mut pool = ClientPool::default();
+        let options = user_options("localhost:27017", None);
+        let key = |options| PoolKey::new(PoolScope::Driver, options);
+        let strict = AttrCpMatch::SQL_CP_STRICT_MATCH;
+        pool.insert(
+            key(&options),
+            pooled(&options, DEFAULT_POOL_IDLE_TIMEOUT),
+            DEFAULT_MAX_POOLED_CLIENTS,
+        );
+
+        // While an environment is allocated, the reaper evicts the idle Client.
+        pool.environments = 1;
+        let (_, runtime) = pool.get(&key(&options), &options, strict).unwrap();
+        pool.release(&runtime);
+        drop(runtime);
+        if !cfg!(feature = "garbage_collect") {
+            assert!(pool.remove_released().is_empty());
+        }
+
+        // The reaper is stopped once the last environment is freed, so the Client is removed
+        // when its last connection is closed.
+        pool.environments = 0;
+        let (_, runtime) = pool.get(&key(&options), &options, strict).unwrap();
+        assert!(pool.remove_released().is_empty());
+        pool.release(&runtime);
+        drop(runtime);
+        let removed = pool.remove_released();
+        assert_eq!(1, removed.len());
+        removed.into_iter().for_each(PooledClient::shutdown);
+        assert_eq!(0, pool.metrics().pooled_clients);
+    }
+
+    #[test]
+    fn reaper_is_started_once_and_stopped() {
+        let mut pool = ClientPool::default();
+        assert!(start_reaper(&mut pool).is_none());
+        assert!(pool.reaper_running);
+        assert!(start_reaper(&mut pool).is_none());
+        // Stopping the reaper does not wait for its next eviction pass.
+        let stopping = Instant::now();
+        pool.reaper.take().unwrap().stop();
+        assert!(stopping.elapsed() < REAP_INTERVAL);
+    }
+}
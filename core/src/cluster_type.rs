@@ -1,4 +1,4 @@
-use crate::{err::Result, Error};
+use crate::{err::Result, oidc_auth, Error};
 use mongodb::bson::{doc, Bson, Document};
 use mongodb::Client;
 use serde::{Deserialize, Serialize};
@@ -17,10 +17,16 @@ pub async fn determine_cluster_type(client: &Client) -> Result<MongoClusterType>
     // The { buildInfo: 1 } command returns information that indicates
     // the type of the cluster.
     let build_info_cmd = doc! { "buildInfo": 1 };
-    let cmd_res: Document = db
-        .run_command(build_info_cmd)
-        .await
-        .map_err(Error::BuildInfoCmdExecutionFailed)?;
+    // This is the first command of a new Client, so it is the one that fails when the
+    // authentication does.
+    let cmd_res: Document = db.run_command(build_info_cmd).await.map_err(|e| match e
+        .get_custom::<oidc_auth::Error>(
+    ) {
+        Some(oidc_auth::Error::RedirectServer(message)) => {
+            Error::OidcRedirectServer(message.clone())
+        }
+        _ => Error::BuildInfoCmdExecutionFailed(e),
+    })?;
 
     // if "ok" is not 1, then the target type could not be determined.
     match cmd_res.get("ok") {
@@ -1,15 +1,19 @@
 use crate::{
+    cluster_type::MongoClusterType,
+    conn::MongoConnection,
     json_schema::{
         simplified::{Atomic, ObjectSchema, Schema},
         BsonTypeName,
     },
     BsonTypeInfo, Error, Result, TypeMode,
 };
-use bson::{Bson, Document};
+use bson::{doc, Bson, Document};
+use constants::SQL_SCHEMAS_COLLECTION;
 use definitions::{Nullability, SqlCode, SqlDataType};
 use itertools::Itertools;
+use mongodb::Database;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 // Metadata information for a column of the result set.
 // The information is to be used when reporting columns information from
@@ -52,6 +56,11 @@ pub struct MongoColMetadata {
     pub sql_code: Option<SqlCode>,
     pub is_unsigned: bool,
     pub is_updatable: bool,
+    // The path of the field in its datasource document. This is the column name, except for
+    // the dotted columns of flattened nested documents, which have one element per level.
+    pub field_path: Vec<String>,
+    // The REMARKS reported by SQLColumns. Polymorphic columns list the BSON types of the field.
+    pub remarks: String,
 }
 
 impl MongoColMetadata {
@@ -89,6 +98,8 @@ impl MongoColMetadata {
             decimal_digits: bson_type_info.decimal_digit(type_mode),
             fixed_prec_scale: bson_type_info.fixed_prec_scale,
             label: field_name.clone(),
+            field_path: vec![field_name.clone()],
+            remarks: "".to_string(),
             length: bson_type_info.length(type_mode, max_string_length),
             literal_prefix: bson_type_info.literal_prefix,
             literal_suffix: bson_type_info.literal_suffix,
@@ -150,6 +161,100 @@ impl MongoColMetadata {
             nullability,
         )
     }
+
+    /// Returns the value of this column in the given datasource document, following the field
+    /// path through nested documents.
+    pub fn get_field<'a>(&self, datasource: &'a Document) -> Option<&'a Bson> {
+        let (last, parents) = self.field_path.split_last()?;
+        parents
+            .iter()
+            .try_fold(datasource, |doc, name| doc.get_document(name).ok())?
+            .get(last)
+    }
+}
+
+/// PolymorphicColumns is how fields with several BSON types (an anyOf schema other than a single
+/// type and null) are reported. It comes from the polymorphic_columns connection string/DSN
+/// option. Values which cannot be converted to the reported type are an error for their row only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PolymorphicColumns {
+    /// The column is a SQL_WVARCHAR holding the JSON of each value.
+    #[default]
+    Json,
+    /// Fields holding only numbers are reported with the widest of their numeric types
+    /// (int < long < double < decimal), the others as with Json.
+    Widest,
+}
+
+impl std::str::FromStr for PolymorphicColumns {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(PolymorphicColumns::Json),
+            "widest" => Ok(PolymorphicColumns::Widest),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid polymorphic_columns value '{s}', expected 'json' or 'widest'"
+            ))),
+        }
+    }
+}
+
+impl PolymorphicColumns {
+    // Returns whether a field with the given anyOf has more than one type besides null.
+    fn is_polymorphic(any_of: &BTreeSet<Atomic>) -> bool {
+        Self::non_null_types(any_of).count() > 1
+    }
+
+    fn non_null_types(any_of: &BTreeSet<Atomic>) -> impl Iterator<Item = &Atomic> {
+        any_of.iter().filter(|atomic| {
+            !matches!(
+                atomic,
+                Atomic::Scalar(BsonTypeName::Null) | Atomic::Scalar(BsonTypeName::Undefined)
+            )
+        })
+    }
+
+    // Returns the schema and the type mode a polymorphic column is reported with.
+    fn column_schema(&self, any_of: &BTreeSet<Atomic>, type_mode: TypeMode) -> (Schema, TypeMode) {
+        if *self == PolymorphicColumns::Widest {
+            let widest = Self::non_null_types(any_of)
+                .map(|atomic| match atomic {
+                    Atomic::Scalar(BsonTypeName::Int) => Some((0, BsonTypeName::Int)),
+                    Atomic::Scalar(BsonTypeName::Long) => Some((1, BsonTypeName::Long)),
+                    Atomic::Scalar(BsonTypeName::Double) => Some((2, BsonTypeName::Double)),
+                    Atomic::Scalar(BsonTypeName::Decimal) => Some((3, BsonTypeName::Decimal)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .and_then(|types| types.into_iter().max());
+            if let Some((_, widest)) = widest {
+                return (Schema::Atomic(Atomic::Scalar(widest)), type_mode);
+            }
+        }
+        // Simple types report a field of any type as a SQL_WVARCHAR of its JSON.
+        (
+            Schema::Atomic(Atomic::Scalar(BsonTypeName::Any)),
+            if type_mode.is_simple() {
+                type_mode
+            } else {
+                TypeMode::Simple
+            },
+        )
+    }
+
+    // Returns the REMARKS of a polymorphic column, listing the types of the field.
+    fn remarks(any_of: &BTreeSet<Atomic>) -> String {
+        format!(
+            "polymorphic column of types: {}",
+            any_of
+                .iter()
+                .map(|atomic| BsonTypeInfo::from(atomic.clone()).type_name)
+                .sorted()
+                .dedup()
+                .join(", ")
+        )
+    }
 }
 
 // Struct representing the response for a sqlGetResultSchema command.
@@ -185,6 +290,43 @@ impl ResultSetSchema {
         let deserializer = serde_stacker::Deserializer::new(deserializer);
         Deserialize::deserialize(deserializer)
     }
+
+    /// Gets the schema of a collection, with sqlGetSchema on Atlas Data Federation and from the
+    /// __sql_schemas collection on Enterprise. A schema which cannot be deserialized is reported
+    /// as a CollectionDeserialization error.
+    pub(crate) async fn for_collection(
+        mongo_connection: &MongoConnection,
+        db: &Database,
+        collection_name: &str,
+    ) -> Result<Self> {
+        match mongo_connection.cluster_type {
+            MongoClusterType::AtlasDataFederation => {
+                let get_schema_cmd = doc! {"sqlGetSchema": collection_name};
+                let sql_get_schema_response: SqlGetSchemaResponse = bson::from_document(
+                    db.run_command(get_schema_cmd)
+                        .await
+                        .map_err(Error::QueryExecutionFailed)?,
+                )
+                .map_err(|e| Error::CollectionDeserialization(collection_name.to_string(), e))?;
+                Ok(sql_get_schema_response.into())
+            }
+            MongoClusterType::Enterprise => {
+                let schema_doc: Document = db
+                    .collection::<Document>(SQL_SCHEMAS_COLLECTION)
+                    .find_one(doc! {
+                        "_id": collection_name
+                    })
+                    .await
+                    .map_err(Error::QueryExecutionFailed)?
+                    .ok_or(Error::SchemaDocumentNotFoundInSchemaCollection(vec![
+                        collection_name.to_string(),
+                    ]))?;
+                ResultSetSchema::from_sql_schemas_document(&schema_doc)
+                    .map_err(|e| Error::CollectionDeserialization(collection_name.to_string(), e))
+            }
+            MongoClusterType::Community | MongoClusterType::UnknownTarget => unreachable!(),
+        }
+    }
 }
 
 impl From<SqlGetSchemaResponse> for ResultSetSchema {
@@ -223,22 +365,24 @@ impl ResultSetSchema {
         current_db: &str,
         type_mode: TypeMode,
         max_string_length: Option<u16>,
+        polymorphic_columns: PolymorphicColumns,
     ) -> Result<Vec<MongoColMetadata>> {
         let result_set_schema: crate::json_schema::simplified::Schema =
             self.schema.clone().try_into()?;
         let result_set_object_schema = result_set_schema.assert_object_schema()?;
 
         // create a map from the naming convention used by select order ([datasource name, column name]),
-        // to the schema
-        let mut processed_result_set_metadata: HashMap<Vec<String>, MongoColMetadata> =
+        // to the metadata of that column. A column holds several metadata when it is a flattened
+        // nested document, one per dotted column.
+        let mut processed_result_set_metadata: HashMap<Vec<String>, Vec<MongoColMetadata>> =
             result_set_object_schema
                 .clone()
                 // 1. Access result_set_schema.properties and turn into an iterator
                 .properties
                 .into_iter()
                 // 2. for each datasource, convert the schema to column metadata. Then,
-                //    turn the resulting vector of metadata into key-value pairs for the
-                //    metadata map we are creating.
+                //    group the resulting vector of metadata by top-level field into
+                //    key-value pairs for the metadata map we are creating.
                 .map(|(datasource_name, datasource_schema)| {
                     let schema = Self::schema_to_col_metadata(
                         &datasource_schema,
@@ -246,25 +390,27 @@ impl ResultSetSchema {
                         &datasource_name,
                         type_mode,
                         max_string_length,
+                        polymorphic_columns,
                     )?;
                     Ok(schema
                         .into_iter()
-                        .map(|col| (vec![col.table_name.clone(), col.col_name.clone()], col)))
+                        .chunk_by(|col| vec![col.table_name.clone(), col.field_path[0].clone()])
+                        .into_iter()
+                        .map(|(key, cols)| (key, cols.collect::<Vec<_>>()))
+                        .collect::<Vec<_>>())
                 })
                 // flatten the key-value pairs representing the metadata into a single vector,
                 // then finally convert to a HashMap
                 .flatten_ok()
-                .collect::<Result<HashMap<Vec<String>, MongoColMetadata>>>()?;
+                .collect::<Result<HashMap<Vec<String>, Vec<MongoColMetadata>>>>()?;
 
         Ok(
             // the select list order is None or empty, for example if using an older adf version, sort by column name
             if self.select_order.is_none() || self.select_order.as_ref().unwrap().is_empty() {
                 processed_result_set_metadata
-                    .into_values()
-                    .sorted_by(|a, b| match Ord::cmp(&a.table_name, &b.table_name) {
-                        core::cmp::Ordering::Equal => Ord::cmp(&a.col_name, &b.col_name),
-                        v => v,
-                    })
+                    .into_iter()
+                    .sorted_by(|a, b| Ord::cmp(&a.0, &b.0))
+                    .flat_map(|(_, cols)| cols)
                     .collect()
             } else {
                 // given a select order, convert the values of the map into an ordered vector
@@ -275,6 +421,7 @@ impl ResultSetSchema {
                     .map(|key| {
                         remove_and_return_field_if_exist(&mut processed_result_set_metadata, key)
                     })
+                    .flatten_ok()
                     .collect::<Result<Vec<_>>>()?
             },
         )
@@ -303,6 +450,7 @@ impl ResultSetSchema {
         current_collection: &str,
         type_mode: TypeMode,
         max_string_length: Option<u16>,
+        polymorphic_columns: PolymorphicColumns,
     ) -> Result<Vec<MongoColMetadata>> {
         let collection_schema: crate::json_schema::simplified::Schema =
             self.schema.clone().try_into()?;
@@ -312,54 +460,141 @@ impl ResultSetSchema {
             current_collection,
             type_mode,
             max_string_length,
+            polymorphic_columns,
         )
     }
 
     // Helper function that asserts the passed object_schema is actually an ObjectSchema
     // (required), and then converts all the propety schemata of the properties into a
     // Result<Vec<MongoColMetadata>>, one MongoColMetadata per property schema in lexicographical
-    // order.
-    fn schema_to_col_metadata(
+    // order. In flatten mode, the properties of nested documents are expanded in place into
+    // dotted columns, up to the flatten depth. Polymorphic properties are reported according to
+    // polymorphic_columns.
+    pub(crate) fn schema_to_col_metadata(
         object_schema: &crate::json_schema::simplified::Schema,
         current_db: &str,
         current_collection: &str,
         type_mode: TypeMode,
         max_string_length: Option<u16>,
+        polymorphic_columns: PolymorphicColumns,
     ) -> Result<Vec<MongoColMetadata>> {
         let object_schema = object_schema.assert_object_schema()?;
+        let mut columns = vec![];
+        Self::object_schema_to_col_metadata(
+            object_schema,
+            &[],
+            Nullability::SQL_NO_NULLS,
+            type_mode.flatten_depth(),
+            current_db,
+            current_collection,
+            type_mode,
+            max_string_length,
+            polymorphic_columns,
+            &mut columns,
+        )?;
+        Ok(columns)
+    }
 
-        object_schema
-            // 1. Access object_schema.properties and sort alphabetically.
-            //    This means we are sorting by field name. This is necessary
-            //    because this defines our ordinal positions.
+    // Appends the column metadata of the properties of object_schema, a document found at
+    // parent_path, to columns. Properties which are documents themselves are expanded while
+    // depth allows it. A column under a nullable document is nullable.
+    #[allow(clippy::too_many_arguments)]
+    fn object_schema_to_col_metadata(
+        object_schema: &ObjectSchema,
+        parent_path: &[String],
+        parent_nullability: Nullability,
+        depth: u16,
+        current_db: &str,
+        current_collection: &str,
+        type_mode: TypeMode,
+        max_string_length: Option<u16>,
+        polymorphic_columns: PolymorphicColumns,
+        columns: &mut Vec<MongoColMetadata>,
+    ) -> Result<()> {
+        // 1. Access object_schema.properties and sort alphabetically.
+        //    This means we are sorting by field name. This is necessary
+        //    because this defines our ordinal positions.
+        for (name, schema) in object_schema
             .properties
             .clone()
             .into_iter()
             .sorted_by(|a, b| Ord::cmp(&a.0, &b.0))
-            // 2. Map each field into a MongoColMetadata.
-            .map(|(name, schema)| {
-                let field_nullability = object_schema.get_field_nullability(name.clone())?;
-
-                Ok(MongoColMetadata::new(
-                    current_db,
-                    current_collection.to_string(),
-                    name,
-                    schema,
-                    field_nullability,
-                    type_mode,
-                    max_string_length,
-                ))
-            })
-            .collect::<Result<Vec<_>>>()
+        {
+            let field_nullability = match (
+                parent_nullability,
+                object_schema.get_field_nullability(name.clone())?,
+            ) {
+                (Nullability::SQL_NO_NULLS, nullability) => nullability,
+                (_, Nullability::SQL_NO_NULLS) => Nullability::SQL_NULLABLE,
+                (_, nullability) => nullability,
+            };
+            let field_path = parent_path
+                .iter()
+                .cloned()
+                .chain(std::iter::once(name))
+                .collect::<Vec<_>>();
+
+            match schema {
+                // 2. Expand nested documents with known properties into their fields.
+                Schema::Atomic(Atomic::Object(nested))
+                    if depth > 0 && !nested.properties.is_empty() =>
+                {
+                    Self::object_schema_to_col_metadata(
+                        &nested,
+                        &field_path,
+                        field_nullability,
+                        depth - 1,
+                        current_db,
+                        current_collection,
+                        type_mode,
+                        max_string_length,
+                        polymorphic_columns,
+                        columns,
+                    )?;
+                }
+                // 3. Map fields with several types according to the polymorphic column policy.
+                Schema::AnyOf(any_of) if PolymorphicColumns::is_polymorphic(&any_of) => {
+                    let (schema, column_type_mode) =
+                        polymorphic_columns.column_schema(&any_of, type_mode);
+                    columns.push(MongoColMetadata {
+                        field_path: field_path.clone(),
+                        remarks: PolymorphicColumns::remarks(&any_of),
+                        ..MongoColMetadata::new(
+                            current_db,
+                            current_collection.to_string(),
+                            field_path.join("."),
+                            schema,
+                            field_nullability,
+                            column_type_mode,
+                            max_string_length,
+                        )
+                    })
+                }
+                // 4. Map every other field into a MongoColMetadata.
+                schema => columns.push(MongoColMetadata {
+                    field_path: field_path.clone(),
+                    ..MongoColMetadata::new(
+                        current_db,
+                        current_collection.to_string(),
+                        field_path.join("."),
+                        schema,
+                        field_nullability,
+                        type_mode,
+                        max_string_length,
+                    )
+                }),
+            }
+        }
+        Ok(())
     }
 }
 
 fn remove_and_return_field_if_exist(
-    processed_result_set_metadata: &mut HashMap<Vec<String>, MongoColMetadata>,
+    processed_result_set_metadata: &mut HashMap<Vec<String>, Vec<MongoColMetadata>>,
     key: &Vec<String>,
-) -> Result<MongoColMetadata> {
+) -> Result<Vec<MongoColMetadata>> {
     match processed_result_set_metadata.remove(key) {
-        Some(metadata) => Ok(metadata.clone()),
+        Some(metadata) => Ok(metadata),
         None => Err(Error::MetadataAccess(
             key.first()
                 .unwrap_or(&"unspecified datasource".to_string())
@@ -440,10 +675,12 @@ impl ObjectSchema {
 mod unit {
     mod process_metadata {
         use crate::{
-            col_metadata::ResultSetSchema,
+            col_metadata::{MongoColMetadata, PolymorphicColumns, ResultSetSchema},
             json_schema::{BsonType, BsonTypeName, Schema},
             map, Error, TypeMode,
         };
+        use bson::{doc, Bson};
+        use definitions::{Nullability, SqlDataType};
 
         #[test]
         fn top_level_schema_not_object() {
@@ -455,7 +692,12 @@ mod unit {
                 select_order: Some(vec![]),
             };
 
-            let actual = input.process_result_metadata("test_db", TypeMode::Standard, None);
+            let actual = input.process_result_metadata(
+                "test_db",
+                TypeMode::Standard,
+                None,
+                PolymorphicColumns::Json,
+            );
 
             match actual {
                 Err(Error::InvalidResultSetJsonSchema(_)) => (),
@@ -487,7 +729,12 @@ mod unit {
             };
 
             let schema = input
-                .process_result_metadata("test_db", TypeMode::Standard, None)
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Standard,
+                    None,
+                    PolymorphicColumns::Json,
+                )
                 .unwrap();
             assert_eq!(
                 schema.first().unwrap().sql_type,
@@ -518,7 +765,12 @@ mod unit {
             };
 
             let schema = input
-                .process_result_metadata("test_db", TypeMode::Simple, None)
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Simple,
+                    None,
+                    PolymorphicColumns::Json,
+                )
                 .unwrap();
             assert_eq!(
                 schema.first().unwrap().sql_type,
@@ -542,7 +794,12 @@ mod unit {
                 select_order: Some(vec![]),
             };
 
-            let actual = input.process_result_metadata("test_db", TypeMode::Standard, None);
+            let actual = input.process_result_metadata(
+                "test_db",
+                TypeMode::Standard,
+                None,
+                PolymorphicColumns::Json,
+            );
 
             match actual {
                 Err(Error::InvalidResultSetJsonSchema(_)) => (),
@@ -552,7 +809,12 @@ mod unit {
         }
 
         fn validate_fields_order(input: ResultSetSchema, expected_output: Vec<(i32, &str, &str)>) {
-            let res = input.process_result_metadata("test_db", TypeMode::Standard, None);
+            let res = input.process_result_metadata(
+                "test_db",
+                TypeMode::Standard,
+                None,
+                PolymorphicColumns::Json,
+            );
 
             match res {
                 Err(e) => panic!("unexpected error: {e:?}"),
@@ -738,7 +1000,12 @@ mod unit {
                 ]),
             };
 
-            let res = input.process_result_metadata("test_db", TypeMode::Standard, None);
+            let res = input.process_result_metadata(
+                "test_db",
+                TypeMode::Standard,
+                None,
+                PolymorphicColumns::Json,
+            );
 
             match res {
                 Err(Error::MetadataAccess(_, _)) => (),
@@ -746,6 +1013,274 @@ mod unit {
                 Ok(ok) => panic!("unexpected result: {ok:?}"),
             }
         }
+
+        fn nested_documents_schema() -> ResultSetSchema {
+            let scalar = |name| Schema {
+                bson_type: Some(BsonType::Single(name)),
+                ..Default::default()
+            };
+            ResultSetSchema {
+                schema: Schema {
+                    bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                    properties: Some(map! {
+                        "foo".to_string() => Schema {
+                            bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                            properties: Some(map! {
+                                "name".to_string() => scalar(BsonTypeName::String),
+                                "address".to_string() => Schema {
+                                    bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                                    properties: Some(map! {
+                                        "zip".to_string() => scalar(BsonTypeName::Int),
+                                        "city".to_string() => scalar(BsonTypeName::String),
+                                        "geo".to_string() => Schema {
+                                            bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                                            properties: Some(map! {
+                                                "lat".to_string() => scalar(BsonTypeName::Double),
+                                            }),
+                                            required: Some(vec!["lat".to_string()]),
+                                            ..Default::default()
+                                        },
+                                    }),
+                                    required: Some(vec!["city".to_string(), "geo".to_string()]),
+                                    ..Default::default()
+                                },
+                            }),
+                            required: Some(vec!["name".to_string(), "address".to_string()]),
+                            ..Default::default()
+                        },
+                    }),
+                    ..Default::default()
+                },
+                select_order: Some(vec![
+                    vec!["foo".to_string(), "name".to_string()],
+                    vec!["foo".to_string(), "address".to_string()],
+                ]),
+            }
+        }
+
+        fn col_names(columns: &[MongoColMetadata]) -> Vec<&str> {
+            columns.iter().map(|col| col.col_name.as_str()).collect()
+        }
+
+        #[test]
+        fn nested_documents_are_single_columns_without_flatten() {
+            let columns = nested_documents_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Simple,
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(vec!["name", "address"], col_names(&columns));
+        }
+
+        #[test]
+        fn nested_documents_are_flattened_in_select_order() {
+            let columns = nested_documents_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(1),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(
+                vec!["name", "address.city", "address.geo", "address.zip"],
+                col_names(&columns)
+            );
+            assert_eq!(
+                vec!["address".to_string(), "city".to_string()],
+                columns[1].field_path
+            );
+            assert_eq!(SqlDataType::SQL_WVARCHAR, columns[2].sql_type);
+
+            let columns = nested_documents_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(5),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(
+                vec!["name", "address.city", "address.geo.lat", "address.zip"],
+                col_names(&columns)
+            );
+        }
+
+        #[test]
+        fn nested_documents_are_flattened_without_select_order() {
+            let input = ResultSetSchema {
+                select_order: None,
+                ..nested_documents_schema()
+            };
+            let columns = input
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(5),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(
+                vec!["address.city", "address.geo.lat", "address.zip", "name"],
+                col_names(&columns)
+            );
+        }
+
+        #[test]
+        fn flattened_columns_are_nullable_when_a_parent_is() {
+            let input = ResultSetSchema {
+                schema: Schema {
+                    bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                    properties: Some(map! {
+                        "foo".to_string() => Schema {
+                            bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                            properties: Some(map! {
+                                "address".to_string() => Schema {
+                                    bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                                    properties: Some(map! {
+                                        "city".to_string() => Schema {
+                                            bson_type: Some(BsonType::Single(BsonTypeName::String)),
+                                            ..Default::default()
+                                        },
+                                    }),
+                                    required: Some(vec!["city".to_string()]),
+                                    ..Default::default()
+                                },
+                            }),
+                            ..Default::default()
+                        },
+                    }),
+                    ..Default::default()
+                },
+                select_order: None,
+            };
+            let columns = input
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(1),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(Nullability::SQL_NULLABLE, columns[0].nullability);
+
+            let columns = nested_documents_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(5),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert!(columns
+                .iter()
+                .filter(|col| col.col_name != "address.zip")
+                .all(|col| col.nullability == Nullability::SQL_NO_NULLS));
+        }
+
+        #[test]
+        fn get_field_follows_field_path() {
+            let columns = nested_documents_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Flatten(5),
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            let datasource = doc! {"name": "a", "address": {"city": "NYC", "geo": {"lat": 40.7}}};
+            assert_eq!(
+                Some(&Bson::String("a".to_string())),
+                columns[0].get_field(&datasource)
+            );
+            assert_eq!(Some(&Bson::Double(40.7)), columns[2].get_field(&datasource));
+            assert_eq!(None, columns[3].get_field(&datasource));
+            assert_eq!(None, columns[1].get_field(&doc! {"address": "NYC"}));
+        }
+
+        fn polymorphic_schema() -> ResultSetSchema {
+            let types = |names: Vec<BsonTypeName>| Schema {
+                bson_type: Some(BsonType::Multiple(names)),
+                ..Default::default()
+            };
+            ResultSetSchema {
+                schema: Schema {
+                    bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                    properties: Some(map! {
+                        "foo".to_string() => Schema {
+                            bson_type: Some(BsonType::Single(BsonTypeName::Object)),
+                            properties: Some(map! {
+                                "amount".to_string() => types(vec![BsonTypeName::Int, BsonTypeName::Long, BsonTypeName::Double, BsonTypeName::Null]),
+                                "code".to_string() => types(vec![BsonTypeName::Int, BsonTypeName::String]),
+                                "count".to_string() => types(vec![BsonTypeName::Int, BsonTypeName::Null]),
+                            }),
+                            required: Some(vec!["amount".to_string(), "code".to_string()]),
+                            ..Default::default()
+                        },
+                    }),
+                    ..Default::default()
+                },
+                select_order: None,
+            }
+        }
+
+        #[test]
+        fn polymorphic_columns_are_json() {
+            let columns = polymorphic_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Standard,
+                    None,
+                    PolymorphicColumns::Json,
+                )
+                .unwrap();
+            assert_eq!(vec!["amount", "code", "count"], col_names(&columns));
+            assert_eq!(SqlDataType::SQL_WVARCHAR, columns[0].sql_type);
+            assert_eq!(Nullability::SQL_NULLABLE, columns[0].nullability);
+            assert_eq!(
+                "polymorphic column of types: double, int, long, null",
+                columns[0].remarks
+            );
+            assert_eq!(SqlDataType::SQL_WVARCHAR, columns[1].sql_type);
+            assert_eq!(Nullability::SQL_NO_NULLS, columns[1].nullability);
+            assert_eq!(
+                "polymorphic column of types: int, string",
+                columns[1].remarks
+            );
+            // A single type and null is not polymorphic
+            assert_eq!(SqlDataType::SQL_INTEGER, columns[2].sql_type);
+            assert_eq!("", columns[2].remarks);
+        }
+
+        #[test]
+        fn polymorphic_numeric_columns_are_widened() {
+            let columns = polymorphic_schema()
+                .process_result_metadata(
+                    "test_db",
+                    TypeMode::Standard,
+                    None,
+                    PolymorphicColumns::Widest,
+                )
+                .unwrap();
+            assert_eq!(SqlDataType::SQL_DOUBLE, columns[0].sql_type);
+            assert_eq!("double", columns[0].type_name);
+            assert_eq!(
+                "polymorphic column of types: double, int, long, null",
+                columns[0].remarks
+            );
+            assert_eq!(SqlDataType::SQL_WVARCHAR, columns[1].sql_type);
+            assert_eq!(SqlDataType::SQL_INTEGER, columns[2].sql_type);
+        }
+
+        #[test]
+        fn parse_polymorphic_columns() {
+            assert_eq!(PolymorphicColumns::Json, "JSON".parse().unwrap());
+            assert_eq!(PolymorphicColumns::Widest, "widest".parse().unwrap());
+            assert!("narrowest".parse::<PolymorphicColumns>().is_err());
+        }
     }
 
     mod object_schema {
@@ -1,23 +1,35 @@
+use crate::databases::DatabaseAs;
 use crate::stmt::EmptyStatement;
-use crate::util::{is_match, table_type_filter_to_vec, to_name_regex};
+use crate::util::{is_match, may_match_with_prefix, table_type_filter_to_vec, to_name_regex};
 use crate::{
-    col_metadata::MongoColMetadata,
+    array_tables::ArrayTable,
+    cluster_type::MongoClusterType,
+    col_metadata::{MongoColMetadata, ResultSetSchema},
     conn::MongoConnection,
     err::Result,
+    json_schema::simplified::Schema,
     stmt::MongoStatement,
-    util::{COLLECTION, TABLE, TIMESERIES},
+    util::{TableType, SYSTEM_COLLECTION_PREFIX},
     BsonTypeInfo, Error,
 };
+use constants::SQL_SCHEMAS_COLLECTION;
 use definitions::Nullability;
-use futures::future;
+use futures::{future, TryStreamExt};
 use mongodb::{
-    bson::{doc, Bson},
+    bson::{doc, Bson, Document},
+    error::ErrorKind,
     results::CollectionType,
+    Database,
 };
 use once_cell::sync::OnceCell;
 use regex::Regex;
+use std::{collections::HashMap, future::Future};
 
 static COLLECTIONS_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+static COLLECTIONS_AS_SCHEMAS_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+
+// Server error code returned when the user lacks the listCollections privilege.
+const UNAUTHORIZED_ERROR_CODE: i32 = 13;
 
 fn init_collections_metadata(max_string_length: Option<u16>) -> Vec<MongoColMetadata> {
     vec![
@@ -68,6 +80,8 @@ fn init_collections_metadata(max_string_length: Option<u16>) -> Vec<MongoColMeta
 pub(crate) struct MongoODBCCollectionSpecification {
     pub name: String,
     pub collection_type: CollectionType,
+    // Description of the collection reported in the REMARKS column of SQLTables.
+    pub remarks: Option<String>,
 }
 
 impl MongoODBCCollectionSpecification {
@@ -75,10 +89,226 @@ impl MongoODBCCollectionSpecification {
         Self {
             name,
             collection_type,
+            remarks: None,
+        }
+    }
+
+    // Build a specification from one of the documents returned in a listCollections cursor batch.
+    // When the full specification is available, the remarks are built from the time-series
+    // options and from the description of the $jsonSchema validator.
+    fn from_list_collections_document(doc: &Document) -> Result<Self> {
+        let name = doc
+            .get_str("name")
+            .map_err(|e| Error::ValueAccess("name".to_string(), e))?
+            .to_string();
+        let collection_type = match doc.get_str("type") {
+            Ok("view") => CollectionType::View,
+            Ok("timeseries") => CollectionType::Timeseries,
+            _ => CollectionType::Collection,
+        };
+        let options = doc.get_document("options").ok();
+
+        let mut remarks = vec![];
+        if let Some(timeseries) = options.and_then(|o| o.get_document("timeseries").ok()) {
+            let mut remark = "time-series collection".to_string();
+            if let Ok(time_field) = timeseries.get_str("timeField") {
+                remark.push_str(&format!(", timeField: {time_field}"));
+            }
+            if let Ok(meta_field) = timeseries.get_str("metaField") {
+                remark.push_str(&format!(", metaField: {meta_field}"));
+            }
+            remarks.push(remark);
+        }
+        if let Some(description) = options
+            .and_then(|o| o.get_document("validator").ok())
+            .and_then(|v| v.get_document("$jsonSchema").ok())
+            .and_then(|s| s.get_str("description").ok())
+        {
+            remarks.push(description.to_string());
+        }
+
+        Ok(Self {
+            name,
+            collection_type,
+            remarks: (!remarks.is_empty()).then(|| remarks.join("; ")),
+        })
+    }
+
+    // The TABLE_TYPE reported by SQLTables for this collection.
+    pub(crate) fn table_type(&self) -> TableType {
+        if self.name.starts_with(SYSTEM_COLLECTION_PREFIX) {
+            TableType::SystemTable
+        } else if self.collection_type == CollectionType::View {
+            TableType::View
+        } else {
+            TableType::Table
         }
     }
 }
 
+// Fill the remarks of the collections which have none with the description stored in their
+// __sql_schemas schema document, if any.
+async fn add_sql_schemas_remarks(
+    db: &Database,
+    collections: &mut [MongoODBCCollectionSpecification],
+) -> Result<()> {
+    let names: Vec<&str> = collections
+        .iter()
+        .filter(|collection| collection.remarks.is_none())
+        .map(|collection| collection.name.as_str())
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    let descriptions: HashMap<String, String> = db
+        .collection::<Document>(SQL_SCHEMAS_COLLECTION)
+        .find(doc! { "_id": { "$in": names } })
+        .projection(doc! { "schema.description": 1 })
+        .await
+        .map_err(Error::QueryExecutionFailed)?
+        .try_collect::<Vec<Document>>()
+        .await
+        .map_err(Error::QueryExecutionFailed)?
+        .iter()
+        .filter_map(|schema_doc| {
+            let name = schema_doc.get_str("_id").ok()?;
+            let description = schema_doc
+                .get_document("schema")
+                .ok()?
+                .get_str("description")
+                .ok()?;
+            Some((name.to_string(), description.to_string()))
+        })
+        .collect();
+    for collection in collections
+        .iter_mut()
+        .filter(|collection| collection.remarks.is_none())
+    {
+        collection.remarks = descriptions.get(&collection.name).cloned();
+    }
+    Ok(())
+}
+
+// Insert the array tables of each collection right after it. The collections whose schema
+// cannot be read have no array tables. The schema of a collection is only read when one of its
+// array tables, which are named after it, can match the collection name filter.
+async fn add_array_tables(
+    mongo_connection: &MongoConnection,
+    db: &Database,
+    collections: Vec<MongoODBCCollectionSpecification>,
+    collection_name_filter: &str,
+) -> Vec<MongoODBCCollectionSpecification> {
+    let mut with_array_tables = vec![];
+    for collection in collections {
+        if !may_match_with_prefix(&format!("{}_", collection.name), collection_name_filter) {
+            with_array_tables.push(collection);
+            continue;
+        }
+        let array_tables =
+            match ResultSetSchema::for_collection(mongo_connection, db, &collection.name)
+                .await
+                .and_then(|schema| Schema::try_from(schema.schema))
+            {
+                Ok(schema) => ArrayTable::from_collection_schema(&collection.name, &schema),
+                Err(e) => {
+                    log::error!("Error getting array tables for {}: {e}", collection.name);
+                    vec![]
+                }
+            };
+        with_array_tables.push(collection);
+        with_array_tables.extend(array_tables.into_iter().map(|table| {
+            MongoODBCCollectionSpecification {
+                remarks: Some(format!(
+                    "elements of the array {} of {}",
+                    table.path.join("."),
+                    table.collection
+                )),
+                ..MongoODBCCollectionSpecification::new(table.name, CollectionType::Collection)
+            }
+        }));
+    }
+    with_array_tables
+}
+
+// Run listCollections against the given database and iterate the whole cursor, not only its
+// first batch. Databases with many collections (or large collection options) return their
+// collections across several batches. With name_only, only the names and types are listed, which
+// is enough unless the options, like the validators the remarks come from, are read.
+pub(crate) async fn list_collection_specifications(
+    db: &Database,
+    name_only: bool,
+) -> Result<Vec<MongoODBCCollectionSpecification>> {
+    list_collection_specifications_with(
+        |command| async move { db.run_command(command).await },
+        name_only,
+    )
+    .await
+}
+
+// Request the full collection specifications first, unless name_only is set. Users without the
+// listCollections privilege can only list the names of the collections they are authorized on, so
+// fall back to nameOnly when the server rejects the full request.
+async fn list_collection_specifications_with<F, Fut>(
+    mut run_command: F,
+    name_only: bool,
+) -> Result<Vec<MongoODBCCollectionSpecification>>
+where
+    F: FnMut(Document) -> Fut,
+    Fut: Future<Output = mongodb::error::Result<Document>>,
+{
+    if name_only {
+        return iterate_list_collections_cursor(&mut run_command, true).await;
+    }
+    match iterate_list_collections_cursor(&mut run_command, false).await {
+        Err(Error::CollectionCursorUpdate(e)) if matches!(e.kind.as_ref(), ErrorKind::Command(c) if c.code == UNAUTHORIZED_ERROR_CODE) => {
+            iterate_list_collections_cursor(&mut run_command, true).await
+        }
+        result => result,
+    }
+}
+
+// Send listCollections through `run_command`, then keep issuing getMore until the server reports
+// a cursor id of 0. The command runner is a parameter so that the cursor handling can be tested
+// without a server.
+async fn iterate_list_collections_cursor<F, Fut>(
+    run_command: &mut F,
+    name_only: bool,
+) -> Result<Vec<MongoODBCCollectionSpecification>>
+where
+    F: FnMut(Document) -> Fut,
+    Fut: Future<Output = mongodb::error::Result<Document>>,
+{
+    let mut specifications = vec![];
+    let mut command =
+        doc! { "listCollections": 1, "nameOnly": name_only, "authorizedCollections": true};
+    let mut batch_name = "firstBatch";
+    loop {
+        let response = run_command(command)
+            .await
+            .map_err(Error::CollectionCursorUpdate)?;
+        let cursor = response
+            .get_document("cursor")
+            .map_err(|e| Error::ValueAccess("cursor".to_string(), e))?;
+        for spec in cursor
+            .get_array(batch_name)
+            .map_err(|e| Error::ValueAccess(format!("cursor.{batch_name}"), e))?
+        {
+            if let Some(spec) = spec.as_document() {
+                specifications
+                    .push(MongoODBCCollectionSpecification::from_list_collections_document(spec)?);
+            }
+        }
+        let cursor_id = cursor
+            .get_i64("id")
+            .map_err(|e| Error::ValueAccess("cursor.id".to_string(), e))?;
+        if cursor_id == 0 {
+            return Ok(specifications);
+        }
+        command = doc! { "getMore": cursor_id, "collection": "$cmd.listCollections" };
+        batch_name = "nextBatch";
+    }
+}
+
 #[derive(Debug)]
 struct CollectionsForDb {
     database_name: String,
@@ -96,13 +326,15 @@ pub struct MongoCollections {
     // List of CollectionsForDb for each db.
     collections_for_db_list: Vec<CollectionsForDb>,
     collection_name_filter: Option<Regex>,
-    table_types_filter: Option<Vec<CollectionType>>,
+    table_types_filter: Option<Vec<TableType>>,
+    // Whether databases are reported as catalogs or schemas.
+    database_as: DatabaseAs,
 }
 
 // Statement related to a SQLColumns call.
 impl MongoCollections {
-    // Create a new MongoStatement to list tables with the given database (catalogs) and collection
-    // (tables) names filters.
+    // Create a new MongoStatement to list tables with the given database (catalogs or schemas,
+    // depending on database_as) and collection (tables) names filters.
     // The query timeout comes from the statement attribute SQL_ATTR_QUERY_TIMEOUT. If there is a
     // timeout, the query must finish before the timeout or an error is returned.
     pub fn list_tables(
@@ -112,6 +344,7 @@ impl MongoCollections {
         collection_name_filter: &str,
         table_type: &str,
         accept_search_patterns: bool,
+        database_as: DatabaseAs,
     ) -> Self {
         let databases = mongo_connection.runtime.block_on(async {
             future::join_all(
@@ -122,30 +355,51 @@ impl MongoCollections {
                     .await
                     .unwrap()
                     .iter()
-                    // MHOUSE-7119 - admin database and empty strings are showing in list_database_names
-                    .filter(|&db_name| !db_name.is_empty() && !db_name.eq("admin"))
+                    .filter(|&db_name| mongo_connection.catalog_filter.is_database_visible(db_name))
                     .filter(|&db_name| is_match(db_name, db_name_filter, accept_search_patterns))
                     .map(|val| async move {
+                        let db = mongo_connection.client.database(val.as_str());
                         CollectionsForDb {
-                database_name: val.to_string(),
-                collection_list: mongo_connection.client.database(val.as_str()).run_command(
-                    doc! { "listCollections": 1, "nameOnly": true, "authorizedCollections": true},
-                ).await.unwrap().get_document("cursor").map(|doc| {
-                    doc.get_array("firstBatch").unwrap().iter().map(|val| {
-                        let doc = val.as_document().unwrap();
-                        let name = doc.get_str("name").unwrap().to_string();
-                        let collection_type = match doc.get_str("type").unwrap() {
-                            "collection" => CollectionType::Collection,
-                            "view" => CollectionType::View,
-                            _ => CollectionType::Collection
-                        };
-                        MongoODBCCollectionSpecification::new(name, collection_type)
-                    }).collect()
-                }).unwrap_or_else(|_| {
-                    log::error!("Error getting collections for db {}", val);
-                    vec![]
-                }),
-            }
+                            database_name: val.to_string(),
+                            collection_list: async {
+                                let mut collections: Vec<MongoODBCCollectionSpecification> =
+                                    list_collection_specifications(&db, false)
+                                        .await?
+                                        .into_iter()
+                                        .filter(|collection| {
+                                            mongo_connection
+                                                .catalog_filter
+                                                .is_collection_visible(&collection.name)
+                                        })
+                                        .collect();
+                                // The collections are still listed, without remarks, when
+                                // their descriptions cannot be read.
+                                if mongo_connection.cluster_type == MongoClusterType::Enterprise {
+                                    if let Err(e) =
+                                        add_sql_schemas_remarks(&db, &mut collections).await
+                                    {
+                                        log::warn!(
+                                            "Error getting collection remarks for db {val}: {e}"
+                                        );
+                                    }
+                                }
+                                if mongo_connection.array_tables {
+                                    collections = add_array_tables(
+                                        mongo_connection,
+                                        &db,
+                                        collections,
+                                        collection_name_filter,
+                                    )
+                                    .await;
+                                }
+                                Ok::<_, Error>(collections)
+                            }
+                            .await
+                            .unwrap_or_else(|e| {
+                                log::error!("Error getting collections for db {val}: {e}");
+                                vec![]
+                            }),
+                        }
                     }),
             )
             .await
@@ -158,6 +412,7 @@ impl MongoCollections {
             collections_for_db_list: databases,
             collection_name_filter: to_name_regex(collection_name_filter),
             table_types_filter: table_type_filter_to_vec(table_type),
+            database_as,
         }
     }
 
@@ -177,6 +432,7 @@ impl MongoCollections {
             collections_for_db_list: Vec::new(),
             table_types_filter: None,
             collection_name_filter: None,
+            database_as: DatabaseAs::Catalog,
         }
     }
 }
@@ -219,7 +475,7 @@ impl MongoStatement for MongoCollections {
                                 .table_types_filter
                                 .as_ref()
                                 .unwrap()
-                                .contains(&collection.collection_type))
+                                .contains(&collection.table_type()))
                             && (self.collection_name_filter.is_none()
                                 || self
                                     .collection_name_filter
@@ -256,41 +512,57 @@ impl MongoStatement for MongoCollections {
     // Fails if the first row as not been retrieved (next must be called at least once before getValue).
     fn get_value(&self, col_index: u16, _: Option<u16>) -> Result<Option<Bson>> {
         // The mapping for col_index <-> Value will be hard-coded and handled in this function
-        // 1-> current_collection_list.database_name
-        // 2-> Schema name; NULL as it is not applicable
+        // 1-> current_collection_list.database_name if databases are catalogs, NULL otherwise
+        // 2-> current_collection_list.database_name if databases are schemas, NULL otherwise
         // 3 -> current_collection.name
-        // 4 -> current_collection.collection_type
-        // 5 -> Remarks; NULL
+        // 4 -> current_collection.table_type
+        // 5 -> current_collection.remarks, or an empty string
         let return_val = match col_index {
-            1 => Bson::String(
-                self.collections_for_db_list
-                    .get(self.current_database_index.unwrap())
-                    .unwrap()
-                    .database_name
-                    .clone(),
-            ),
-            2 => Bson::Null,
-            3 => Bson::String(self.current_collection.as_ref().unwrap().name.clone()),
-            4 => {
-                let coll_type = format!(
-                    "{:?}",
-                    self.current_collection.as_ref().unwrap().collection_type
-                )
-                .to_lowercase();
-                match coll_type.as_str() {
-                    // Mapping 'collection' or 'timeseries' to 'TABLE'
-                    COLLECTION | TIMESERIES => Bson::String(TABLE.to_string()),
-                    _ => Bson::String(coll_type.to_uppercase()),
+            1 | 2 => {
+                let (catalog, schema) = self.database_as.catalog_and_schema(
+                    &self
+                        .collections_for_db_list
+                        .get(self.current_database_index.unwrap())
+                        .unwrap()
+                        .database_name,
+                );
+                if col_index == 1 {
+                    catalog
+                } else {
+                    schema
                 }
             }
-            5 => Bson::String("".to_string()),
+            3 => Bson::String(self.current_collection.as_ref().unwrap().name.clone()),
+            4 => Bson::String(
+                self.current_collection
+                    .as_ref()
+                    .unwrap()
+                    .table_type()
+                    .as_str()
+                    .to_string(),
+            ),
+            5 => Bson::String(
+                self.current_collection
+                    .as_ref()
+                    .unwrap()
+                    .remarks
+                    .clone()
+                    .unwrap_or_default(),
+            ),
             _ => return Err(Error::ColIndexOutOfBounds(col_index)),
         };
         Ok(Some(return_val))
     }
 
     fn get_resultset_metadata(&self, max_string_length: Option<u16>) -> &Vec<MongoColMetadata> {
-        COLLECTIONS_METADATA.get_or_init(|| init_collections_metadata(max_string_length))
+        match self.database_as {
+            DatabaseAs::Catalog => {
+                COLLECTIONS_METADATA.get_or_init(|| init_collections_metadata(max_string_length))
+            }
+            DatabaseAs::Schema => COLLECTIONS_AS_SCHEMAS_METADATA.get_or_init(|| {
+                DatabaseAs::Schema.adjust_metadata(init_collections_metadata(max_string_length))
+            }),
+        }
     }
 }
 
@@ -433,8 +705,8 @@ mod unit {
     #[cfg(test)]
     mod table_type {
         use crate::util::table_type_filter_to_vec;
+        use crate::util::TableType;
         use constants::SQL_ALL_TABLE_TYPES;
-        use mongodb::results::CollectionType;
 
         #[test]
         fn all_types() {
@@ -448,7 +720,7 @@ mod unit {
             assert!(filters_opt.is_some());
             let filters = filters_opt.unwrap();
             assert_eq!(filters.len(), 1);
-            assert!(filters.contains(&CollectionType::View));
+            assert!(filters.contains(&TableType::View));
         }
         #[test]
         fn table() {
@@ -456,7 +728,7 @@ mod unit {
             assert!(filters_opt.is_some());
             let filters = filters_opt.unwrap();
             assert_eq!(filters.len(), 1);
-            assert!(filters.contains(&CollectionType::Collection));
+            assert!(filters.contains(&TableType::Table));
         }
         #[test]
         fn view_table() {
@@ -464,8 +736,8 @@ mod unit {
             assert!(filters_opt.is_some());
             let filters = filters_opt.unwrap();
             assert_eq!(filters.len(), 2);
-            assert!(filters.contains(&CollectionType::Collection));
-            assert!(filters.contains(&CollectionType::View));
+            assert!(filters.contains(&TableType::Table));
+            assert!(filters.contains(&TableType::View));
         }
         #[test]
         fn some_not_supported() {
@@ -473,14 +745,189 @@ mod unit {
             assert!(filters_opt.is_some());
             let filters = filters_opt.unwrap();
             assert_eq!(filters.len(), 1);
-            assert!(filters.contains(&CollectionType::Collection));
+            assert!(filters.contains(&TableType::Table));
         }
         #[test]
         fn none_supported() {
-            let filters_opt = table_type_filter_to_vec("GLOBAL TEMPORARY, SYSTEM TABLE");
+            let filters_opt = table_type_filter_to_vec("GLOBAL TEMPORARY, SYNONYM");
             assert!(filters_opt.is_some());
             let filters = filters_opt.unwrap();
             assert!(filters.is_empty());
         }
     }
+
+    #[cfg(test)]
+    mod list_collections_cursor {
+        use crate::{collections::list_collection_specifications_with, util::TableType};
+        use mongodb::{
+            bson::{doc, Document},
+            error::{CommandError, ErrorKind},
+            results::CollectionType,
+        };
+        use std::{cell::RefCell, collections::VecDeque};
+
+        #[test]
+        fn all_batches_are_read() {
+            let mut responses: VecDeque<Document> = VecDeque::from([
+                doc! {"cursor": {"id": 42_i64, "ns": "db.$cmd.listCollections", "firstBatch": [
+                    {"name": "a", "type": "collection"},
+                    {"name": "b", "type": "view"},
+                ]}, "ok": 1},
+                doc! {"cursor": {"id": 42_i64, "ns": "db.$cmd.listCollections", "nextBatch": [
+                    {"name": "c", "type": "timeseries"},
+                ]}, "ok": 1},
+                doc! {"cursor": {"id": 0_i64, "ns": "db.$cmd.listCollections", "nextBatch": [
+                    {"name": "d", "type": "collection"},
+                ]}, "ok": 1},
+            ]);
+            let commands = RefCell::new(vec![]);
+            let specs = futures::executor::block_on(list_collection_specifications_with(
+                |command| {
+                    commands.borrow_mut().push(command);
+                    let response = responses.pop_front().unwrap();
+                    async move { Ok(response) }
+                },
+                false,
+            ))
+            .unwrap();
+
+            assert_eq!(
+                vec![
+                    ("a", CollectionType::Collection),
+                    ("b", CollectionType::View),
+                    ("c", CollectionType::Timeseries),
+                    ("d", CollectionType::Collection),
+                ],
+                specs
+                    .iter()
+                    .map(|spec| (spec.name.as_str(), spec.collection_type.clone()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                vec![
+                    doc! {"listCollections": 1, "nameOnly": false, "authorizedCollections": true},
+                    doc! {"getMore": 42_i64, "collection": "$cmd.listCollections"},
+                    doc! {"getMore": 42_i64, "collection": "$cmd.listCollections"},
+                ],
+                commands.into_inner()
+            );
+        }
+
+        #[test]
+        fn single_batch_does_not_issue_get_more() {
+            let mut calls = 0;
+            let specs = futures::executor::block_on(list_collection_specifications_with(
+                |_| {
+                    calls += 1;
+                    async move {
+                        Ok(doc! {"cursor": {"id": 0_i64, "firstBatch": [{"name": "a"}]}, "ok": 1})
+                    }
+                },
+                false,
+            ))
+            .unwrap();
+            assert_eq!(1, calls);
+            assert_eq!(1, specs.len());
+        }
+
+        #[test]
+        fn missing_cursor_is_an_error() {
+            let res = futures::executor::block_on(list_collection_specifications_with(
+                |_| async { Ok(doc! {"ok": 1}) },
+                false,
+            ));
+            assert!(res.is_err());
+        }
+
+        #[test]
+        fn unauthorized_falls_back_to_name_only() {
+            let commands = RefCell::new(vec![]);
+            let specs = futures::executor::block_on(list_collection_specifications_with(|command| {
+                commands.borrow_mut().push(command.clone());
+                async move {
+                    if command.get_bool("nameOnly").unwrap() {
+                        Ok(doc! {"cursor": {"id": 0_i64, "firstBatch": [{"name": "a"}]}, "ok": 1})
+                    } else {
+                        let command_error: CommandError = mongodb::bson::from_document(
+                            doc! {"code": 13, "codeName": "Unauthorized", "errmsg": "not authorized"},
+                        )
+                        .unwrap();
+                        Err(ErrorKind::Command(command_error).into())
+                    }
+                }
+            }, false))
+            .unwrap();
+            assert_eq!(1, specs.len());
+            assert_eq!(
+                vec![
+                    doc! {"listCollections": 1, "nameOnly": false, "authorizedCollections": true},
+                    doc! {"listCollections": 1, "nameOnly": true, "authorizedCollections": true},
+                ],
+                commands.into_inner()
+            );
+        }
+
+        #[test]
+        fn name_only_lists_the_names_only() {
+            let commands = RefCell::new(vec![]);
+            let specs = futures::executor::block_on(list_collection_specifications_with(
+                |command| {
+                    commands.borrow_mut().push(command);
+                    async move {
+                        Ok(doc! {"cursor": {"id": 0_i64, "firstBatch": [{"name": "a", "type": "view"}]}, "ok": 1})
+                    }
+                },
+                true,
+            ))
+            .unwrap();
+            assert_eq!(
+                vec![("a", CollectionType::View)],
+                specs
+                    .iter()
+                    .map(|spec| (spec.name.as_str(), spec.collection_type.clone()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                vec![doc! {"listCollections": 1, "nameOnly": true, "authorizedCollections": true}],
+                commands.into_inner()
+            );
+        }
+
+        #[test]
+        fn table_types_and_remarks() {
+            let specs = futures::executor::block_on(list_collection_specifications_with(|_| async {
+                Ok(doc! {"cursor": {"id": 0_i64, "firstBatch": [
+                    {"name": "orders", "type": "collection", "options": {
+                        "validator": {"$jsonSchema": {"bsonType": "object", "description": "Customer orders"}}
+                    }},
+                    {"name": "readings", "type": "timeseries", "options": {
+                        "timeseries": {"timeField": "ts", "metaField": "sensor", "granularity": "seconds"}
+                    }},
+                    {"name": "recent_orders", "type": "view", "options": {"viewOn": "orders", "pipeline": []}},
+                    {"name": "system.views", "type": "collection", "options": {}},
+                ]}, "ok": 1})
+            }, false))
+            .unwrap();
+            assert_eq!(
+                vec![
+                    ("orders", TableType::Table, Some("Customer orders")),
+                    (
+                        "readings",
+                        TableType::Table,
+                        Some("time-series collection, timeField: ts, metaField: sensor")
+                    ),
+                    ("recent_orders", TableType::View, None),
+                    ("system.views", TableType::SystemTable, None),
+                ],
+                specs
+                    .iter()
+                    .map(|spec| (
+                        spec.name.as_str(),
+                        spec.table_type(),
+                        spec.remarks.as_deref()
+                    ))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
 }
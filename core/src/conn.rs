@@ -1,99 +1,30 @@
+use crate::catalog_filter::CatalogFilter;
+use crate::client_pool::{self, PoolOptions};
 use crate::cluster_type::{determine_cluster_type, MongoClusterType};
+use crate::col_metadata::PolymorphicColumns;
+use crate::liveness::{Liveness, DEFAULT_PING_TIMEOUT};
 use crate::mongosqltranslate::{
     get_mongosqltranslate_library, libmongosqltranslate_run_command,
     load_mongosqltranslate_library, CheckDriverVersion, CommandResponse,
     GetMongosqlTranslateVersion,
 };
 use crate::odbc_uri::UserOptions;
+use crate::retry::RetryPolicy;
+use crate::schema_drift::SchemaDrift;
 use crate::{err::Result, Error};
 use crate::{MongoQuery, TypeMode};
 use constants::DRIVER_ODBC_VERSION;
-use lazy_static::lazy_static;
 use mongodb::{
     bson::{doc, Bson, UuidRepresentation},
     Client,
 };
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "garbage_collect")]
-use std::sync::Weak;
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Runtime;
 
-// we make from UserOptions to Client and Weak<Runtime> so that we do not hold around
-// Clients and Runtimes that are no longer in use. In most cases it won't matter, but for drivers that live in
-// memory for a long time, this could be a problem.
-#[cfg(feature = "garbage_collect")]
-struct ClientMap(Vec<(UserOptions, Client, Weak<Runtime>)>);
-#[cfg(not(feature = "garbage_collect"))]
-struct ClientMap(Vec<(UserOptions, Client, Arc<Runtime>)>);
-
-impl ClientMap {
-    // new creates a new ClientMap.
-    fn new() -> Self {
-        ClientMap(Vec::new())
-    }
-
-    // get returns the client associated with the given user options if it exists.
-    #[cfg(feature = "garbage_collect")]
-    fn get(&mut self, user_options: &UserOptions) -> Option<(Client, Arc<Runtime>)> {
-        let mut to_remove = Vec::new();
-        for (i, (options, client, weak_rt)) in self.0.iter().enumerate() {
-            if options == user_options {
-                if let Some(rt) = weak_rt.upgrade() {
-                    return Some((client.clone(), rt));
-                // if somehow the Runtime cannot be upgraded, we want to remove this entry in the
-                // ClientMap, because the Runtime associated with the Client has been dropped,
-                // which will make the Topology hang forever.
-                } else {
-                    to_remove.push(i);
-                }
-            }
-        }
-        // We have to do this this way due to the borrow checker, it would be nice if we could just
-        // remove in the else above, but we cannot.
-        for i in to_remove.into_iter().rev() {
-            self.0.remove(i);
-        }
-        None
-    }
-
-    // get returns the client associated with the given user options if it exists.
-    #[cfg(not(feature = "garbage_collect"))]
-    fn get(&mut self, user_options: &UserOptions) -> Option<(Client, Arc<Runtime>)> {
-        for (options, client, rt) in self.0.iter() {
-            if options == user_options {
-                return Some((client.clone(), rt.clone()));
-            }
-        }
-        None
-    }
-
-    // gc is garbage collection of any clients that are no longer in use.
-    #[cfg(feature = "garbage_collect")]
-    fn gc(&mut self) {
-        self.0
-            .retain(|(_, _, weak_client)| Weak::strong_count(weak_client) != 0);
-    }
-
-    // insert inserts a new client into the client map keyed on the user options. Note this will
-    // insert duplicates and it is on the user to check if an entry already exists.
-    #[cfg(feature = "garbage_collect")]
-    fn insert(&mut self, user_options: UserOptions, client: &Client, rt: &Arc<Runtime>) {
-        self.0
-            .push((user_options, client.clone(), Arc::downgrade(rt)));
-    }
-
-    #[cfg(not(feature = "garbage_collect"))]
-    fn insert(&mut self, user_options: UserOptions, client: &Client, rt: &Arc<Runtime>) {
-        self.0.push((user_options, client.clone(), rt.clone()));
-    }
-}
-
-lazy_static! {
-    static ref CLIENT_MAP: tokio::sync::Mutex<ClientMap> =
-        tokio::sync::Mutex::new(ClientMap::new());
-}
-
 #[derive(Debug)]
 #[repr(C)]
 pub struct MongoConnection {
@@ -112,35 +43,28 @@ pub struct MongoConnection {
 
     /// client cluster type. Valid types are AtlasDataFederation and Enterprise
     pub cluster_type: MongoClusterType,
+
+    /// The databases and collections visible through the catalog functions.
+    pub catalog_filter: CatalogFilter,
+
+    /// Whether the arrays of the collections are exposed as virtual child tables.
+    pub array_tables: bool,
+
+    /// How fields with several BSON types are reported.
+    pub polymorphic_columns: PolymorphicColumns,
+
+    /// What happens to fetched values whose type does not match their column.
+    pub schema_drift: SchemaDrift,
+
+    /// The outcome of the last interaction with the server, which tells whether the connection
+    /// is dead.
+    pub liveness: Liveness,
+
+    /// How the operations failing with a transient error are retried.
+    pub retry_policy: RetryPolicy,
 }
 
 impl MongoConnection {
-    fn get_client_and_runtime(
-        user_options: UserOptions,
-        runtime: Arc<Runtime>,
-    ) -> Result<(Client, Arc<Runtime>)> {
-        let mut client_map = runtime.block_on(async { CLIENT_MAP.lock().await });
-        if let Some(cv) = client_map.get(&user_options) {
-            log::info!("reusing Client");
-            Ok(cv)
-        } else {
-            let key_user_options = user_options.clone();
-            log::info!("creating new Client",);
-            let guard = runtime.enter();
-            // the Client Topology uses tokio::spawn, so we need a guard here.
-            let client = runtime.block_on(async {
-                Client::with_options(user_options.client_options)
-                    .map_err(Error::InvalidClientOptions)
-            })?;
-            // we need to drop the guard before we return the runtime to kill the borrow
-            // on the runtime. We drop it before the insert to hold the lock for as little time as
-            // possible.
-            drop(guard);
-            client_map.insert(key_user_options, &client, &runtime);
-            Ok((client, runtime))
-        }
-    }
-
     fn get_libmongosqltranslate_version() -> Result<String> {
         let command = GetMongosqlTranslateVersion::default();
 
@@ -175,6 +99,8 @@ impl MongoConnection {
     /// and will take precedence over the database setting specified in the uri if any.
     /// The initial operation time if provided should come from and will take precedence over the
     /// setting specified in the uri if any.
+    /// The Client is taken from, and kept in, the pool described by the pool options.
+    #[allow(clippy::too_many_arguments)]
     pub fn connect(
         mut user_options: UserOptions,
         current_db: Option<String>,
@@ -183,6 +109,12 @@ impl MongoConnection {
         type_mode: TypeMode,
         mut runtime: Option<Runtime>,
         max_string_length: Option<u16>,
+        catalog_filter: CatalogFilter,
+        array_tables: bool,
+        polymorphic_columns: PolymorphicColumns,
+        schema_drift: SchemaDrift,
+        retry_policy: RetryPolicy,
+        pool_options: PoolOptions,
     ) -> Result<Self> {
         let runtime = Arc::new(runtime.take().unwrap_or_else(|| {
             tokio::runtime::Builder::new_current_thread()
@@ -219,7 +151,7 @@ impl MongoConnection {
                 (None, None)
             };
 
-        let (client, runtime) = Self::get_client_and_runtime(user_options, runtime)?;
+        let (client, runtime) = client_pool::get_client(user_options, runtime, &pool_options)?;
 
         let type_of_cluster = runtime.block_on(async { determine_cluster_type(&client).await })?;
         match type_of_cluster {
@@ -264,6 +196,12 @@ impl MongoConnection {
             uuid_repr,
             runtime,
             cluster_type: type_of_cluster,
+            catalog_filter,
+            array_tables,
+            polymorphic_columns,
+            schema_drift,
+            liveness: Liveness::default(),
+            retry_policy,
         };
 
         // Verify that the connection is working and the user has access to the default DB
@@ -280,33 +218,53 @@ impl MongoConnection {
         Ok(connection)
     }
 
-    #[cfg(feature = "garbage_collect")]
+    /// Closes the connection. A Client no other connection uses is shut down, unless it is
+    /// pooled, in which case it is shut down once it has been idle for the idle timeout of the
+    /// pool.
     pub fn shutdown(self) -> Result<()> {
-        // we need to lock the CLIENT_MAP to potentially remove the client from the map.
-        // This prevents races on the strong_count or with gc().
-        let mut client_map = self.runtime.block_on(async { CLIENT_MAP.lock().await });
-        // If this is the last reference to the Runtime, we need to shutdown the Client.
         if Arc::strong_count(&self.runtime) == 1 {
             self.runtime
                 .block_on(async { self.client.shutdown().await });
+        } else {
+            client_pool::release_client(self.runtime);
         }
-        // We need to drop the Runtime before we gc, otherwise this will not be collected.
-        drop(self.runtime);
-        // garbage collect any Clients and Runtimes that are no longer in use, which is denoted by
-        // the strong_count for the Runtime being 0. It's possible there could be
-        // other clients that are no longer in use because shutdown was not properly called before,
-        // so we gc them all.
-        client_map.gc();
         Ok(())
     }
 
-    #[cfg(not(feature = "garbage_collect"))]
-    pub fn shutdown(self) -> Result<()> {
-        if Arc::strong_count(&self.runtime) == 1 {
-            self.runtime
-                .block_on(async { self.client.shutdown().await });
+    /// Returns Error::ConnectionDead if the server could not be reached the last time it was
+    /// used, so that the next statement fails with 08S01 instead of a generic execution error.
+    pub fn check_alive(&self) -> Result<()> {
+        self.liveness.check()
+    }
+
+    /// Records the outcome of an interaction with the server and returns it.
+    pub fn record<T>(&self, result: Result<T>) -> Result<T> {
+        self.liveness.record(&result);
+        result
+    }
+
+    /// Whether the connection is dead, for SQL_ATTR_CONNECTION_DEAD. The outcome of the last
+    /// interaction with the server is used when it is recent enough, otherwise the server is
+    /// pinged, waiting up to the operation timeout.
+    pub fn is_dead(&self) -> bool {
+        if self.liveness.needs_ping(Instant::now()) {
+            let timeout = self.operation_timeout.unwrap_or(DEFAULT_PING_TIMEOUT);
+            let _guard = self.runtime.enter();
+            let ping = self.runtime.block_on(async {
+                tokio::time::timeout(
+                    timeout,
+                    self.client.database("admin").run_command(doc! {"ping": 1}),
+                )
+                .await
+            });
+            match ping {
+                Ok(result) => self
+                    .liveness
+                    .record(&result.map_err(Error::QueryExecutionFailed)),
+                Err(_) => self.liveness.record_timeout(timeout),
+            }
         }
-        Ok(())
+        self.liveness.is_dead()
     }
 
     /// Gets the ADF version the client is connected to.
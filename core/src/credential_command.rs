@@ -0,0 +1,242 @@
+use crate::err::{Error, Result};
+use std::{fmt, process::Stdio, str::FromStr};
+use tokio::{process::Command, time::Duration};
+
+// The command is killed when it does not exit within this time, so a prompt waiting for input
+// cannot hang the connection.
+pub const CREDENTIAL_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+// The stderr of a failed command is reported up to this many characters.
+const MAX_STDERR_LEN: usize = 512;
+const REDACTED: &str = "<redacted>";
+
+/// CredentialCommand is the program printing the password, or the OIDC access token, on its
+/// stdout. It comes from the credential_command connection string/DSN option, so that no secret
+/// has to be stored in the DSN.
+///
+/// The program is run directly, never through a shell: the option is split on whitespace into
+/// the program and its arguments, and single or double quotes group words containing whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialCommand {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+/// CommandCredential is the secret printed by a CredentialCommand, which is kept to run it again
+/// when the secret expires. Its Debug output does not include the secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CommandCredential {
+    pub command: CredentialCommand,
+    pub secret: String,
+}
+
+impl fmt::Debug for CommandCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandCredential")
+            .field("command", &self.command)
+            .field("secret", &REDACTED)
+            .finish()
+    }
+}
+
+impl FromStr for CredentialCommand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut words = Vec::new();
+        let mut word: Option<String> = None;
+        let mut quote = None;
+        for c in s.chars() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                    word.get_or_insert_with(String::new);
+                }
+                (None, c) if c.is_whitespace() => words.extend(word.take()),
+                (None, c) => word.get_or_insert_with(String::new).push(c),
+            }
+        }
+        if quote.is_some() {
+            return Err(Error::InvalidUriFormat(format!(
+                "invalid credential_command value '{s}', unterminated quote"
+            )));
+        }
+        words.extend(word);
+        let mut words = words.into_iter();
+        match words.next() {
+            Some(program) if !program.is_empty() => Ok(CredentialCommand {
+                program,
+                args: words.collect(),
+                timeout: CREDENTIAL_COMMAND_TIMEOUT,
+            }),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid credential_command value '{s}', expected a program"
+            ))),
+        }
+    }
+}
+
+impl CredentialCommand {
+    /// Runs the command and returns its stdout, without the trailing line break, as the secret.
+    /// The command failing to start, timing out, exiting with an error or printing nothing is an
+    /// Error::CredentialCommand, which includes its stderr, with the secret redacted.
+    pub async fn run(&self) -> Result<CommandCredential> {
+        let child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                Error::CredentialCommand(format!("starting '{}' failed: {e}", self.program))
+            })?;
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                Error::CredentialCommand(format!(
+                    "'{}' did not exit within {:?}",
+                    self.program, self.timeout
+                ))
+            })?
+            .map_err(|e| {
+                Error::CredentialCommand(format!("running '{}' failed: {e}", self.program))
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let secret = stdout.trim_end_matches(['\r', '\n']);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stderr = redact(stderr.trim(), secret);
+            return Err(Error::CredentialCommand(if stderr.is_empty() {
+                format!("'{}' failed with {}", self.program, output.status)
+            } else {
+                format!(
+                    "'{}' failed with {}: {}",
+                    self.program,
+                    output.status,
+                    stderr.chars().take(MAX_STDERR_LEN).collect::<String>()
+                )
+            }));
+        }
+        if secret.is_empty() {
+            return Err(Error::CredentialCommand(format!(
+                "'{}' printed no credential",
+                self.program
+            )));
+        }
+        log::info!("'{}' provided the credential", self.program);
+        Ok(CommandCredential {
+            command: self.clone(),
+            secret: secret.to_string(),
+        })
+    }
+}
+
+fn redact(message: &str, secret: &str) -> String {
+    if secret.trim().is_empty() {
+        message.to_string()
+    } else {
+        message.replace(secret.trim(), REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use constants::INVALID_AUTHORIZATION_SPECIFICATION;
+
+    fn command(program: &str, args: &[&str]) -> CredentialCommand {
+        CredentialCommand {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            timeout: CREDENTIAL_COMMAND_TIMEOUT,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            command("vault", &["read", "-field=password", "secret/db"]),
+            " vault  read -field=password\tsecret/db ".parse().unwrap()
+        );
+        assert_eq!(
+            command(
+                r"C:\Program Files\Vault\vault.exe",
+                &["read", "a b", "it's", ""]
+            ),
+            r#""C:\Program Files\Vault\vault.exe" read 'a b' "it's" """#
+                .parse()
+                .unwrap()
+        );
+        // Shell syntax has no meaning, it is passed to the program.
+        assert_eq!(
+            command("echo", &["$(id);", "rm", "-rf", "*"]),
+            "echo $(id); rm -rf *".parse().unwrap()
+        );
+        assert_eq!(
+            "Err(InvalidUriFormat(\"invalid credential_command value 'vault 'read', unterminated quote\"))",
+            format!("{:?}", "vault 'read".parse::<CredentialCommand>())
+        );
+        for value in ["", "  ", "'' read"] {
+            assert!(value.parse::<CredentialCommand>().is_err());
+        }
+    }
+
+    #[test]
+    fn credential_is_redacted() {
+        let credential = CommandCredential {
+            command: command("vault", &[]),
+            secret: "s3cr3t".to_string(),
+        };
+        assert!(!format!("{credential:?}").contains("s3cr3t"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn run() {
+        let credential = command("sh", &["-c", "echo ' s3cr3t '"])
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(" s3cr3t ", credential.secret);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_failures() {
+        let e = command(
+            "sh",
+            &["-c", "printf s3cr3t; echo 'rejected s3cr3t' >&2; exit 3"],
+        )
+        .run()
+        .await
+        .unwrap_err();
+        assert_eq!(INVALID_AUTHORIZATION_SPECIFICATION, e.get_sql_state());
+        assert_eq!(
+            "The credential command failed: 'sh' failed with exit status: 3: rejected <redacted>",
+            e.to_string()
+        );
+
+        let e = command("sh", &["-c", "true"]).run().await.unwrap_err();
+        assert_eq!(
+            "The credential command failed: 'sh' printed no credential",
+            e.to_string()
+        );
+
+        let e = command("/nonexistent/credential_command", &[])
+            .run()
+            .await
+            .unwrap_err();
+        assert_eq!(INVALID_AUTHORIZATION_SPECIFICATION, e.get_sql_state());
+
+        let mut sleep = command("sleep", &["5"]);
+        sleep.timeout = Duration::from_millis(100);
+        assert_eq!(
+            "The credential command failed: 'sleep' did not exit within 100ms",
+            sleep.run().await.unwrap_err().to_string()
+        );
+    }
+}
@@ -8,6 +8,56 @@ use mongodb::bson::Bson;
 use once_cell::sync::OnceCell;
 
 pub(crate) static DATABASES_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+static DATABASES_AS_SCHEMAS_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+
+/// DatabaseAs controls which level of the ODBC catalog hierarchy MongoDB databases are reported
+/// as. It comes from the database_as connection string/DSN option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatabaseAs {
+    /// Databases are catalogs (TABLE_CAT) and TABLE_SCHEM is always NULL.
+    #[default]
+    Catalog,
+    /// Databases are schemas (TABLE_SCHEM) and TABLE_CAT is always NULL.
+    Schema,
+}
+
+impl std::str::FromStr for DatabaseAs {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "catalog" => Ok(DatabaseAs::Catalog),
+            "schema" => Ok(DatabaseAs::Schema),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid database_as value '{s}', expected 'catalog' or 'schema'"
+            ))),
+        }
+    }
+}
+
+impl DatabaseAs {
+    // Returns the (TABLE_CAT, TABLE_SCHEM) values for a row belonging to the given database.
+    pub(crate) fn catalog_and_schema(&self, db_name: &str) -> (Bson, Bson) {
+        match self {
+            DatabaseAs::Catalog => (Bson::String(db_name.to_string()), Bson::Null),
+            DatabaseAs::Schema => (Bson::Null, Bson::String(db_name.to_string())),
+        }
+    }
+
+    // Adjusts the TABLE_CAT and TABLE_SCHEM (first and second columns) nullability of catalog
+    // function metadata to this mapping. The given metadata must be the one used when databases
+    // are catalogs.
+    pub(crate) fn adjust_metadata(
+        &self,
+        mut metadata: Vec<MongoColMetadata>,
+    ) -> Vec<MongoColMetadata> {
+        if *self == DatabaseAs::Schema {
+            metadata[0].nullability = Nullability::SQL_NULLABLE;
+            metadata[1].nullability = Nullability::SQL_NO_NULLS;
+        }
+        metadata
+    }
+}
 
 pub(crate) fn init_databases_metadata(max_string_length: Option<u16>) -> Vec<MongoColMetadata> {
     vec![
@@ -64,6 +114,18 @@ mod unit {
         );
     }
 
+    #[test]
+    fn database_as_from_str() {
+        use crate::databases::DatabaseAs;
+        use std::str::FromStr;
+        assert_eq!(
+            DatabaseAs::Catalog,
+            DatabaseAs::from_str("catalog").unwrap()
+        );
+        assert_eq!(DatabaseAs::Schema, DatabaseAs::from_str("Schema").unwrap());
+        assert!(DatabaseAs::from_str("table").is_err());
+    }
+
     #[test]
     fn metadata_column_names() {
         use crate::{databases::MongoDatabases, stmt::MongoStatement};
@@ -196,18 +258,38 @@ pub struct MongoDatabases {
     database_names: Vec<String>,
     // The current database index.
     current_db_index: usize,
+    // Whether the databases are listed as catalogs or schemas.
+    database_as: DatabaseAs,
 }
 
-// Statement for SQLTables(SQL_ALL_CATALOGS, "","").
+// Statement for SQLTables(SQL_ALL_CATALOGS, "","") and SQLTables("", SQL_ALL_SCHEMAS, "").
 impl MongoDatabases {
-    // Create a new MongoStatement to list all the valid databases.
+    // Create a new MongoStatement to list all the valid databases as catalogs.
     // Correspond to SQLTables(SQL_ALL_CATALOGS, "","").
     // All columns except the TABLE_CAT column contain NULLs.
     // The query timeout comes from the statement attribute SQL_ATTR_QUERY_TIMEOUT. If there is a
     // timeout, the query must finish before the timeout or an error is returned.
     pub fn list_all_catalogs(
+        mongo_connection: &MongoConnection,
+        query_timeout: Option<i32>,
+    ) -> Self {
+        Self::list_databases(mongo_connection, query_timeout, DatabaseAs::Catalog)
+    }
+
+    // Create a new MongoStatement to list all the valid databases as schemas.
+    // Correspond to SQLTables("", SQL_ALL_SCHEMAS, "") when databases are mapped to schemas.
+    // All columns except the TABLE_SCHEM column contain NULLs.
+    pub fn list_all_schemas(
+        mongo_connection: &MongoConnection,
+        query_timeout: Option<i32>,
+    ) -> Self {
+        Self::list_databases(mongo_connection, query_timeout, DatabaseAs::Schema)
+    }
+
+    fn list_databases(
         mongo_connection: &MongoConnection,
         _query_timeout: Option<i32>,
+        database_as: DatabaseAs,
     ) -> Self {
         let _guard = mongo_connection.runtime.enter();
         let database_names: Vec<String> = mongo_connection
@@ -221,13 +303,14 @@ impl MongoDatabases {
             })
             .unwrap()
             .iter()
-            .filter(|&db_name| !db_name.is_empty() && !db_name.eq("admin"))
+            .filter(|&db_name| mongo_connection.catalog_filter.is_database_visible(db_name))
             .map(|s| s.to_string())
             .collect();
 
         MongoDatabases {
             database_names,
             current_db_index: 0,
+            database_as,
         }
     }
 
@@ -235,6 +318,7 @@ impl MongoDatabases {
         MongoDatabases {
             database_names: vec![],
             current_db_index: 0,
+            database_as: DatabaseAs::Catalog,
         }
     }
 }
@@ -250,22 +334,30 @@ impl MongoStatement for MongoDatabases {
     // Get the BSON value for the value at the given colIndex on the current row.
     fn get_value(&self, col_index: u16, _: Option<u16>) -> Result<Option<Bson>> {
         // The mapping for col_index <-> Value will be hard-coded and handled in this function
-        // 1-> databases_names[current_row_index]
-        // 2..=4 -> Null
+        // 1-> databases_names[current_row_index] if databases are catalogs, Null otherwise
+        // 2-> databases_names[current_row_index] if databases are schemas, Null otherwise
+        // 3..=4 -> Null
         // 5 => "" (Remarks)
         match col_index {
-            1 => Ok(Some(Bson::String(
-                self.database_names
-                    .get(self.current_db_index - 1)
-                    .unwrap()
-                    .to_string(),
-            ))),
-            2..=5 => Ok(Some(Bson::Null)),
+            1 | 2 => {
+                let (catalog, schema) = self.database_as.catalog_and_schema(
+                    self.database_names.get(self.current_db_index - 1).unwrap(),
+                );
+                Ok(Some(if col_index == 1 { catalog } else { schema }))
+            }
+            3..=5 => Ok(Some(Bson::Null)),
             _ => Err(Error::ColIndexOutOfBounds(col_index)),
         }
     }
 
     fn get_resultset_metadata(&self, max_string_length: Option<u16>) -> &Vec<MongoColMetadata> {
-        DATABASES_METADATA.get_or_init(|| init_databases_metadata(max_string_length))
+        match self.database_as {
+            DatabaseAs::Catalog => {
+                DATABASES_METADATA.get_or_init(|| init_databases_metadata(max_string_length))
+            }
+            DatabaseAs::Schema => DATABASES_AS_SCHEMAS_METADATA.get_or_init(|| {
+                DatabaseAs::Schema.adjust_metadata(init_databases_metadata(max_string_length))
+            }),
+        }
     }
 }
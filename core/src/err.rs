@@ -1,7 +1,7 @@
 use constants::{
-    OdbcState, FUNCTION_SEQUENCE_ERROR, GENERAL_ERROR, INVALID_CURSOR_STATE,
-    INVALID_DESCRIPTOR_INDEX, NO_DSN_OR_DRIVER, OPERATION_CANCELLED, TIMEOUT_EXPIRED,
-    UNABLE_TO_CONNECT,
+    OdbcState, COMMUNICATION_LINK_FAILURE, FUNCTION_SEQUENCE_ERROR, GENERAL_ERROR, GENERAL_WARNING,
+    INVALID_AUTHORIZATION_SPECIFICATION, INVALID_CURSOR_STATE, INVALID_DESCRIPTOR_INDEX,
+    NO_DSN_OR_DRIVER, OPERATION_CANCELLED, RESTRICTED_DATATYPE, TIMEOUT_EXPIRED, UNABLE_TO_CONNECT,
 };
 use mongodb::error::{ErrorKind, WriteFailure};
 use thiserror::Error;
@@ -16,12 +16,16 @@ pub enum Error {
     CollectionCursorUpdate(mongodb::error::Error),
     #[error("Getting metadata for collection '{0}' failed with error: {1}")]
     CollectionDeserialization(String, mongodb::bson::de::Error),
+    #[error("The columns of collection '{0}' are not listed because its schema is invalid: {1}")]
+    CollectionSchemaProcessing(String, Box<Error>),
     #[error("Retrieving information for database failed with error: {0}")]
     DatabaseVersionRetreival(mongodb::error::Error),
     #[error("Getting database metadata failed with error: {0}")]
     DatabaseVersionDeserialization(mongodb::bson::de::Error),
     #[error("Setting connection options failed with error: {0}")]
     InvalidClientOptions(mongodb::error::Error),
+    #[error("Communication link failure, the connection is dead: {0}")]
+    ConnectionDead(String),
     #[error("Invalid cursor state: cursor not advanced")]
     InvalidCursorState,
     #[error("{0}")]
@@ -92,6 +96,16 @@ pub enum Error {
     MultipleSchemaDocumentsReturned(usize),
     #[error("The buildInfo command failed with the following error: `{0}`")]
     BuildInfoCmdExecutionFailed(mongodb::error::Error),
+    #[error("Value of column '{0}' in row {3} is of type {2} instead of its schema type {1}")]
+    SchemaDrift(String, String, &'static str, u64),
+    #[error("{0}")]
+    SchemaDriftRejected(Box<Error>),
+    #[error("Retry {0} of {1} after a transient error: {2}")]
+    TransientErrorRetried(u32, u32, Box<Error>),
+    #[error("Starting the OIDC redirect server failed with error: {0}")]
+    OidcRedirectServer(String),
+    #[error("The credential command failed: {0}")]
+    CredentialCommand(String),
 }
 
 impl Error {
@@ -106,14 +120,21 @@ impl Error {
                 {
                     return TIMEOUT_EXPIRED;
                 }
+                if self.is_connection_failure() {
+                    return COMMUNICATION_LINK_FAILURE;
+                }
                 GENERAL_ERROR
             }
+            Error::ConnectionDead(_) => COMMUNICATION_LINK_FAILURE,
             Error::InvalidUriFormat(_) => UNABLE_TO_CONNECT,
             Error::MongoParseConnectionString(_) => UNABLE_TO_CONNECT,
+            Error::OidcRedirectServer(_) => UNABLE_TO_CONNECT,
+            Error::CredentialCommand(_) => INVALID_AUTHORIZATION_SPECIFICATION,
             Error::NoDatabase => NO_DSN_OR_DRIVER,
             Error::ColIndexOutOfBounds(_) => INVALID_DESCRIPTOR_INDEX,
             Error::InvalidCursorState => INVALID_CURSOR_STATE,
             Error::CollectionDeserialization(_, _)
+            | Error::CollectionSchemaProcessing(_, _)
             | Error::DatabaseVersionDeserialization(_)
             | Error::InvalidResultSetJsonSchema(_)
             | Error::MissingConnection(_)
@@ -139,6 +160,9 @@ impl Error {
             | Error::MetadataAccess(_, _)
             | Error::BuildInfoCmdExecutionFailed(_) => GENERAL_ERROR,
             Error::StatementNotExecuted => FUNCTION_SEQUENCE_ERROR,
+            Error::SchemaDrift(_, _, _, _) => GENERAL_WARNING,
+            Error::SchemaDriftRejected(_) => RESTRICTED_DATATYPE,
+            Error::TransientErrorRetried(_, _, _) => GENERAL_WARNING,
             Error::QueryCancelled => OPERATION_CANCELLED,
         }
     }
@@ -164,10 +188,14 @@ impl Error {
             },
             Error::ColIndexOutOfBounds(_)
             | Error::CollectionDeserialization(_, _)
+            | Error::CollectionSchemaProcessing(_, _)
             | Error::DatabaseVersionDeserialization(_)
+            | Error::ConnectionDead(_)
             | Error::InvalidCursorState
             | Error::InvalidResultSetJsonSchema(_)
             | Error::InvalidUriFormat(_)
+            | Error::OidcRedirectServer(_)
+            | Error::CredentialCommand(_)
             | Error::MissingConnection(_)
             | Error::MissingFieldBsonType(_)
             | Error::NoDatabase
@@ -192,7 +220,28 @@ impl Error {
             | Error::NoSchemaInformationReturned
             | Error::MultipleSchemaDocumentsReturned(_)
             | Error::BuildInfoCmdExecutionFailed(_)
+            | Error::SchemaDrift(_, _, _, _)
+            | Error::SchemaDriftRejected(_)
+            | Error::TransientErrorRetried(_, _, _)
             | Error::MetadataAccess(_, _) => 0,
         }
     }
+
+    /// Whether the error is the server not being reachable, rather than the server failing the
+    /// operation. Timeouts are not connection failures.
+    pub fn is_connection_failure(&self) -> bool {
+        match self {
+            Error::CollectionCursorUpdate(err)
+            | Error::DatabaseVersionRetreival(err)
+            | Error::QueryCursorUpdate(err)
+            | Error::QueryExecutionFailed(err)
+            | Error::BuildInfoCmdExecutionFailed(err) => match err.kind.as_ref() {
+                ErrorKind::Io(io_err) => io_err.kind() != std::io::ErrorKind::TimedOut,
+                ErrorKind::ConnectionPoolCleared { .. } | ErrorKind::ServerSelection { .. } => true,
+                _ => false,
+            },
+            Error::ConnectionDead(_) => true,
+            _ => false,
+        }
+    }
 }
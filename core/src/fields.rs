@@ -1,24 +1,175 @@
 use crate::{
-    cluster_type::MongoClusterType,
-    col_metadata::{MongoColMetadata, ResultSetSchema, SqlGetSchemaResponse},
-    collections::MongoODBCCollectionSpecification,
+    array_tables::ArrayTable,
+    col_metadata::{MongoColMetadata, PolymorphicColumns, ResultSetSchema},
+    collections::{list_collection_specifications, MongoODBCCollectionSpecification},
     conn::MongoConnection,
+    databases::DatabaseAs,
     err::{Error, Result},
+    json_schema::simplified::Schema,
     stmt::MongoStatement,
-    util::to_name_regex,
+    util::{is_match, may_match_with_prefix, to_name_regex},
     BsonTypeInfo, TypeMode,
 };
-use constants::SQL_SCHEMAS_COLLECTION;
 use definitions::{Nullability, SqlDataType};
-use mongodb::{
-    bson::{doc, Bson, Document},
-    results::CollectionType,
-};
+use mongodb::bson::Bson;
 use once_cell::sync::OnceCell;
 use regex::Regex;
 use std::collections::VecDeque;
 
 static FIELDS_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+static FIELDS_AS_SCHEMAS_METADATA: OnceCell<Vec<MongoColMetadata>> = OnceCell::new();
+
+fn init_fields_metadata(max_string_length: Option<u16>) -> Vec<MongoColMetadata> {
+    vec![
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "TABLE_CAT".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "TABLE_SCHEM".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "TABLE_NAME".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "COLUMN_NAME".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "DATA_TYPE".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "TYPE_NAME".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "COLUMN_SIZE".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "BUFFER_LENGTH".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "DECIMAL_DIGITS".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "NUM_PREC_RADIX".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "NULLABLE".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "REMARKS".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "COLUMN_DEF".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "SQL_DATA_TYPE".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "SQL_DATETIME_SUB".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "CHAR_OCTET_LENGTH".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NULLABLE,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "ORDINAL_POSITION".to_string(),
+            BsonTypeInfo::INT,
+            max_string_length,
+            Nullability::SQL_NO_NULLS,
+        ),
+        MongoColMetadata::new_metadata_from_bson_type_info_default(
+            "",
+            "".to_string(),
+            "IS_NULLABLE".to_string(),
+            BsonTypeInfo::STRING,
+            max_string_length,
+            // the docs do not say 'not NULL', but they also say the only possible values for
+            // ISO SQL are 'YES' and 'NO'. And even for non-ISO SQL they only allow additionally
+            // the empty varchar... so NO_NULLS seems correct to me.
+            Nullability::SQL_NO_NULLS,
+        ),
+    ]
+}
 
 mod unit {
     #[test]
@@ -436,11 +587,18 @@ pub struct MongoFields {
     current_col_metadata: Vec<MongoColMetadata>,
     current_field_for_collection: isize,
     collection_name_filter: Option<Regex>,
+    // The search pattern of collection_name_filter, used to find the collections whose array
+    // tables can match it
+    collection_name_pattern: String,
     field_name_filter: Option<Regex>,
     type_mode: TypeMode,
     max_string_length: Option<u16>,
     /// Whether this mongofield should map to odbc 3 types or not
     odbc_3_types: bool,
+    /// Whether databases are reported as catalogs or schemas
+    database_as: DatabaseAs,
+    /// The metadata of the array tables of the current collection which remain to be listed
+    array_tables_metadata: VecDeque<Vec<MongoColMetadata>>,
 }
 
 // Statement related to a SQLTables call.
@@ -456,8 +614,10 @@ impl MongoFields {
             _ => data_type,
         }
     }
-    // Create a new MongoStatement to list tables with the given database (catalogs) and collection
+    // Create a new MongoStatement to list tables with the given database and collection
     // (tables) names filters.
+    // When databases are catalogs, db_name is the exact catalog name. When databases are
+    // schemas, db_name is a schema search pattern.
     // The query timeout comes from the statement attribute SQL_ATTR_QUERY_TIMEOUT. If there is a
     // timeout, the query must finish before the timeout or an error is returned.
     #[allow(clippy::too_many_arguments)]
@@ -470,8 +630,13 @@ impl MongoFields {
         type_mode: TypeMode,
         max_string_length: Option<u16>,
         odbc_3_types: bool,
+        database_as: DatabaseAs,
     ) -> Self {
-        let dbs = db_name.map_or_else(
+        let exact_db_name = match database_as {
+            DatabaseAs::Catalog => db_name,
+            DatabaseAs::Schema => None,
+        };
+        let dbs = exact_db_name.map_or_else(
             || {
                 let _guard = mongo_connection.runtime.enter();
                 mongo_connection
@@ -484,25 +649,33 @@ impl MongoFields {
                             .await
                     })
                     .unwrap()
-                    // MHOUSE-7119 - admin database and empty strings are showing in list_database_names
                     .iter()
-                    .filter(|&db_name| !db_name.is_empty() && !db_name.eq("admin"))
                     .map(|s| s.to_string())
                     .collect()
             },
             |db| vec![db.to_string()],
         );
+        let dbs = dbs
+            .into_iter()
+            .filter(|name| {
+                mongo_connection.catalog_filter.is_database_visible(name)
+                    && (exact_db_name.is_some() || is_match(name, db_name.unwrap_or(""), true))
+            })
+            .collect::<VecDeque<String>>();
         MongoFields {
-            dbs: dbs.into(),
+            dbs,
             current_db_name: "".to_string(),
             collections_for_db: None,
             current_col_metadata: Vec::new(),
             current_field_for_collection: -1,
             collection_name_filter: collection_name_filter.and_then(to_name_regex),
+            collection_name_pattern: collection_name_filter.unwrap_or_default().to_string(),
             field_name_filter: field_name_filter.and_then(to_name_regex),
             type_mode,
             max_string_length,
             odbc_3_types,
+            database_as,
+            array_tables_metadata: VecDeque::new(),
         }
     }
 
@@ -514,10 +687,46 @@ impl MongoFields {
             current_col_metadata: Vec::new(),
             current_field_for_collection: -1,
             collection_name_filter: None,
+            collection_name_pattern: String::new(),
             field_name_filter: None,
             type_mode: TypeMode::Standard,
             max_string_length: None,
             odbc_3_types: true,
+            database_as: DatabaseAs::Catalog,
+            array_tables_metadata: VecDeque::new(),
+        }
+    }
+
+    // Queue the metadata of the array tables of a collection which match the collection name
+    // filter. A schema which cannot be simplified has no array tables.
+    fn queue_array_tables(
+        &mut self,
+        collection_schema: &ResultSetSchema,
+        collection_name: &str,
+        polymorphic_columns: PolymorphicColumns,
+    ) {
+        let Ok(schema) = Schema::try_from(collection_schema.schema.clone()) else {
+            return;
+        };
+        for table in ArrayTable::from_collection_schema(collection_name, &schema) {
+            if self
+                .collection_name_filter
+                .as_ref()
+                .is_some_and(|filter| !filter.is_match(&table.name))
+            {
+                continue;
+            }
+            match ResultSetSchema::schema_to_col_metadata(
+                &table.schema,
+                &self.current_db_name,
+                &table.name,
+                self.type_mode,
+                self.max_string_length,
+                polymorphic_columns,
+            ) {
+                Ok(metadata) => self.array_tables_metadata.push_back(metadata),
+                Err(e) => log::error!("Error while processing array table metadata: {}", e),
+            }
         }
     }
 
@@ -529,86 +738,69 @@ impl MongoFields {
         mongo_connection.runtime.block_on(async {
             let mut warnings: Vec<Error> = vec![];
             loop {
+                // The array tables of the previous collection come right after it
+                if let Some(array_table_metadata) = self.array_tables_metadata.pop_front() {
+                    self.current_col_metadata = array_table_metadata;
+                    self.current_field_for_collection = 0;
+                    return Ok((true, warnings));
+                }
                 if self.collections_for_db.is_some() {
                     if let Some(current_collection) =
                         self.collections_for_db.as_mut().unwrap().pop_front()
                     {
                         let collection_name = current_collection.name.clone();
-                        if self.collection_name_filter.is_some()
-                            && !self
-                                .collection_name_filter
-                                .as_ref()
-                                .unwrap()
-                                .is_match(&collection_name)
-                        {
-                            // The collection does not match the filter, moving to the next one
+                        let collection_matches = self
+                            .collection_name_filter
+                            .as_ref()
+                            .is_none_or(|filter| filter.is_match(&collection_name));
+                        // Array tables are listed even when their collection does not match
+                        let array_tables = mongo_connection.array_tables
+                            && may_match_with_prefix(
+                                &format!("{collection_name}_"),
+                                &self.collection_name_pattern,
+                            );
+                        if !collection_matches && !array_tables {
+                            // Neither the collection nor its array tables match the filter,
+                            // moving to the next one
                             continue;
                         }
 
                         let db = mongo_connection.client.database(&self.current_db_name);
 
-                        let current_col_metadata_response: ResultSetSchema = if mongo_connection
-                            .cluster_type
-                            == MongoClusterType::AtlasDataFederation
+                        let current_col_metadata_response = match ResultSetSchema::for_collection(
+                            mongo_connection,
+                            &db,
+                            &collection_name,
+                        )
+                        .await
                         {
-                            let get_schema_cmd = doc! {"sqlGetSchema": &collection_name};
-
-                            let sql_get_schema_response: Result<SqlGetSchemaResponse> =
-                                mongodb::bson::from_document(
-                                    db.run_command(get_schema_cmd).await.unwrap(),
-                                )
-                                .map_err(|e| {
-                                    Error::CollectionDeserialization(collection_name.clone(), e)
-                                });
-
-                            if let Err(error) = sql_get_schema_response {
-                                // If there is an Error while deserializing the schema, we won't show any columns for it
+                            Ok(schema) => schema,
+                            // If there is an Error while deserializing the schema, we won't show any columns for it
+                            Err(error @ Error::CollectionDeserialization(_, _)) => {
                                 warnings.push(error);
                                 continue;
                             }
+                            Err(error) => return Err(error),
+                        };
 
-                            sql_get_schema_response.unwrap().into()
-                        } else if mongo_connection.cluster_type == MongoClusterType::Enterprise {
-                            let schema_collection =
-                                db.collection::<Document>(SQL_SCHEMAS_COLLECTION);
-
-                            let schema_doc: Document = mongo_connection
-                                .runtime
-                                .block_on(async {
-                                    schema_collection
-                                        .find_one(doc! {
-                                            "_id": &collection_name
-                                        })
-                                        .await
-                                        .map_err(Error::QueryExecutionFailed)
-                                })?
-                                .ok_or(Error::SchemaDocumentNotFoundInSchemaCollection(vec![
-                                    collection_name.clone(),
-                                ]))?;
-
-                            let result_set_schema: Result<ResultSetSchema> =
-                                ResultSetSchema::from_sql_schemas_document(&schema_doc).map_err(
-                                    |e| {
-                                        Error::CollectionDeserialization(collection_name.clone(), e)
-                                    },
-                                );
-
-                            if let Err(error) = result_set_schema {
-                                // If there is an Error while deserializing the schema, we won't show any columns for it
-                                warnings.push(error);
-                                continue;
-                            }
+                        if array_tables {
+                            self.queue_array_tables(
+                                &current_col_metadata_response,
+                                &collection_name,
+                                mongo_connection.polymorphic_columns,
+                            );
+                        }
 
-                            result_set_schema.unwrap()
-                        } else {
-                            unreachable!()
-                        };
+                        if !collection_matches {
+                            continue;
+                        }
 
                         match current_col_metadata_response.process_collection_metadata(
                             &self.current_db_name,
                             collection_name.as_str(),
                             self.type_mode,
                             self.max_string_length,
+                            mongo_connection.polymorphic_columns,
                         ) {
                             Ok(current_col_metadata) => {
                                 if !current_col_metadata.is_empty() {
@@ -617,9 +809,13 @@ impl MongoFields {
                                     return Ok((true, warnings));
                                 }
                             }
-                            // If there is an error simplifying the schema (e.g. an AnyOf), skip the collection
+                            // If the schema cannot be processed, skip the collection with a warning
                             Err(e) => {
                                 log::error!("Error while processing collection metadata: {}", e);
+                                warnings.push(Error::CollectionSchemaProcessing(
+                                    collection_name,
+                                    Box::new(e),
+                                ));
                                 continue;
                             }
                         }
@@ -630,27 +826,27 @@ impl MongoFields {
                 }
                 let db_name = self.dbs.pop_front().unwrap();
                 self.collections_for_db = Some(
-                mongo_connection
-                    .client
-                    .database(&db_name)
-                    .run_command(
-                    doc! { "listCollections": 1, "nameOnly": true, "authorizedCollections": true},
-                ).await.unwrap().get_document("cursor").map(|doc| {
-                    doc.get_array("firstBatch").unwrap().iter().map(|val| {
-                        let doc = val.as_document().unwrap();
-                        let name = doc.get_str("name").unwrap().to_string();
-                        let collection_type = match doc.get_str("type").unwrap() {
-                            "collection" => CollectionType::Collection,
-                            "view" => CollectionType::View,
-                            _ => CollectionType::Collection
-                        };
-                        MongoODBCCollectionSpecification::new(name, collection_type)
-                    }).collect()
-                }).unwrap_or_else(|_| {
-                    log::error!("Error getting collections for database {db_name}");
-                    VecDeque::new()
-                }),
-            );
+                    // The columns do not need the collection options, only their names.
+                    list_collection_specifications(
+                        &mongo_connection.client.database(&db_name),
+                        true,
+                    )
+                    .await
+                    .map(|collections| {
+                        collections
+                            .into_iter()
+                            .filter(|collection| {
+                                mongo_connection
+                                    .catalog_filter
+                                    .is_collection_visible(&collection.name)
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_else(|e| {
+                        log::error!("Error getting collections for database {db_name}: {e}");
+                        VecDeque::new()
+                    }),
+                );
                 self.current_db_name = db_name;
             }
         })
@@ -706,8 +902,8 @@ impl MongoStatement for MongoFields {
     // Fails if the first row as not been retrieved (next must be called at least once before getValue).
     fn get_value(&self, col_index: u16, _: Option<u16>) -> Result<Option<Bson>> {
         // use self.current_col_metadata[current_field_for_collection]
-        // 1 -> TABLE_CAT
-        // 2 -> TABLE_SCHEM  (NULL)
+        // 1 -> TABLE_CAT (NULL when databases are schemas)
+        // 2 -> TABLE_SCHEM  (NULL when databases are catalogs)
         // 3 -> TABLE_NAME
         // 4 -> COLUMN_NAME
         // 5 -> DATA_TYPE
@@ -731,9 +927,9 @@ impl MongoStatement for MongoFields {
         };
         Ok(Some(match col_index {
             // TABLE_CAT
-            1 => Bson::String(self.current_db_name.clone()),
+            1 => self.database_as.catalog_and_schema(&self.current_db_name).0,
             // TABLE_SCHEM
-            2 => Bson::Null,
+            2 => self.database_as.catalog_and_schema(&self.current_db_name).1,
             // TABLE_NAME
             3 => Bson::String(get_meta_data()?.table_name.clone()),
             // COLUMN_NAME
@@ -781,7 +977,7 @@ impl MongoStatement for MongoFields {
             // NULLABLE
             11 => Bson::Int32(get_meta_data()?.nullability as i32),
             // REMARKS
-            12 => Bson::String("".to_string()),
+            12 => Bson::String(get_meta_data()?.remarks.clone()),
             // COLUMN_DEF
             13 => Bson::Null,
             // SQL_DATA_TYPE
@@ -822,156 +1018,13 @@ impl MongoStatement for MongoFields {
     }
 
     fn get_resultset_metadata(&self, max_string_length: Option<u16>) -> &Vec<MongoColMetadata> {
-        FIELDS_METADATA.get_or_init(|| {
-            vec![
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "TABLE_CAT".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "TABLE_SCHEM".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "TABLE_NAME".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "COLUMN_NAME".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "DATA_TYPE".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "TYPE_NAME".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "COLUMN_SIZE".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "BUFFER_LENGTH".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "DECIMAL_DIGITS".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "NUM_PREC_RADIX".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "NULLABLE".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "REMARKS".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "COLUMN_DEF".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "SQL_DATA_TYPE".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "SQL_DATETIME_SUB".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "CHAR_OCTET_LENGTH".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NULLABLE,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "ORDINAL_POSITION".to_string(),
-                    BsonTypeInfo::INT,
-                    max_string_length,
-                    Nullability::SQL_NO_NULLS,
-                ),
-                MongoColMetadata::new_metadata_from_bson_type_info_default(
-                    "",
-                    "".to_string(),
-                    "IS_NULLABLE".to_string(),
-                    BsonTypeInfo::STRING,
-                    max_string_length,
-                    // the docs do not say 'not NULL', but they also say the only possible values for
-                    // ISO SQL are 'YES' and 'NO'. And even for non-ISO SQL they only allow additionally
-                    // the empty varchar... so NO_NULLS seems correct to me.
-                    Nullability::SQL_NO_NULLS,
-                ),
-            ]
-        })
+        match self.database_as {
+            DatabaseAs::Catalog => {
+                FIELDS_METADATA.get_or_init(|| init_fields_metadata(max_string_length))
+            }
+            DatabaseAs::Schema => FIELDS_AS_SCHEMAS_METADATA.get_or_init(|| {
+                DatabaseAs::Schema.adjust_metadata(init_fields_metadata(max_string_length))
+            }),
+        }
     }
 }
@@ -320,12 +320,23 @@ pub mod simplified {
                     0 => Err(Error::InvalidResultSetJsonSchema("Empty anyOf is invalid")),
                     // AnyOf with a single schema is equivalent to the schema.
                     1 => Schema::try_from(any_of.into_iter().next().unwrap()),
-                    _ => Ok(Schema::AnyOf(
-                        any_of
-                            .into_iter()
-                            .map(Atomic::try_from)
-                            .collect::<Result<BTreeSet<Atomic>>>()?,
-                    )),
+                    _ => {
+                        let mut atomics = BTreeSet::new();
+                        for schema in any_of {
+                            match Schema::try_from(schema)? {
+                                Schema::Atomic(atomic) => {
+                                    atomics.insert(atomic);
+                                }
+                                // A nested AnyOf is merged into the enclosing one.
+                                Schema::AnyOf(nested) => atomics.extend(nested),
+                            }
+                        }
+                        Ok(if atomics.len() == 1 {
+                            Schema::Atomic(atomics.pop_first().unwrap())
+                        } else {
+                            Schema::AnyOf(atomics)
+                        })
+                    }
                 },
             }
         }
@@ -344,6 +355,8 @@ pub mod simplified {
     impl From<Schema> for BsonTypeInfo {
         fn from(v: Schema) -> Self {
             match v {
+                // A decimal column is SQL_DECIMAL, decimals in other schemas keep MONGO_DECIMAL.
+                Schema::Atomic(Atomic::Scalar(BsonTypeName::Decimal)) => BsonTypeInfo::DECIMAL,
                 Schema::Atomic(a) => a.into(),
                 Schema::AnyOf(b) => {
                     if b.len() == 2 {
@@ -352,7 +365,7 @@ pub mod simplified {
                             .filter(|a| !matches!(a, Atomic::Scalar(BsonTypeName::Null)))
                             .collect::<Vec<Atomic>>();
                         if atomics.len() == 1 {
-                            atomics.first().unwrap().to_owned().into()
+                            Schema::Atomic(atomics.first().unwrap().to_owned()).into()
                         } else {
                             BsonTypeInfo::BSON
                         }
@@ -870,6 +883,41 @@ mod unit {
             }
         );
 
+        try_from_test!(
+            nested_any_of_is_merged,
+            variant = Schema,
+            expected = Ok(Schema::AnyOf(set! {
+                Atomic::Scalar(BsonTypeName::Int),
+                Atomic::Scalar(BsonTypeName::Long),
+                Atomic::Scalar(BsonTypeName::String)
+            })),
+            input = json_schema::Schema {
+                any_of: Some(vec![
+                    json_schema::Schema {
+                        bson_type: Some(BsonType::Single(BsonTypeName::Int)),
+                        ..Default::default()
+                    },
+                    json_schema::Schema {
+                        any_of: Some(vec![
+                            json_schema::Schema {
+                                bson_type: Some(BsonType::Single(BsonTypeName::Int)),
+                                ..Default::default()
+                            },
+                            json_schema::Schema {
+                                bson_type: Some(BsonType::Multiple(vec![
+                                    BsonTypeName::Long,
+                                    BsonTypeName::String
+                                ])),
+                                ..Default::default()
+                            }
+                        ]),
+                        ..Default::default()
+                    }
+                ]),
+                ..Default::default()
+            }
+        );
+
         try_from_test!(
             any_of_one,
             variant = Schema,
@@ -1,16 +1,20 @@
 #![allow(dead_code)]
+mod array_tables;
 mod bson_type_info;
 use bson_type_info::BsonTypeInfo;
 pub use bson_type_info::TypeMode;
 pub use bson_type_info::MAX_STRING_SIZE;
 pub use bson_type_info::SQL_SEARCHABLE;
 
+mod catalog_filter;
+pub use catalog_filter::CatalogFilter;
+pub mod client_pool;
 mod collections;
 pub use collections::MongoCollections;
 mod conn;
 pub use conn::MongoConnection;
 mod databases;
-pub use databases::MongoDatabases;
+pub use databases::{DatabaseAs, MongoDatabases};
 mod table_types;
 pub use table_types::MongoTableTypes;
 mod err;
@@ -19,10 +23,14 @@ mod fields;
 pub use fields::MongoFields;
 pub mod col_metadata;
 pub mod json_schema;
-pub use col_metadata::MongoColMetadata;
+pub use col_metadata::{MongoColMetadata, PolymorphicColumns};
 mod query;
 pub use query::MongoQuery;
+mod read_options;
+pub use read_options::ReadOptions;
 pub mod mock_query;
+mod schema_drift;
+pub use schema_drift::SchemaDrift;
 mod stmt;
 pub use stmt::MongoStatement;
 pub mod odbc_uri;
@@ -31,9 +39,15 @@ mod type_info;
 pub use type_info::MongoTypesInfo;
 pub mod util;
 pub use primary_keys::MongoPrimaryKeys;
+mod liveness;
+pub use liveness::Liveness;
+mod retry;
+pub use retry::RetryPolicy;
 mod foreign_keys;
 pub use foreign_keys::MongoForeignKeys;
 pub mod cluster_type;
+pub mod credential_command;
 mod mongosqltranslate;
 pub mod oidc_auth;
+pub mod oidc_token_cache;
 pub mod test_config;
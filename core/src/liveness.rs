@@ -0,0 +1,128 @@
+use crate::err::{Error, Result};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The time during which the last interaction with the server is trusted to tell whether the
+/// connection is dead, before a ping is sent to check it again.
+pub const PING_INTERVAL: Duration = Duration::from_secs(10);
+/// The time a ping can take when the connection has no operation timeout.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Liveness is the outcome of the last interaction of a connection with the server. A
+/// connection is dead once the server could not be reached, and stays dead until it is closed.
+#[derive(Debug, Default)]
+pub struct Liveness(Mutex<LivenessState>);
+
+#[derive(Debug, Default)]
+struct LivenessState {
+    // The reason the connection is dead.
+    dead: Option<String>,
+    last_interaction: Option<Instant>,
+}
+
+impl Liveness {
+    /// Updates the liveness from the result of an interaction with the server. Errors which are
+    /// not caused by the connection, like a failed command, still mean the server was reached.
+    pub fn record<T>(&self, result: &Result<T>) {
+        let mut state = self.0.lock().unwrap();
+        match result {
+            Err(e) if e.is_connection_failure() => {
+                log::error!("the connection is dead: {e}");
+                state.dead = Some(e.to_string());
+            }
+            _ => {}
+        }
+        state.last_interaction = Some(Instant::now());
+    }
+
+    /// Records a ping which did not complete within the given timeout.
+    pub(crate) fn record_timeout(&self, timeout: Duration) {
+        let mut state = self.0.lock().unwrap();
+        log::error!("the connection is dead: the ping did not complete within {timeout:?}");
+        state.dead = Some(format!("the ping did not complete within {timeout:?}"));
+        state.last_interaction = Some(Instant::now());
+    }
+
+    /// Returns Error::ConnectionDead when the connection is dead.
+    pub fn check(&self) -> Result<()> {
+        match &self.0.lock().unwrap().dead {
+            Some(reason) => Err(Error::ConnectionDead(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the server could not be reached, without checking it again.
+    pub fn is_dead(&self) -> bool {
+        self.0.lock().unwrap().dead.is_some()
+    }
+
+    /// Whether the connection has to be pinged to know if it is dead: it is not known to be dead
+    /// and the server was not reached for PING_INTERVAL.
+    pub(crate) fn needs_ping(&self, now: Instant) -> bool {
+        let state = self.0.lock().unwrap();
+        state.dead.is_none()
+            && state
+                .last_interaction
+                .is_none_or(|last| now.saturating_duration_since(last) >= PING_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use constants::COMMUNICATION_LINK_FAILURE;
+    use mongodb::error::ErrorKind;
+    use std::sync::Arc;
+
+    fn io_error(kind: std::io::ErrorKind) -> Error {
+        Error::QueryExecutionFailed(
+            ErrorKind::Io(Arc::new(std::io::Error::new(kind, "connection reset"))).into(),
+        )
+    }
+
+    #[test]
+    fn connection_failures_kill_the_connection() {
+        let liveness = Liveness::default();
+        assert!(liveness.needs_ping(Instant::now()));
+
+        liveness.record(&Ok(()));
+        assert!(!liveness.needs_ping(Instant::now()));
+        assert!(liveness.needs_ping(Instant::now() + PING_INTERVAL));
+
+        // The server answered, the connection is alive.
+        liveness.record::<()>(&Err(Error::QueryExecutionFailed(
+            ErrorKind::Command(
+                mongodb::bson::from_document(
+                    mongodb::bson::doc! {"code": 11601, "codeName": "Interrupted", "errmsg": ""},
+                )
+                .unwrap(),
+            )
+            .into(),
+        )));
+        // A timeout is not a connection failure.
+        liveness.record::<()>(&Err(io_error(std::io::ErrorKind::TimedOut)));
+        assert!(liveness.check().is_ok());
+
+        liveness.record::<()>(&Err(io_error(std::io::ErrorKind::ConnectionReset)));
+        assert!(liveness.is_dead());
+        assert!(!liveness.needs_ping(Instant::now() + PING_INTERVAL));
+        let e = liveness.check().unwrap_err();
+        assert_eq!(COMMUNICATION_LINK_FAILURE, e.get_sql_state());
+
+        // A dead connection stays dead.
+        liveness.record(&Ok(()));
+        assert!(liveness.is_dead());
+    }
+
+    #[test]
+    fn ping_timeout_kills_the_connection() {
+        let liveness = Liveness::default();
+        liveness.record_timeout(Duration::from_millis(100));
+        assert_eq!(
+            "Communication link failure, the connection is dead: the ping did not complete within 100ms",
+            liveness.check().unwrap_err().to_string()
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{
-    col_metadata::MongoColMetadata, err::Result, stmt::MongoStatement, Error, MongoConnection,
+    col_metadata::MongoColMetadata,
+    err::Result,
+    schema_drift::{SchemaDrift, SchemaDriftTracker},
+    stmt::MongoStatement,
+    Error, MongoConnection,
 };
 use mongodb::bson::{document::ValueAccessError, Bson, Document};
 
@@ -11,6 +15,10 @@ pub struct MongoQuery {
     resultset_metadata: Vec<MongoColMetadata>,
     // The current index in the resultset.
     current: Option<usize>,
+    // Checks the rows against the result set metadata.
+    schema_drift: SchemaDriftTracker,
+    // The schema drift policy used without a connection.
+    schema_drift_policy: SchemaDrift,
 }
 
 impl MongoQuery {
@@ -19,14 +27,22 @@ impl MongoQuery {
             resultset,
             resultset_metadata,
             current: None,
+            schema_drift: SchemaDriftTracker::default(),
+            schema_drift_policy: SchemaDrift::default(),
         }
     }
+
+    pub fn with_schema_drift(mut self, schema_drift_policy: SchemaDrift) -> Self {
+        self.schema_drift_policy = schema_drift_policy;
+        self
+    }
 }
 
 impl MongoStatement for MongoQuery {
     // Move the current index to the next Document in the Vec.
     // Return true if moving was successful, false otherwise.
-    fn next(&mut self, _: Option<&MongoConnection>) -> Result<(bool, Vec<Error>)> {
+    // The values of the row are checked with the schema_drift policy of the connection, if any.
+    fn next(&mut self, connection: Option<&MongoConnection>) -> Result<(bool, Vec<Error>)> {
         if let Some(current) = self.current {
             self.current = Some(current + 1);
         } else {
@@ -34,7 +50,12 @@ impl MongoStatement for MongoQuery {
         }
         let current = self.current.unwrap();
         if current < self.resultset.len() {
-            return Ok((true, vec![]));
+            let warnings = self.schema_drift.check_row(
+                connection.map_or(self.schema_drift_policy, |c| c.schema_drift),
+                &self.resultset_metadata,
+                &self.resultset[current],
+            )?;
+            return Ok((true, warnings));
         }
         Ok((false, vec![]))
     }
@@ -42,13 +63,16 @@ impl MongoStatement for MongoQuery {
     // Get the BSON value for the cell at the given colIndex on the current row.
     // Fails if the first row has not been retrieved (next must be called at least once before get_value).
     fn get_value(&self, col_index: u16, max_string_length: Option<u16>) -> Result<Option<Bson>> {
+        if self.schema_drift.is_null(col_index) {
+            return Ok(None);
+        }
         let md = self
             .get_col_metadata(col_index, max_string_length)
             .map_err(|_| Error::ColIndexOutOfBounds(col_index))?;
         let datasource = self.resultset[self.current.ok_or(Error::InvalidCursorState)?]
             .get_document(&md.table_name)
             .map_err(|e: ValueAccessError| Error::ValueAccess(col_index.to_string(), e))?;
-        let column = datasource.get(&md.col_name);
+        let column = md.get_field(datasource);
         Ok(column.cloned())
     }
 
@@ -61,4 +85,8 @@ impl MongoStatement for MongoQuery {
         self.current = None;
         self.resultset = vec![];
     }
+
+    fn schema_drift_count(&self) -> u64 {
+        self.schema_drift.count()
+    }
 }
@@ -0,0 +1,499 @@
+use directories::BaseDirs;
+use lazy_static::lazy_static;
+use mongodb::options::oidc::IdpServerResponse;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const CACHE_FILE_NAME: &str = "oidc_token_cache";
+const KEY_FILE_NAME: &str = "oidc_token_cache.key";
+const LOCK_FILE_NAME: &str = "oidc_token_cache.lock";
+const KEY_LEN: usize = 32;
+// Access tokens expiring sooner than this are not reused, they would not outlive the connection.
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    // The cached access token this process returned already for each cache entry, as its SHA-256
+    // digest along with its expiration. The driver only asks for a token again when the one it has
+    // is expired or was rejected by the server, so they are never returned twice. An entry is
+    // replaced when the token of the cache entry changes, and removed once the token expires.
+    static ref RETURNED_ACCESS_TOKENS: Mutex<HashMap<String, ReturnedToken>> =
+        Mutex::new(HashMap::new());
+}
+
+// ReturnedToken is the digest of an access token returned from the cache, and when it expires in
+// seconds since the Unix epoch.
+struct ReturnedToken {
+    digest: Vec<u8>,
+    expires_at: Option<u64>,
+}
+
+// The number of temporary files this process created, which makes their names unique.
+static TMP_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// TokenCacheMode is whether the tokens of the OIDC human flows are kept across connections and
+/// processes, in a file only readable by the user. It comes from the oidc_token_cache connection
+/// string/DSN option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenCacheMode {
+    /// The tokens are read from and written to the cache.
+    #[default]
+    On,
+    /// The cache is not used.
+    Off,
+    /// The cache is cleared when connecting, then used.
+    Clear,
+}
+
+impl std::str::FromStr for TokenCacheMode {
+    type Err = crate::err::Error;
+
+    fn from_str(s: &str) -> crate::err::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "on" => Ok(TokenCacheMode::On),
+            "off" => Ok(TokenCacheMode::Off),
+            "clear" => Ok(TokenCacheMode::Clear),
+            _ => Err(crate::err::Error::InvalidUriFormat(format!(
+                "invalid oidc_token_cache value '{s}', expected 'on', 'off' or 'clear'"
+            ))),
+        }
+    }
+}
+
+/// TokenCacheKey identifies the tokens of a user of an identity provider client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenCacheKey {
+    pub issuer: String,
+    pub client_id: String,
+    pub username: Option<String>,
+}
+
+impl TokenCacheKey {
+    // The entries of the cache are keyed on the JSON array of the key parts, which cannot be
+    // ambiguous.
+    fn entry_key(&self) -> String {
+        serde_json::json!([self.issuer, self.client_id, self.username]).to_string()
+    }
+}
+
+/// CachedToken is a token response kept in the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedToken {
+    pub access_token: String,
+    /// When the access token expires, in seconds since the Unix epoch.
+    pub expires_at: Option<u64>,
+    pub refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    fn new(response: &IdpServerResponse) -> Self {
+        let expires_at = response.expires.map(|expires| {
+            (SystemTime::now() + expires.saturating_duration_since(Instant::now()))
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        });
+        CachedToken {
+            access_token: response.access_token.clone(),
+            expires_at,
+            refresh_token: response.refresh_token.clone(),
+        }
+    }
+
+    /// Returns the cached access token of the key as a callback response if it is still valid
+    /// and this process did not return it already.
+    pub fn reusable_response(&self, key: &TokenCacheKey) -> Option<IdpServerResponse> {
+        let expires = match self.expires_at {
+            Some(expires_at) => {
+                let remaining = (UNIX_EPOCH + Duration::from_secs(expires_at))
+                    .duration_since(SystemTime::now())
+                    .ok()
+                    .filter(|remaining| *remaining > EXPIRATION_MARGIN)?;
+                Some(Instant::now() + remaining)
+            }
+            None => None,
+        };
+        let token_digest = digest(&SHA256, self.access_token.as_bytes())
+            .as_ref()
+            .to_vec();
+        let mut returned = RETURNED_ACCESS_TOKENS.lock().unwrap();
+        if returned
+            .get(&key.entry_key())
+            .is_some_and(|token| token.digest == token_digest)
+        {
+            return None;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        returned.retain(|_, token| token.expires_at.is_none_or(|expires_at| expires_at > now));
+        returned.insert(
+            key.entry_key(),
+            ReturnedToken {
+                digest: token_digest,
+                expires_at: self.expires_at,
+            },
+        );
+        Some(
+            IdpServerResponse::builder()
+                .access_token(self.access_token.clone())
+                .expires(expires)
+                .refresh_token(self.refresh_token.clone())
+                .build(),
+        )
+    }
+}
+
+/// TokenCache is the encrypted file the OIDC tokens are kept in. The file is in the cache
+/// directory of the user, the key it is encrypted with in their configuration directory, and
+/// both are only readable by the user. Processes using the cache take an advisory lock on a lock
+/// file next to it, so that concurrent updates are not lost and a single key is generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenCache {
+    cache_path: PathBuf,
+    key_path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl TokenCache {
+    /// Returns the token cache of the current user, if their directories are known.
+    pub fn for_current_user() -> Option<Self> {
+        let dirs = BaseDirs::new()?;
+        let driver_dir = |dir: &Path| dir.join("MongoDB").join("Atlas SQL ODBC");
+        Some(TokenCache {
+            cache_path: driver_dir(dirs.cache_dir()).join(CACHE_FILE_NAME),
+            key_path: driver_dir(dirs.config_dir()).join(KEY_FILE_NAME),
+            lock_path: driver_dir(dirs.cache_dir()).join(LOCK_FILE_NAME),
+        })
+    }
+
+    /// Returns the cached token of the given key, if any.
+    pub fn get(&self, key: &TokenCacheKey) -> io::Result<Option<CachedToken>> {
+        let _lock = self.lock(false)?;
+        Ok(self.load()?.remove(&key.entry_key()))
+    }
+
+    /// Caches the token response of the given key, replacing the previous one.
+    pub fn insert(&self, key: &TokenCacheKey, response: &IdpServerResponse) -> io::Result<()> {
+        let _lock = self.lock(true)?;
+        let mut entries = self.load()?;
+        entries.insert(key.entry_key(), CachedToken::new(response));
+        self.store(&entries)
+    }
+
+    /// Removes the cached token of the given key, if any.
+    pub fn remove(&self, key: &TokenCacheKey) -> io::Result<()> {
+        let _lock = self.lock(true)?;
+        let mut entries = self.load()?;
+        if entries.remove(&key.entry_key()).is_some() {
+            self.store(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all the cached tokens, and the key they were encrypted with.
+    pub fn clear(&self) -> io::Result<()> {
+        let _lock = self.lock(true)?;
+        for path in [&self.cache_path, &self.key_path] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Locks the cache until the returned file is dropped, exclusively for the writers, which read,
+    // modify and write the cache and may generate its key, and shared for the readers.
+    fn lock(&self, exclusive: bool) -> io::Result<fs::File> {
+        if let Some(dir) = self.lock_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = private_options().read(true).open(&self.lock_path)?;
+        if exclusive {
+            file.lock()?;
+        } else {
+            file.lock_shared()?;
+        }
+        Ok(file)
+    }
+
+    // Reads the cached tokens. A cache that cannot be decrypted, because its key was removed
+    // or it was tampered with, is empty.
+    fn load(&self) -> io::Result<HashMap<String, CachedToken>> {
+        let contents = match fs::read(&self.cache_path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e),
+        };
+        let Some(key) = self.key(false)? else {
+            return Ok(HashMap::new());
+        };
+        if contents.len() < NONCE_LEN {
+            return Ok(HashMap::new());
+        }
+        let (nonce, ciphertext) = contents.split_at(NONCE_LEN);
+        let mut in_out = ciphertext.to_vec();
+        let Ok(plaintext) = key.open_in_place(
+            Nonce::try_assume_unique_for_key(nonce).map_err(crypto_error)?,
+            Aad::empty(),
+            &mut in_out,
+        ) else {
+            log::warn!("the OIDC token cache cannot be decrypted, it is ignored");
+            return Ok(HashMap::new());
+        };
+        Ok(serde_json::from_slice(plaintext).unwrap_or_default())
+    }
+
+    // Writes the cached tokens, encrypted with a new nonce.
+    fn store(&self, entries: &HashMap<String, CachedToken>) -> io::Result<()> {
+        let key = self.key(true)?.ok_or_else(|| crypto_error(()))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce).map_err(crypto_error)?;
+        let mut in_out = serde_json::to_vec(entries)?;
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(crypto_error)?;
+        write_private(&self.cache_path, &[nonce.as_slice(), &in_out].concat())
+    }
+
+    // Reads the encryption key, generating it first when create is set and there is none.
+    fn key(&self, create: bool) -> io::Result<Option<LessSafeKey>> {
+        let bytes = match fs::read(&self.key_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && create => {
+                let mut bytes = vec![0u8; KEY_LEN];
+                SystemRandom::new().fill(&mut bytes).map_err(crypto_error)?;
+                write_private(&self.key_path, &bytes)?;
+                bytes
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(UnboundKey::new(&AES_256_GCM, &bytes)
+            .ok()
+            .map(LessSafeKey::new))
+    }
+}
+
+/// Removes all the OIDC tokens cached for the current user.
+pub fn clear_token_cache() -> io::Result<()> {
+    TokenCache::for_current_user().map_or(Ok(()), |cache| cache.clear())
+}
+
+fn crypto_error<E>(_: E) -> io::Error {
+    io::Error::other("OIDC token cache encryption failed")
+}
+
+// Returns the options to open a file for writing, creating it only readable and writable by the
+// user.
+fn private_options() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+// Writes a file only readable and writable by the user, through a temporary file of its own which
+// is renamed over it, so that readers never see it partially written.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TMP_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = path.with_file_name(tmp_name);
+    let result = private_options()
+        .truncate(true)
+        .open(&tmp_path)
+        .and_then(|mut file| io::Write::write_all(&mut file, contents))
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    // Returns a cache in a directory of its own, removed first.
+    fn test_cache(name: &str) -> TokenCache {
+        let dir = std::env::temp_dir()
+            .join("oidc_token_cache_tests")
+            .join(format!("{}_{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        TokenCache {
+            cache_path: dir.join("cache").join(CACHE_FILE_NAME),
+            key_path: dir.join("config").join(KEY_FILE_NAME),
+            lock_path: dir.join("cache").join(LOCK_FILE_NAME),
+        }
+    }
+
+    fn key(username: Option<&str>) -> TokenCacheKey {
+        TokenCacheKey {
+            issuer: "https://issuer.example.com".to_string(),
+            client_id: "client".to_string(),
+            username: username.map(String::from),
+        }
+    }
+
+    fn response(access_token: &str, expires_in: Option<u64>) -> IdpServerResponse {
+        IdpServerResponse::builder()
+            .access_token(access_token.to_string())
+            .expires(expires_in.map(|secs| Instant::now() + Duration::from_secs(secs)))
+            .refresh_token(Some(format!("{access_token}_refresh")))
+            .build()
+    }
+
+    #[test]
+    fn parse_mode() {
+        assert_eq!(TokenCacheMode::On, "on".parse().unwrap());
+        assert_eq!(TokenCacheMode::Off, "OFF".parse().unwrap());
+        assert_eq!(TokenCacheMode::Clear, "Clear".parse().unwrap());
+        assert!("1".parse::<TokenCacheMode>().is_err());
+    }
+
+    #[test]
+    fn tokens_are_cached_per_key() {
+        let cache = test_cache("per_key");
+        assert_eq!(None, cache.get(&key(Some("alice"))).unwrap());
+        cache
+            .insert(&key(Some("alice")), &response("alice_token", Some(3600)))
+            .unwrap();
+        cache
+            .insert(&key(None), &response("anonymous_token", None))
+            .unwrap();
+        let cached = cache.get(&key(Some("alice"))).unwrap().unwrap();
+        assert_eq!("alice_token", cached.access_token);
+        assert_eq!(
+            Some("alice_token_refresh".to_string()),
+            cached.refresh_token
+        );
+        assert!(cached.expires_at.is_some());
+        let cached = cache.get(&key(None)).unwrap().unwrap();
+        assert_eq!("anonymous_token", cached.access_token);
+        assert_eq!(None, cached.expires_at);
+        assert_eq!(None, cache.get(&key(Some("bob"))).unwrap());
+
+        cache.remove(&key(Some("alice"))).unwrap();
+        assert_eq!(None, cache.get(&key(Some("alice"))).unwrap());
+        assert!(cache.get(&key(None)).unwrap().is_some());
+    }
+
+    #[test]
+    fn cache_is_encrypted() {
+        let cache = test_cache("encrypted");
+        cache
+            .insert(&key(Some("alice")), &response("secret_token", None))
+            .unwrap();
+        let contents = fs::read(&cache.cache_path).unwrap();
+        assert!(!String::from_utf8_lossy(&contents).contains("secret_token"));
+
+        // Without its key, the cache is empty
+        fs::remove_file(&cache.key_path).unwrap();
+        assert_eq!(None, cache.get(&key(Some("alice"))).unwrap());
+        // and it is replaced on the next write.
+        cache
+            .insert(&key(Some("bob")), &response("bob_token", None))
+            .unwrap();
+        assert!(cache.get(&key(Some("bob"))).unwrap().is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn cache_is_only_readable_by_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+        let cache = test_cache("permissions");
+        cache.insert(&key(None), &response("token", None)).unwrap();
+        for path in [&cache.cache_path, &cache.key_path] {
+            assert_eq!(
+                0o600,
+                fs::metadata(path).unwrap().permissions().mode() & 0o777
+            );
+        }
+    }
+
+    #[test]
+    fn concurrent_inserts_are_kept() {
+        let cache = test_cache("concurrent");
+        let users = (0..8).map(|i| format!("user{i}")).collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            for user in &users {
+                let cache = &cache;
+                scope.spawn(move || {
+                    cache
+                        .insert(&key(Some(user)), &response(user, None))
+                        .unwrap()
+                });
+            }
+        });
+        for user in &users {
+            assert_eq!(
+                *user,
+                cache.get(&key(Some(user))).unwrap().unwrap().access_token
+            );
+        }
+        // The temporary files were all renamed.
+        assert_eq!(
+            2,
+            fs::read_dir(cache.cache_path.parent().unwrap())
+                .unwrap()
+                .count()
+        );
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let cache = test_cache("clear");
+        cache.clear().unwrap();
+        cache.insert(&key(None), &response("token", None)).unwrap();
+        cache.clear().unwrap();
+        assert!(!cache.cache_path.exists());
+        assert!(!cache.key_path.exists());
+        assert_eq!(None, cache.get(&key(None)).unwrap());
+    }
+
+    #[test]
+    fn access_tokens_are_reused_once_while_valid() {
+        let valid = CachedToken::new(&response("reusable_token", Some(3600)));
+        let returned = valid.reusable_response(&key(Some("reused"))).unwrap();
+        assert_eq!("reusable_token", returned.access_token);
+        assert!(returned.expires.is_some());
+        assert!(valid.reusable_response(&key(Some("reused"))).is_none());
+
+        // A new token of the entry replaces the one returned before.
+        let refreshed = CachedToken::new(&response("refreshed_token", Some(3600)));
+        assert!(refreshed.reusable_response(&key(Some("reused"))).is_some());
+        assert!(valid.reusable_response(&key(Some("reused"))).is_some());
+
+        let expiring = CachedToken::new(&response("expiring_token", Some(10)));
+        assert!(expiring.reusable_response(&key(Some("reused"))).is_none());
+    }
+}
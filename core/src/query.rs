@@ -1,12 +1,17 @@
 use crate::{
+    array_tables::{self, ArrayTable},
     cluster_type::MongoClusterType,
     col_metadata::{MongoColMetadata, ResultSetSchema, SqlGetSchemaResponse},
     conn::MongoConnection,
     err::Result,
+    json_schema::simplified::Schema,
     mongosqltranslate::{
         libmongosqltranslate_run_command, CommandResponse, GetNamespaces, Namespace, Translate,
         TranslateCommandResponse,
     },
+    read_options::ReadOptions,
+    retry::ResumeKey,
+    schema_drift::SchemaDriftTracker,
     stmt::MongoStatement,
     Error, TypeMode,
 };
@@ -17,7 +22,7 @@ use mongodb::{
     error::{CommandError, ErrorKind},
     Cursor, Database,
 };
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::time::Duration;
 
 const BATCH_SIZE_REPLACEMENT_THRESHOLD: u32 = 100;
@@ -38,6 +43,31 @@ pub struct MongoQuery {
     pub pipeline: Vec<Document>,
     // The query timeout
     pub query_timeout: Option<u32>,
+    // Checks the fetched rows against the result set metadata.
+    schema_drift: SchemaDriftTracker,
+    // The unique sort key of the pipeline, when the reads can be resumed after a transient error.
+    resume_key: Option<ResumeKey>,
+    // The options of the execution, to run the pipeline again when resuming.
+    execution: Option<Execution>,
+    // Where the read resumes, None once a row without the resume key was read.
+    resume_point: Option<ResumePoint>,
+    // The retries of the last prepare or execute.
+    warnings: Vec<Error>,
+}
+
+// Execution is what the pipeline was executed with.
+#[derive(Debug, Clone)]
+struct Execution {
+    stmt_id: Bson,
+    rowset_size: u32,
+    read_options: ReadOptions,
+}
+
+// ResumePoint is where a read resumes: from the start, or after the row with the given key.
+#[derive(Debug, Clone)]
+enum ResumePoint {
+    Start,
+    After(Bson),
 }
 
 impl MongoQuery {
@@ -160,7 +190,63 @@ impl MongoQuery {
         }
     }
 
-    // Create a MongoQuery with only the resultset_metadata.
+    // Replace the references to array tables in a query with derived tables unwinding the
+    // arrays of their collection. A name which is also the name of a collection refers to the
+    // collection.
+    fn rewrite_array_tables(
+        client: &MongoConnection,
+        working_db: &str,
+        query: &str,
+    ) -> Result<String> {
+        let references = array_tables::table_references(query);
+        if references.is_empty() {
+            return Ok(query.to_string());
+        }
+        let _guard = client.runtime.enter();
+        let replacements = client.runtime.block_on(async {
+            let mut collections_by_db: HashMap<String, Vec<String>> = HashMap::new();
+            let mut replacements = vec![];
+            for reference in references {
+                let db_name = reference.db.as_deref().unwrap_or(working_db);
+                let db = client.client.database(db_name);
+                if !collections_by_db.contains_key(db_name) {
+                    let names = db
+                        .list_collection_names()
+                        .await
+                        .map_err(Error::QueryExecutionFailed)?;
+                    collections_by_db.insert(db_name.to_string(), names);
+                }
+                let collections = &collections_by_db[db_name];
+                if collections.contains(&reference.name) {
+                    continue;
+                }
+                // The name of an array table starts with the name of its collection.
+                for collection in collections
+                    .iter()
+                    .filter(|collection| reference.name.starts_with(&format!("{collection}_")))
+                {
+                    let Ok(schema) = ResultSetSchema::for_collection(client, &db, collection)
+                        .await
+                        .and_then(|schema| Schema::try_from(schema.schema))
+                    else {
+                        continue;
+                    };
+                    if let Some(table) = ArrayTable::from_collection_schema(collection, &schema)
+                        .into_iter()
+                        .find(|table| table.name == reference.name)
+                    {
+                        replacements.push((reference, table));
+                        break;
+                    }
+                }
+            }
+            Ok::<_, Error>(replacements)
+        })?;
+        Ok(array_tables::rewrite_query(query, &replacements))
+    }
+
+    // Create a MongoQuery with only the resultset_metadata. Transient errors are retried
+    // according to the retry policy of the connection, and every retry is a warning.
     pub fn prepare(
         client: &MongoConnection,
         current_db: Option<String>,
@@ -168,9 +254,42 @@ impl MongoQuery {
         query: &str,
         type_mode: TypeMode,
         max_string_length: Option<u16>,
+    ) -> Result<Self> {
+        client.check_alive()?;
+        let mut warnings = vec![];
+        let _guard = client.runtime.enter();
+        let mut query =
+            client.record(client.retry_policy.run(&client.runtime, &mut warnings, || {
+                Self::prepare_once(
+                    client,
+                    current_db.clone(),
+                    query_timeout,
+                    query,
+                    type_mode,
+                    max_string_length,
+                )
+            }))?;
+        query.warnings = warnings;
+        Ok(query)
+    }
+
+    fn prepare_once(
+        client: &MongoConnection,
+        current_db: Option<String>,
+        query_timeout: Option<u32>,
+        query: &str,
+        type_mode: TypeMode,
+        max_string_length: Option<u16>,
     ) -> Result<Self> {
         let working_db = current_db.as_ref().ok_or(Error::NoDatabase)?;
         let db = client.client.database(working_db);
+        let rewritten_query;
+        let query = if client.array_tables {
+            rewritten_query = Self::rewrite_array_tables(client, working_db, query)?;
+            rewritten_query.as_str()
+        } else {
+            query
+        };
 
         let (pipeline, current_collection, result_set_schema) = match client.cluster_type {
             MongoClusterType::AtlasDataFederation => {
@@ -237,19 +356,139 @@ impl MongoQuery {
             }
         };
 
-        let metadata =
-            result_set_schema.process_result_metadata(working_db, type_mode, max_string_length)?;
+        let metadata = result_set_schema.process_result_metadata(
+            working_db,
+            type_mode,
+            max_string_length,
+            client.polymorphic_columns,
+        )?;
 
         Ok(Self {
             resultset_cursor: None,
             resultset_metadata: metadata,
             current: None,
             current_db,
+            // Only the documents of a single collection have a unique _id.
+            resume_key: current_collection
+                .as_ref()
+                .and_then(|_| ResumeKey::of(&pipeline)),
             current_collection,
             pipeline,
             query_timeout,
+            schema_drift: SchemaDriftTracker::default(),
+            execution: None,
+            resume_point: None,
+            warnings: vec![],
         })
     }
+
+    // Run the pipeline with the options of the execution and return the cursor on its result set.
+    fn open_cursor(
+        &self,
+        connection: &MongoConnection,
+        pipeline: Vec<Document>,
+        execution: &Execution,
+    ) -> Result<Cursor<Document>> {
+        let current_db = self.current_db.as_ref().ok_or(Error::NoDatabase)?;
+        let db = connection.client.database(current_db);
+
+        let collection;
+        let mut aggregate = if let Some(c_name) = self.current_collection.as_ref() {
+            collection = db.collection::<Document>(c_name);
+            collection.aggregate(pipeline)
+        } else {
+            db.aggregate(pipeline)
+        };
+
+        aggregate = aggregate.comment(execution.stmt_id.clone());
+
+        // The read preference and read concern of the statement override those of the connection.
+        if let Some(selection_criteria) = execution
+            .read_options
+            .selection_criteria(connection.client.selection_criteria())
+        {
+            aggregate = aggregate.selection_criteria(selection_criteria);
+        }
+        if let Some(read_concern) = execution.read_options.read_concern() {
+            aggregate = aggregate.read_concern(read_concern);
+        }
+
+        // If the query timeout is 0, it means "no timeout"
+        if self.query_timeout.is_some_and(|timeout| timeout > 0) {
+            aggregate = aggregate.max_time(Duration::from_millis(u64::from(
+                self.query_timeout.unwrap(),
+            )));
+        }
+
+        // If rowset_size is large, then update the batch_size to be rowset_size for better efficiency.
+        if execution.rowset_size > BATCH_SIZE_REPLACEMENT_THRESHOLD {
+            aggregate = aggregate.batch_size(execution.rowset_size);
+        }
+
+        // handle an error coming back from execution; if it was cancelled, throw a specific error to
+        // denote this to the program, otherwise return a generic query execution error
+        let map_query_error = |e: mongodb::error::Error| match *e.kind {
+            ErrorKind::Command(CommandError {
+                code: 11601, // interrupted
+                ..
+            }) => Error::QueryCancelled,
+            _ => Error::QueryExecutionFailed(e),
+        };
+
+        let _guard = connection.runtime.enter();
+        connection
+            .runtime
+            .block_on(async { aggregate.await.map_err(map_query_error) })
+    }
+
+    // Move the cursor to the next document. A read interrupted by a transient error is resumed,
+    // according to the retry policy of the connection, by running the pipeline again for the rows
+    // after the last one read. This is only possible when the rows are in a deterministic order.
+    fn advance(&mut self, connection: &MongoConnection, warnings: &mut Vec<Error>) -> Result<bool> {
+        let _guard = connection.runtime.enter();
+        let mut retry = 0;
+        let mut resume_pipeline = None;
+        loop {
+            let reopened = match (resume_pipeline.take(), self.execution.as_ref()) {
+                (Some(pipeline), Some(execution)) => self
+                    .open_cursor(connection, pipeline, execution)
+                    .map(|cursor| self.resultset_cursor = Some(cursor)),
+                _ => Ok(()),
+            };
+            let result = reopened.and_then(|()| {
+                let cursor = self
+                    .resultset_cursor
+                    .as_mut()
+                    .ok_or(Error::StatementNotExecuted)?;
+                connection
+                    .runtime
+                    .block_on(async { cursor.advance().await.map_err(Error::QueryCursorUpdate) })
+            });
+            match result {
+                Err(e) if e.is_transient() && retry < connection.retry_policy.max_retries => {
+                    let Some(pipeline) = self.resume_pipeline() else {
+                        return Err(e);
+                    };
+                    retry += 1;
+                    connection
+                        .retry_policy
+                        .wait(&connection.runtime, retry, e, warnings);
+                    resume_pipeline = Some(pipeline);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Return the pipeline reading the rows after the last one read, if the read can be resumed.
+    fn resume_pipeline(&self) -> Option<Vec<Document>> {
+        let key = self.resume_key.as_ref()?;
+        self.execution.as_ref()?;
+        match self.resume_point.as_ref()? {
+            ResumePoint::Start => Some(self.pipeline.clone()),
+            ResumePoint::After(value) => Some(key.resume_pipeline(&self.pipeline, value.clone())),
+        }
+    }
 }
 
 impl MongoStatement for MongoQuery {
@@ -257,46 +496,52 @@ impl MongoStatement for MongoQuery {
     // Return true if moving was successful, false otherwise.
     // This method deserializes the current row and stores it in self.
     fn next(&mut self, connection: Option<&MongoConnection>) -> Result<(bool, Vec<Error>)> {
-        let guard = connection.unwrap().runtime.enter();
-        let res = self
-            .resultset_cursor
-            .as_mut()
-            .map_or(Err(Error::StatementNotExecuted), |c| {
-                connection
-                    .unwrap()
-                    .runtime
-                    .block_on(async { c.advance().await.map_err(Error::QueryCursorUpdate) })
-            })?;
-        drop(guard);
+        let connection = connection.unwrap();
+        let mut warnings = vec![];
+        let res = connection.record(self.advance(connection, &mut warnings))?;
         // Cursor::advance must return Ok(true) before Cursor::deserialize_current can be invoked.
         // Calling Cursor::deserialize_current after Cursor::advance does not return true or without
         // calling Cursor::advance at all may result in a panic
         if res {
-            self.current = Some(
-                self.resultset_cursor
-                    .as_ref()
-                    .unwrap()
-                    .deserialize_current()
-                    .map_err(Error::QueryCursorUpdate)?,
-            );
+            let current: Document = self
+                .resultset_cursor
+                .as_ref()
+                .unwrap()
+                .deserialize_current()
+                .map_err(Error::QueryCursorUpdate)?;
+            // A row without the resume key cannot be resumed after.
+            self.resume_point = match (&self.resume_key, self.resume_point.take()) {
+                (Some(key), Some(_)) => key.value(&current).map(ResumePoint::After),
+                _ => None,
+            };
+            // A row rejected by the schema drift policy cannot be read.
+            let drift_warnings = self
+                .schema_drift
+                .check_row(connection.schema_drift, &self.resultset_metadata, &current)
+                .inspect_err(|_| self.current = None)?;
+            warnings.extend(drift_warnings);
+            self.current = Some(current);
         } else {
             self.current = None;
         }
 
-        Ok((res, vec![]))
+        Ok((res, warnings))
     }
 
     // Get the BSON value for the cell at the given colIndex on the current row.
     // Fails if the first row as not been retrieved (next must be called at least once before getValue).
     fn get_value(&self, col_index: u16, max_string_length: Option<u16>) -> Result<Option<Bson>> {
         let current = self.current.as_ref().ok_or(Error::InvalidCursorState)?;
+        if self.schema_drift.is_null(col_index) {
+            return Ok(None);
+        }
         let md = self
             .get_col_metadata(col_index, max_string_length)
             .map_err(|_| Error::ColIndexOutOfBounds(col_index))?;
         let datasource = current
             .get_document(&md.table_name)
             .map_err(|e: ValueAccessError| Error::ValueAccess(col_index.to_string(), e))?;
-        let column = datasource.get(&md.col_name);
+        let column = md.get_field(datasource);
         Ok(column.cloned())
     }
 
@@ -306,54 +551,35 @@ impl MongoStatement for MongoQuery {
 
     // Execute the $sql aggregation for the query and initialize the result set
     // cursor. If there is a timeout, the query must finish before the timeout
-    // or an error is returned.
+    // or an error is returned. Transient errors are retried according to the retry policy of the
+    // connection, and every retry is a warning.
     fn execute(
         &mut self,
         connection: &MongoConnection,
         stmt_id: Bson,
         rowset_size: u32,
+        read_options: ReadOptions,
     ) -> Result<bool> {
-        let current_db = self.current_db.as_ref().ok_or(Error::NoDatabase)?;
-        let db = connection.client.database(current_db);
-
-        let collection;
-        let mut aggregate = if let Some(c_name) = self.current_collection.as_ref() {
-            collection = db.collection::<Document>(c_name);
-            collection.aggregate(self.pipeline.to_owned())
-        } else {
-            db.aggregate(self.pipeline.to_owned())
+        connection.check_alive()?;
+        let execution = Execution {
+            stmt_id,
+            rowset_size,
+            read_options,
         };
-
-        aggregate = aggregate.comment(stmt_id);
-
-        // If the query timeout is 0, it means "no timeout"
-        if self.query_timeout.is_some_and(|timeout| timeout > 0) {
-            aggregate = aggregate.max_time(Duration::from_millis(u64::from(
-                self.query_timeout.unwrap(),
-            )));
-        }
-
-        // If rowset_size is large, then update the batch_size to be rowset_size for better efficiency.
-        if rowset_size > BATCH_SIZE_REPLACEMENT_THRESHOLD {
-            aggregate = aggregate.batch_size(rowset_size);
-        }
-
-        // handle an error coming back from execution; if it was cancelled, throw a specific error to
-        // denote this to the program, otherwise return a generic query execution error
-        let map_query_error = |e: mongodb::error::Error| match *e.kind {
-            ErrorKind::Command(CommandError {
-                code: 11601, // interrupted
-                ..
-            }) => Error::QueryCancelled,
-            _ => Error::QueryExecutionFailed(e),
-        };
-
         let _guard = connection.runtime.enter();
-        let cursor: Cursor<Document> = connection
-            .runtime
-            .block_on(async { aggregate.await.map_err(map_query_error) })?;
+        let mut warnings = vec![];
+        let cursor = connection.record(connection.retry_policy.run(
+            &connection.runtime,
+            &mut warnings,
+            || self.open_cursor(connection, self.pipeline.to_owned(), &execution),
+        ));
+        self.warnings.extend(warnings);
+        let cursor = cursor?;
 
         self.resultset_cursor = Some(cursor);
+        self.schema_drift = SchemaDriftTracker::default();
+        self.resume_point = self.resume_key.as_ref().map(|_| ResumePoint::Start);
+        self.execution = Some(execution);
         Ok(true)
     }
 
@@ -362,4 +588,12 @@ impl MongoStatement for MongoQuery {
         self.current = None;
         self.resultset_cursor = None;
     }
+
+    fn schema_drift_count(&self) -> u64 {
+        self.schema_drift.count()
+    }
+
+    fn take_warnings(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings)
+    }
 }
@@ -0,0 +1,287 @@
+use crate::err::{Error, Result};
+use definitions::{AttrMongoDbReadConcernLevel, AttrMongoDbReadPreference};
+use mongodb::options::{
+    ReadConcern, ReadConcernLevel, ReadPreference, ReadPreferenceOptions, SelectionCriteria, TagSet,
+};
+use std::time::Duration;
+
+// The smallest max staleness accepted by the servers.
+const MIN_MAX_STALENESS_SECONDS: i64 = 90;
+
+/// ReadOptions are the read preference and read concern level of the queries of a statement, from
+/// the driver-specific statement attributes. They override the options of the connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    pub read_preference: AttrMongoDbReadPreference,
+    pub read_concern_level: AttrMongoDbReadConcernLevel,
+}
+
+impl ReadOptions {
+    // selection_criteria returns the read preference of the statement, if it overrides the one of
+    // the connection. The tag sets and max staleness of the connection are kept, unless the
+    // statement reads from the primary.
+    pub(crate) fn selection_criteria(
+        &self,
+        connection: Option<&SelectionCriteria>,
+    ) -> Option<SelectionCriteria> {
+        let options = match connection {
+            Some(SelectionCriteria::ReadPreference(
+                ReadPreference::Secondary { options }
+                | ReadPreference::PrimaryPreferred { options }
+                | ReadPreference::SecondaryPreferred { options }
+                | ReadPreference::Nearest { options },
+            )) => options.clone(),
+            _ => None,
+        };
+        read_preference(self.read_preference, options).map(SelectionCriteria::ReadPreference)
+    }
+
+    // read_concern returns the read concern of the statement, if it overrides the one of the
+    // connection.
+    pub(crate) fn read_concern(&self) -> Option<ReadConcern> {
+        let level = match self.read_concern_level {
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_DEFAULT => return None,
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_LOCAL => ReadConcernLevel::Local,
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_MAJORITY => {
+                ReadConcernLevel::Majority
+            }
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_LINEARIZABLE => {
+                ReadConcernLevel::Linearizable
+            }
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_AVAILABLE => {
+                ReadConcernLevel::Available
+            }
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_SNAPSHOT => {
+                ReadConcernLevel::Snapshot
+            }
+        };
+        Some(level.into())
+    }
+}
+
+// read_preference builds the read preference of the given mode, None for the default mode.
+fn read_preference(
+    mode: AttrMongoDbReadPreference,
+    options: Option<ReadPreferenceOptions>,
+) -> Option<ReadPreference> {
+    Some(match mode {
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_DEFAULT => return None,
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY => ReadPreference::Primary,
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY_PREFERRED => {
+            ReadPreference::PrimaryPreferred { options }
+        }
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_SECONDARY => {
+            ReadPreference::Secondary { options }
+        }
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_SECONDARY_PREFERRED => {
+            ReadPreference::SecondaryPreferred { options }
+        }
+        AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_NEAREST => {
+            ReadPreference::Nearest { options }
+        }
+    })
+}
+
+/// connection_read_preference builds the read preference of the read_preference,
+/// read_preference_tags and max_staleness_seconds connection string/DSN options. Tag sets are
+/// separated by '|' and are comma-separated lists of name:value, where an empty tag set matches any
+/// server. A max staleness of -1 means no maximum.
+pub(crate) fn connection_read_preference(
+    mode: &str,
+    tag_sets: Option<&str>,
+    max_staleness_seconds: Option<&str>,
+) -> Result<ReadPreference> {
+    let mode = match mode.to_lowercase().as_str() {
+        "primary" => AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY,
+        "primarypreferred" => AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY_PREFERRED,
+        "secondary" => AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_SECONDARY,
+        "secondarypreferred" => {
+            AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_SECONDARY_PREFERRED
+        }
+        "nearest" => AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_NEAREST,
+        _ => {
+            return Err(Error::InvalidUriFormat(format!(
+                "invalid read_preference value '{mode}', expected 'primary', 'primaryPreferred', 'secondary', 'secondaryPreferred' or 'nearest'"
+            )))
+        }
+    };
+    let tag_sets = tag_sets.map(parse_tag_sets).transpose()?;
+    let max_staleness = max_staleness_seconds
+        .map(parse_max_staleness)
+        .transpose()?
+        .flatten();
+    let options = if tag_sets.is_some() || max_staleness.is_some() {
+        if mode == AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY {
+            return Err(Error::InvalidUriFormat(
+                "read_preference_tags and max_staleness_seconds cannot be used with the primary read_preference".to_string(),
+            ));
+        }
+        Some(
+            ReadPreferenceOptions::builder()
+                .tag_sets(tag_sets)
+                .max_staleness(max_staleness)
+                .build(),
+        )
+    } else {
+        None
+    };
+    Ok(read_preference(mode, options).expect("the mode is not the default"))
+}
+
+/// connection_read_concern builds the read concern of the read_concern_level connection
+/// string/DSN option.
+pub(crate) fn connection_read_concern(level: &str) -> Result<ReadConcern> {
+    match level.to_lowercase().as_str() {
+        "local" => Ok(ReadConcern::local()),
+        "majority" => Ok(ReadConcern::majority()),
+        "linearizable" => Ok(ReadConcern::linearizable()),
+        "available" => Ok(ReadConcern::available()),
+        "snapshot" => Ok(ReadConcern::snapshot()),
+        _ => Err(Error::InvalidUriFormat(format!(
+            "invalid read_concern_level value '{level}', expected 'local', 'majority', 'linearizable', 'available' or 'snapshot'"
+        ))),
+    }
+}
+
+fn parse_tag_sets(value: &str) -> Result<Vec<TagSet>> {
+    value
+        .split('|')
+        .map(|tag_set| {
+            tag_set
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(|tag| match tag.split_once(':') {
+                    Some((name, value)) if !name.trim().is_empty() => {
+                        Ok((name.trim().to_string(), value.trim().to_string()))
+                    }
+                    _ => Err(Error::InvalidUriFormat(format!(
+                        "invalid read_preference_tags value '{value}', expected tag sets of name:value pairs"
+                    ))),
+                })
+                .collect::<Result<TagSet>>()
+        })
+        .collect()
+}
+
+fn parse_max_staleness(value: &str) -> Result<Option<Duration>> {
+    match value.trim().parse::<i64>() {
+        Ok(-1) => Ok(None),
+        Ok(seconds) if seconds >= MIN_MAX_STALENESS_SECONDS => {
+            Ok(Some(Duration::from_secs(seconds.unsigned_abs())))
+        }
+        _ => Err(Error::InvalidUriFormat(format!(
+            "invalid max_staleness_seconds value '{value}', expected -1 or at least {MIN_MAX_STALENESS_SECONDS}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn tag_set(tags: &[(&str, &str)]) -> TagSet {
+        tags.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>()
+    }
+
+    #[test]
+    fn connection_read_preference_parsing() {
+        assert_eq!(
+            ReadPreference::Primary,
+            connection_read_preference("PRIMARY", None, None).unwrap()
+        );
+        assert_eq!(
+            ReadPreference::SecondaryPreferred {
+                options: Some(
+                    ReadPreferenceOptions::builder()
+                        .tag_sets(vec![
+                            tag_set(&[("dc", "ny"), ("rack", "1")]),
+                            tag_set(&[("dc", "ny")]),
+                            tag_set(&[]),
+                        ])
+                        .max_staleness(Duration::from_secs(120))
+                        .build()
+                ),
+            },
+            connection_read_preference(
+                "secondaryPreferred",
+                Some("dc:ny, rack:1|dc:ny|"),
+                Some("120")
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            ReadPreference::Nearest { options: None },
+            connection_read_preference("nearest", None, Some("-1")).unwrap()
+        );
+
+        for (mode, tag_sets, max_staleness) in [
+            ("secondaries", None, None),
+            ("primary", Some("dc:ny"), None),
+            ("secondary", Some("dc"), None),
+            ("secondary", None, Some("30")),
+            ("secondary", None, Some("soon")),
+        ] {
+            assert!(connection_read_preference(mode, tag_sets, max_staleness).is_err());
+        }
+    }
+
+    #[test]
+    fn connection_read_concern_parsing() {
+        assert_eq!(
+            ReadConcern::majority(),
+            connection_read_concern("Majority").unwrap()
+        );
+        assert!(connection_read_concern("strong").is_err());
+    }
+
+    #[test]
+    fn statement_overrides() {
+        let connection = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
+            options: Some(
+                ReadPreferenceOptions::builder()
+                    .tag_sets(vec![tag_set(&[("use", "reporting")])])
+                    .build(),
+            ),
+        });
+
+        let default = ReadOptions::default();
+        assert_eq!(None, default.selection_criteria(Some(&connection)));
+        assert_eq!(None, default.read_concern());
+
+        let primary = ReadOptions {
+            read_preference: AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY,
+            read_concern_level: AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_MAJORITY,
+        };
+        assert_eq!(
+            Some(SelectionCriteria::ReadPreference(ReadPreference::Primary)),
+            primary.selection_criteria(Some(&connection))
+        );
+        assert_eq!(Some(ReadConcern::majority()), primary.read_concern());
+
+        // The tag sets of the connection are kept.
+        let nearest = ReadOptions {
+            read_preference: AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_NEAREST,
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(SelectionCriteria::ReadPreference(ReadPreference::Nearest {
+                options: Some(
+                    ReadPreferenceOptions::builder()
+                        .tag_sets(vec![tag_set(&[("use", "reporting")])])
+                        .build()
+                ),
+            })),
+            nearest.selection_criteria(Some(&connection))
+        );
+        assert_eq!(
+            Some(SelectionCriteria::ReadPreference(ReadPreference::Nearest {
+                options: None
+            })),
+            nearest.selection_criteria(None)
+        );
+    }
+}
@@ -0,0 +1,537 @@
+use crate::err::{Error, Result};
+use mongodb::{
+    bson::{doc, Bson, Document},
+    error::{ErrorKind, RETRYABLE_WRITE_ERROR, TRANSIENT_TRANSACTION_ERROR},
+};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+// The codes of the server errors a read can be retried after, which are the state changes of a
+// replica set, the shutdown of a server, and the loss of a cursor.
+const TRANSIENT_ERROR_CODES: [i32; 13] = [
+    6,     // HostUnreachable
+    7,     // HostNotFound
+    43,    // CursorNotFound
+    89,    // NetworkTimeout
+    91,    // ShutdownInProgress
+    134,   // ReadConcernMajorityNotAvailableYet
+    189,   // PrimarySteppedDown
+    9001,  // SocketException
+    10107, // NotWritablePrimary
+    11600, // InterruptedAtShutdown
+    11602, // InterruptedDueToReplStateChange
+    13435, // NotPrimaryNoSecondaryOk
+    13436, // NotPrimaryOrSecondary
+];
+
+/// The default number of times an operation failing with a transient error is retried.
+pub const DEFAULT_TRANSIENT_ERROR_RETRIES: u32 = 2;
+/// The default time waited before the first retry, which doubles with every retry.
+pub const DEFAULT_TRANSIENT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+// The longest time waited before a retry.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// RetryPolicy is how the operations of a connection failing with a transient error, like a
+/// failover or a network error, are retried. It comes from the transient_error_retries and
+/// transient_error_backoff_ms connection string/DSN options. Every retry is reported as a
+/// warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of retries, 0 disables them.
+    pub max_retries: u32,
+    /// The time waited before the first retry, which doubles with every retry.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: DEFAULT_TRANSIENT_ERROR_RETRIES,
+            initial_backoff: DEFAULT_TRANSIENT_ERROR_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the RetryPolicy of the transient_error_retries and transient_error_backoff_ms
+    /// options, the default being used for the missing ones.
+    pub fn new(retries: Option<&str>, backoff_ms: Option<&str>) -> Result<Self> {
+        let mut policy = RetryPolicy::default();
+        if let Some(retries) = retries {
+            policy.max_retries = retries.trim().parse().map_err(|_| {
+                Error::InvalidUriFormat(format!(
+                    "invalid transient_error_retries value '{retries}', expected a non-negative integer"
+                ))
+            })?;
+        }
+        if let Some(backoff_ms) = backoff_ms {
+            policy.initial_backoff =
+                Duration::from_millis(backoff_ms.trim().parse().map_err(|_| {
+                    Error::InvalidUriFormat(format!(
+                        "invalid transient_error_backoff_ms value '{backoff_ms}', expected a non-negative integer"
+                    ))
+                })?);
+        }
+        Ok(policy)
+    }
+
+    // backoff returns the time to wait before the given retry, starting at 1.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(MAX_BACKOFF)
+    }
+
+    /// Runs the operation, and runs it again while it fails with a transient error and retries
+    /// are left, after waiting on the runtime so that the Client keeps monitoring the servers. A
+    /// warning is added for every retry.
+    pub(crate) fn run<T>(
+        &self,
+        runtime: &Runtime,
+        warnings: &mut Vec<Error>,
+        mut operation: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        let mut retry = 0;
+        loop {
+            match operation() {
+                Err(e) if e.is_transient() && retry < self.max_retries => {
+                    retry += 1;
+                    self.wait(runtime, retry, e, warnings);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Records the retry of the operation which failed with the error, and waits before it.
+    pub(crate) fn wait(
+        &self,
+        runtime: &Runtime,
+        retry: u32,
+        error: Error,
+        warnings: &mut Vec<Error>,
+    ) {
+        let backoff = self.backoff(retry);
+        let warning = Error::TransientErrorRetried(retry, self.max_retries, Box::new(error));
+        log::warn!("{warning}, waiting {backoff:?}");
+        warnings.push(warning);
+        runtime.block_on(async { tokio::time::sleep(backoff).await });
+    }
+}
+
+impl Error {
+    /// Whether the operation which failed with the error can be run again: the driver labels the
+    /// error as retryable or transient, the server could not be reached, or the server failed
+    /// because of a state change of the replica set or the loss of a cursor. Timeouts are not
+    /// transient.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::CollectionCursorUpdate(err)
+            | Error::DatabaseVersionRetreival(err)
+            | Error::QueryCursorUpdate(err)
+            | Error::QueryExecutionFailed(err)
+            | Error::BuildInfoCmdExecutionFailed(err) => {
+                err.contains_label(RETRYABLE_WRITE_ERROR)
+                    || err.contains_label(TRANSIENT_TRANSACTION_ERROR)
+                    || self.is_connection_failure()
+                    || matches!(err.kind.as_ref(), ErrorKind::Command(command_error)
+                        if TRANSIENT_ERROR_CODES.contains(&command_error.code))
+            }
+            _ => false,
+        }
+    }
+}
+
+// The stages which neither duplicate, merge nor reorder the documents of the collection, so the
+// `_id` of a document stays unique through them. $skip and $limit are left out: before the sort on
+// `_id`, they may select different documents when the pipeline is run again.
+const ROW_PRESERVING_STAGES: [&str; 6] = [
+    "$match",
+    "$project",
+    "$addFields",
+    "$set",
+    "$unset",
+    "$sort",
+];
+
+// The groups of BSON types, as $type aliases, in the order $sort compares values of different
+// types. Values of the same group are compared with each other by $gt and $lt.
+const SORT_ORDER: [&[&str]; 13] = [
+    &["minKey"],
+    &["null", "undefined"],
+    &["double", "int", "long", "decimal"],
+    &["symbol", "string"],
+    &["object"],
+    &["array"],
+    &["binData"],
+    &["objectId"],
+    &["bool"],
+    &["date"],
+    &["timestamp"],
+    &["regex"],
+    &["maxKey"],
+];
+
+// sort_group returns the index of the SORT_ORDER group of the value.
+fn sort_group(value: &Bson) -> Option<usize> {
+    Some(match value {
+        Bson::MinKey => 0,
+        Bson::Null | Bson::Undefined => 1,
+        Bson::Double(_) | Bson::Int32(_) | Bson::Int64(_) | Bson::Decimal128(_) => 2,
+        Bson::Symbol(_) | Bson::String(_) => 3,
+        Bson::Document(_) => 4,
+        Bson::Array(_) => 5,
+        Bson::Binary(_) => 6,
+        Bson::ObjectId(_) => 7,
+        Bson::Boolean(_) => 8,
+        Bson::DateTime(_) => 9,
+        Bson::Timestamp(_) => 10,
+        Bson::RegularExpression(_) => 11,
+        Bson::MaxKey => 12,
+        _ => return None,
+    })
+}
+
+/// ResumeKey is the unique sort key of a deterministic pipeline, whose last stage sorts the
+/// documents of a single collection on their `_id`. A forward-only read of such a pipeline can
+/// be resumed after the last row read by filtering out the rows before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResumeKey {
+    path: String,
+    ascending: bool,
+}
+
+impl ResumeKey {
+    /// Returns the ResumeKey of the pipeline run on a single collection, if its sort key is
+    /// provably unique: the `_id` of the collection, either at the root or under a field bound
+    /// to `$$ROOT`, with no stage duplicating or merging documents, like `$unwind` or `$lookup`.
+    pub(crate) fn of(pipeline: &[Document]) -> Option<Self> {
+        let (sort, stages) = pipeline.split_last()?;
+        let sort = sort.get_document("$sort").ok()?;
+        if sort.len() != 1 {
+            return None;
+        }
+        let (path, direction) = sort.iter().next()?;
+        let ascending = match direction {
+            Bson::Int32(1) | Bson::Int64(1) => true,
+            Bson::Int32(-1) | Bson::Int64(-1) => false,
+            Bson::Double(d) if *d == 1.0 => true,
+            Bson::Double(d) if *d == -1.0 => false,
+            _ => return None,
+        };
+        // The field holding the document of the collection, if it is not the root.
+        let alias = match path.as_str() {
+            "_id" => None,
+            _ => Some(
+                path.strip_suffix("._id")
+                    .filter(|alias| !alias.contains('.'))?,
+            ),
+        };
+
+        let mut bound = alias.is_none();
+        for stage in stages {
+            let (name, spec) = stage.iter().next()?;
+            if stage.len() != 1 || !ROW_PRESERVING_STAGES.contains(&name.as_str()) {
+                return None;
+            }
+            if !matches!(name.as_str(), "$project" | "$addFields" | "$set") {
+                continue;
+            }
+            for (field, value) in spec.as_document()? {
+                let assigned = match alias {
+                    None => field == "_id" || field.starts_with("_id."),
+                    Some(alias) => field == alias || field.starts_with(&format!("{alias}.")),
+                };
+                if !assigned {
+                    continue;
+                }
+                match value {
+                    // Keeping or removing the field does not change the key.
+                    Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Boolean(_)
+                        if name == "$project" => {}
+                    Bson::String(root) if alias == Some(field.as_str()) && root == "$$ROOT" => {
+                        bound = true
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        bound.then(|| ResumeKey {
+            path: path.clone(),
+            ascending,
+        })
+    }
+
+    /// Returns the key of the row, which is resumed after, if its type can be compared.
+    pub(crate) fn value(&self, row: &Document) -> Option<Bson> {
+        let mut path = self.path.split('.');
+        let mut value = row.get(path.next()?)?;
+        for field in path {
+            value = value.as_document()?.get(field)?;
+        }
+        sort_group(value)?;
+        Some(value.clone())
+    }
+
+    /// Returns the pipeline reading the rows after the one with the given key. $gt and $lt only
+    /// match values of the same type group, so the keys of the types sorted after it are matched
+    /// by their type.
+    pub(crate) fn resume_pipeline(&self, pipeline: &[Document], after: Bson) -> Vec<Document> {
+        let group = sort_group(&after).unwrap_or_default();
+        let (operator, later_groups) = if self.ascending {
+            ("$gt", &SORT_ORDER[group + 1..])
+        } else {
+            ("$lt", &SORT_ORDER[..group])
+        };
+        let later_types: Vec<&str> = later_groups
+            .iter()
+            .flat_map(|g| g.iter().copied())
+            .collect();
+        let mut predicate = doc! {&self.path: {operator: after}};
+        if !later_types.is_empty() {
+            predicate = doc! {"$or": [predicate, {&self.path: {"$type": later_types}}]};
+        }
+        let mut pipeline = pipeline.to_vec();
+        pipeline.push(doc! {"$match": predicate});
+        pipeline
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use std::{cell::Cell, sync::Arc};
+
+    fn network_error() -> Error {
+        Error::QueryExecutionFailed(
+            ErrorKind::Io(Arc::new(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "connection reset",
+            )))
+            .into(),
+        )
+    }
+
+    fn command_error(code: i32) -> Error {
+        Error::QueryCursorUpdate(
+            ErrorKind::Command(
+                mongodb::bson::from_document(doc! {"code": code, "errmsg": "failed"}).unwrap(),
+            )
+            .into(),
+        )
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(network_error().is_transient());
+        assert!(command_error(43).is_transient());
+        assert!(command_error(11602).is_transient());
+        // A syntax error is not transient.
+        assert!(!command_error(9).is_transient());
+        assert!(!Error::QueryExecutionFailed(
+            ErrorKind::Io(Arc::new(std::io::ErrorKind::TimedOut.into())).into()
+        )
+        .is_transient());
+        assert!(!Error::QueryCancelled.is_transient());
+    }
+
+    #[test]
+    fn policy() {
+        assert_eq!(
+            RetryPolicy::default(),
+            RetryPolicy::new(None, None).unwrap()
+        );
+        let policy = RetryPolicy::new(Some("5"), Some("1000")).unwrap();
+        assert_eq!(
+            RetryPolicy {
+                max_retries: 5,
+                initial_backoff: Duration::from_secs(1),
+            },
+            policy
+        );
+        assert_eq!(Duration::from_secs(1), policy.backoff(1));
+        assert_eq!(Duration::from_secs(4), policy.backoff(3));
+        assert_eq!(MAX_BACKOFF, policy.backoff(5));
+        assert!(RetryPolicy::new(Some("-1"), None).is_err());
+        assert!(RetryPolicy::new(None, Some("soon")).is_err());
+    }
+
+    #[test]
+    fn run_retries_transient_errors() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+        };
+
+        let attempts = Cell::new(0);
+        let mut warnings = vec![];
+        let result = policy.run(&runtime, &mut warnings, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(network_error())
+            } else {
+                Ok(attempts.get())
+            }
+        });
+        assert_eq!(3, result.unwrap());
+        assert_eq!(2, warnings.len());
+        assert!(warnings[1]
+            .to_string()
+            .starts_with("Retry 2 of 2 after a transient error: "));
+
+        // The retries are exhausted.
+        attempts.set(0);
+        warnings.clear();
+        let result = policy.run(&runtime, &mut warnings, || -> Result<()> {
+            attempts.set(attempts.get() + 1);
+            Err(network_error())
+        });
+        assert!(result.unwrap_err().is_transient());
+        assert_eq!(3, attempts.get());
+
+        // Other errors are not retried.
+        attempts.set(0);
+        let result = policy.run(&runtime, &mut warnings, || -> Result<()> {
+            attempts.set(attempts.get() + 1);
+            Err(command_error(9))
+        });
+        assert!(result.is_err());
+        assert_eq!(1, attempts.get());
+    }
+
+    #[test]
+    fn resume_key() {
+        let sorted = |sort: Document| {
+            vec![
+                doc! {"$project": {"_id": 0, "foo": "$$ROOT"}},
+                doc! {"$match": {"foo.a": 1}},
+                doc! {"$sort": sort},
+            ]
+        };
+        assert_eq!(
+            Some(ResumeKey {
+                path: "foo._id".to_string(),
+                ascending: false,
+            }),
+            ResumeKey::of(&sorted(doc! {"foo._id": -1}))
+        );
+        assert!(ResumeKey::of(&[doc! {"$match": {"a": 1}}, doc! {"$sort": {"_id": 1}}]).is_some());
+        // The rows are not in a deterministic order.
+        assert_eq!(None, ResumeKey::of(&sorted(doc! {"foo.name": 1})));
+        assert_eq!(None, ResumeKey::of(&sorted(doc! {"foo._id": 1, "a": 1})));
+        assert_eq!(
+            None,
+            ResumeKey::of(&[doc! {"$sort": {"_id": 1}}, doc! {"$limit": 10}])
+        );
+        assert_eq!(
+            None,
+            ResumeKey::of(&[doc! {"$limit": 10}, doc! {"$sort": {"_id": 1}}])
+        );
+        assert_eq!(
+            None,
+            ResumeKey::of(&[doc! {"$skip": 10}, doc! {"$sort": {"_id": 1}}])
+        );
+        assert_eq!(
+            None,
+            ResumeKey::of(&[doc! {"$sql": {"statement": "select"}}])
+        );
+        // foo is not the document of the collection.
+        assert_eq!(None, ResumeKey::of(&[doc! {"$sort": {"foo._id": 1}}]));
+        assert_eq!(
+            None,
+            ResumeKey::of(&[
+                doc! {"$project": {"_id": 0, "foo": "$$ROOT"}},
+                doc! {"$addFields": {"foo._id": "$foo.name"}},
+                doc! {"$sort": {"foo._id": 1}},
+            ])
+        );
+        assert_eq!(
+            None,
+            ResumeKey::of(&[
+                doc! {"$project": {"_id": "$name"}},
+                doc! {"$sort": {"_id": 1}},
+            ])
+        );
+
+        let key = ResumeKey::of(&sorted(doc! {"foo._id": 1})).unwrap();
+        assert_eq!(
+            Some(Bson::Int32(7)),
+            key.value(&doc! {"foo": {"_id": 7, "name": "x"}})
+        );
+        assert_eq!(None, key.value(&doc! {"bar": {"_id": 7}}));
+        assert_eq!(
+            None,
+            key.value(&doc! {"foo": {"_id": Bson::JavaScriptCode("1".to_string())}})
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_are_not_resumed() {
+        // After an $unwind or a $lookup, several rows share an orders._id, and resuming after the
+        // last one read would skip the others.
+        let project = doc! {"$project": {"_id": 0, "orders": "$$ROOT"}};
+        let sort = doc! {"$sort": {"orders._id": 1}};
+        assert_eq!(
+            None,
+            ResumeKey::of(&[
+                project.clone(),
+                doc! {"$unwind": "$orders.items"},
+                sort.clone()
+            ])
+        );
+        assert_eq!(
+            None,
+            ResumeKey::of(&[
+                project.clone(),
+                doc! {"$lookup": {
+                    "from": "items",
+                    "localField": "orders._id",
+                    "foreignField": "order",
+                    "as": "items",
+                }},
+                sort.clone(),
+            ])
+        );
+        assert!(ResumeKey::of(&[project, sort]).is_some());
+    }
+
+    #[test]
+    fn resume_pipeline_matches_the_later_types() {
+        let pipeline = [doc! {"$sort": {"_id": 1}}];
+        let key = ResumeKey::of(&pipeline).unwrap();
+        assert_eq!(
+            vec![
+                doc! {"$sort": {"_id": 1}},
+                doc! {"$match": {"$or": [
+                    {"_id": {"$gt": "m"}},
+                    {"_id": {"$type": [
+                        "object", "array", "binData", "objectId", "bool", "date", "timestamp",
+                        "regex", "maxKey",
+                    ]}},
+                ]}},
+            ],
+            key.resume_pipeline(&pipeline, Bson::String("m".to_string()))
+        );
+
+        let pipeline = [doc! {"$sort": {"_id": -1}}];
+        let key = ResumeKey::of(&pipeline).unwrap();
+        assert_eq!(
+            vec![
+                doc! {"$sort": {"_id": -1}},
+                doc! {"$match": {"$or": [
+                    {"_id": {"$lt": 7}},
+                    {"_id": {"$type": ["minKey", "null", "undefined"]}},
+                ]}},
+            ],
+            key.resume_pipeline(&pipeline, Bson::Int32(7))
+        );
+        assert_eq!(
+            doc! {"$match": {"_id": {"$lt": Bson::MinKey}}},
+            key.resume_pipeline(&pipeline, Bson::MinKey)[1]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    col_metadata::MongoColMetadata,
+    err::{Error, Result},
+};
+use mongodb::bson::{Bson, Document};
+
+/// SchemaDrift is what happens to a fetched value whose BSON type does not match the type of its
+/// column in the result set metadata, which can be stale. It comes from the schema_drift
+/// connection string/DSN option. Every drifted value is counted, and reported with its column,
+/// its expected and actual types and its row number unless it is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaDrift {
+    /// The value is returned as is, without a warning.
+    #[default]
+    Ignore,
+    /// The value is returned as is, with a warning.
+    Warn,
+    /// The value is returned as NULL, with a warning.
+    Null,
+    /// Fetching the row fails.
+    Error,
+}
+
+impl std::str::FromStr for SchemaDrift {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ignore" => Ok(SchemaDrift::Ignore),
+            "warn" => Ok(SchemaDrift::Warn),
+            "null" => Ok(SchemaDrift::Null),
+            "error" => Ok(SchemaDrift::Error),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid schema_drift value '{s}', expected 'ignore', 'warn', 'null' or 'error'"
+            ))),
+        }
+    }
+}
+
+// Returns the name of the BSON type of a value, as reported in the type_name of the metadata.
+fn bson_type_name(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Array(_) => "array",
+        Bson::Document(_) => "object",
+        Bson::Boolean(_) => "bool",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::Int32(_) => "int",
+        Bson::Int64(_) => "long",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Binary(_) => "binData",
+        Bson::ObjectId(_) => "objectId",
+        Bson::DateTime(_) => "date",
+        Bson::Symbol(_) => "symbol",
+        Bson::Decimal128(_) => "decimal",
+        Bson::Undefined => "undefined",
+        Bson::MaxKey => "maxKey",
+        Bson::MinKey => "minKey",
+        Bson::DbPointer(_) => "dbPointer",
+    }
+}
+
+// Returns whether a value of the actual type can be held by a column of the expected type.
+// Missing values are a matter of nullability, and numbers fit in the wider numeric types.
+fn is_compatible(expected: &str, actual: &str) -> bool {
+    matches!(
+        (expected, actual),
+        (_, "null" | "undefined")
+            | ("bson", _)
+            | ("long", "int")
+            | ("double", "int" | "long")
+            | ("decimal", "int" | "long" | "double")
+    ) || expected == actual
+}
+
+/// SchemaDriftTracker checks the rows fetched by a statement against its result set metadata
+/// and counts the drifted values.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SchemaDriftTracker {
+    // The number of rows fetched so far.
+    row_number: u64,
+    // The number of drifted values found so far.
+    count: u64,
+    // The 1-indexed columns of the current row returned as NULL.
+    null_columns: Vec<u16>,
+}
+
+impl SchemaDriftTracker {
+    /// Checks the row just fetched, returning a warning for every drifted value. With
+    /// SchemaDrift::Ignore, no warning is returned, and with SchemaDrift::Error, the first drifted
+    /// value is returned as an error instead.
+    pub(crate) fn check_row(
+        &mut self,
+        policy: SchemaDrift,
+        metadata: &[MongoColMetadata],
+        row: &Document,
+    ) -> Result<Vec<Error>> {
+        self.row_number += 1;
+        self.null_columns.clear();
+        let mut warnings = vec![];
+        for (col_index, md) in (1u16..).zip(metadata) {
+            let Some(value) = row
+                .get_document(&md.table_name)
+                .ok()
+                .and_then(|datasource| md.get_field(datasource))
+            else {
+                continue;
+            };
+            let actual = bson_type_name(value);
+            if is_compatible(&md.type_name, actual) {
+                continue;
+            }
+            self.count += 1;
+            let error = Error::SchemaDrift(
+                md.col_name.clone(),
+                md.type_name.clone(),
+                actual,
+                self.row_number,
+            );
+            match policy {
+                SchemaDrift::Ignore => continue,
+                SchemaDrift::Warn => {}
+                SchemaDrift::Null => self.null_columns.push(col_index),
+                SchemaDrift::Error => return Err(Error::SchemaDriftRejected(Box::new(error))),
+            }
+            warnings.push(error);
+        }
+        Ok(warnings)
+    }
+
+    /// Returns whether the given column of the current row is returned as NULL.
+    pub(crate) fn is_null(&self, col_index: u16) -> bool {
+        self.null_columns.contains(&col_index)
+    }
+
+    /// Returns the number of drifted values found since the statement was executed.
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::{
+        json_schema::{
+            simplified::{Atomic, Schema},
+            BsonTypeName,
+        },
+        TypeMode,
+    };
+    use definitions::Nullability;
+    use mongodb::bson::doc;
+
+    fn metadata() -> Vec<MongoColMetadata> {
+        [("a", BsonTypeName::Int), ("b", BsonTypeName::Double)]
+            .into_iter()
+            .map(|(name, bson_type)| {
+                MongoColMetadata::new(
+                    "",
+                    "foo".to_string(),
+                    name.to_string(),
+                    Schema::Atomic(Atomic::Scalar(bson_type)),
+                    Nullability::SQL_NULLABLE,
+                    TypeMode::Standard,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(SchemaDrift::Ignore, "ignore".parse().unwrap());
+        assert_eq!(SchemaDrift::Warn, "warn".parse().unwrap());
+        assert_eq!(SchemaDrift::Null, "NULL".parse().unwrap());
+        assert_eq!(SchemaDrift::Error, "Error".parse().unwrap());
+        assert!("skip".parse::<SchemaDrift>().is_err());
+    }
+
+    #[test]
+    fn matching_values_do_not_drift() {
+        let mut tracker = SchemaDriftTracker::default();
+        for row in [
+            doc! {"foo": {"a": 1, "b": 1.5}},
+            doc! {"foo": {"a": Bson::Null, "b": 2i64}},
+            doc! {"foo": {}},
+        ] {
+            assert!(tracker
+                .check_row(SchemaDrift::Error, &metadata(), &row)
+                .unwrap()
+                .is_empty());
+        }
+        assert_eq!(0, tracker.count());
+    }
+
+    #[test]
+    fn drifted_values_are_reported() {
+        let mut tracker = SchemaDriftTracker::default();
+        tracker
+            .check_row(SchemaDrift::Warn, &metadata(), &doc! {"foo": {"a": 1}})
+            .unwrap();
+        let warnings = tracker
+            .check_row(
+                SchemaDrift::Warn,
+                &metadata(),
+                &doc! {"foo": {"a": "one", "b": true}},
+            )
+            .unwrap();
+        assert_eq!(
+            vec![
+                "Value of column 'a' in row 2 is of type string instead of its schema type int",
+                "Value of column 'b' in row 2 is of type bool instead of its schema type double",
+            ],
+            warnings.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(2, tracker.count());
+        assert!(!tracker.is_null(1));
+    }
+
+    #[test]
+    fn drifted_values_are_ignored() {
+        let mut tracker = SchemaDriftTracker::default();
+        assert!(tracker
+            .check_row(
+                SchemaDrift::Ignore,
+                &metadata(),
+                &doc! {"foo": {"a": "one"}}
+            )
+            .unwrap()
+            .is_empty());
+        assert_eq!(1, tracker.count());
+        assert!(!tracker.is_null(1));
+    }
+
+    #[test]
+    fn drifted_values_are_null() {
+        let mut tracker = SchemaDriftTracker::default();
+        let warnings = tracker
+            .check_row(SchemaDrift::Null, &metadata(), &doc! {"foo": {"b": "x"}})
+            .unwrap();
+        assert_eq!(1, warnings.len());
+        assert!(!tracker.is_null(1));
+        assert!(tracker.is_null(2));
+        tracker
+            .check_row(SchemaDrift::Null, &metadata(), &doc! {"foo": {"b": 1.0}})
+            .unwrap();
+        assert!(!tracker.is_null(2));
+    }
+
+    #[test]
+    fn drifted_values_are_errors() {
+        let mut tracker = SchemaDriftTracker::default();
+        match tracker.check_row(SchemaDrift::Error, &metadata(), &doc! {"foo": {"a": 1.5}}) {
+            Err(error @ Error::SchemaDriftRejected(_)) => {
+                assert_eq!(
+                    "Value of column 'a' in row 1 is of type double instead of its schema type int",
+                    error.to_string()
+                );
+                assert_eq!(constants::RESTRICTED_DATATYPE, error.get_sql_state());
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
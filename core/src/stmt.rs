@@ -1,6 +1,6 @@
 use crate::{
     err::{Error, Result},
-    MongoColMetadata, MongoConnection,
+    MongoColMetadata, MongoConnection, ReadOptions,
 };
 use mongodb::bson::Bson;
 use std::fmt::Debug;
@@ -34,12 +34,23 @@ pub trait MongoStatement: Debug {
         _connection: &MongoConnection,
         _stmt_id: Bson,
         _rowset_size: u32,
+        _read_options: ReadOptions,
     ) -> Result<bool> {
         Err(Error::UnsupportedOperation("execute"))
     }
     // Closes the cursor.
     // Only MongoQuery supports this workflow. The other statements don't.
     fn close_cursor(&mut self) {}
+    // Returns the number of fetched values whose type did not match their column since the
+    // statement was executed. Only MongoQuery checks the fetched values.
+    fn schema_drift_count(&self) -> u64 {
+        0
+    }
+    // Returns the warnings of the last prepare or execute, like the retries of transient errors.
+    // Only MongoQuery retries them.
+    fn take_warnings(&mut self) -> Vec<Error> {
+        vec![]
+    }
 }
 
 #[derive(Debug)]
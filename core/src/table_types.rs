@@ -1,10 +1,13 @@
 use crate::{
-    databases::init_databases_metadata, databases::DATABASES_METADATA, err::Result, Error,
-    MongoColMetadata, MongoConnection, MongoStatement,
+    databases::init_databases_metadata,
+    databases::DATABASES_METADATA,
+    err::Result,
+    util::{SYSTEM_TABLE, TABLE, VIEW},
+    Error, MongoColMetadata, MongoConnection, MongoStatement,
 };
 use mongodb::bson::Bson;
 
-const TABLE_TYPES: [&str; 2] = ["TABLE", "VIEW"];
+const TABLE_TYPES: [&str; 3] = [TABLE, VIEW, SYSTEM_TABLE];
 
 #[derive(Debug)]
 pub struct MongoTableTypes {
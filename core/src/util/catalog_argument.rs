@@ -0,0 +1,152 @@
+// Implements the handling of catalog function arguments described in
+// https://learn.microsoft.com/en-us/sql/odbc/reference/develop-app/arguments-in-catalog-functions
+
+/// The escape character for search patterns, reported by SQL_SEARCH_PATTERN_ESCAPE.
+pub const SEARCH_PATTERN_ESCAPE: char = '\\';
+
+// Both the SQL-92 quote and the MongoSQL backtick delimit identifiers.
+const IDENTIFIER_QUOTES: [char; 2] = ['"', '`'];
+
+/// IdentifierCase is how unquoted identifiers are folded, as reported by SQL_IDENTIFIER_CASE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierCase {
+    Upper,
+    Lower,
+    Sensitive,
+    Mixed,
+}
+
+/// MongoDB database, collection and field names are case sensitive.
+pub const IDENTIFIER_CASE: IdentifierCase = IdentifierCase::Sensitive;
+
+/// The kind of a catalog function argument when SQL_ATTR_METADATA_ID is SQL_FALSE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogArgument {
+    /// Ordinary argument (OA), matched exactly.
+    Ordinary,
+    /// Pattern value argument (PV), which accepts the `%` and `_` wildcards.
+    PatternValue,
+}
+
+/// Returns the value the catalog statements expect for a catalog function argument. When
+/// SQL_ATTR_METADATA_ID is SQL_TRUE, the argument is an identifier: it is unquoted or folded
+/// and, for pattern value arguments, its `%` and `_` characters are escaped so that they match
+/// literally.
+pub fn catalog_argument_value(value: &str, kind: CatalogArgument, metadata_id: bool) -> String {
+    if !metadata_id {
+        return value.to_string();
+    }
+    let name = identifier_to_name(value, IDENTIFIER_CASE);
+    match kind {
+        CatalogArgument::Ordinary => name,
+        CatalogArgument::PatternValue => escape_search_pattern(&name),
+    }
+}
+
+// Converts an identifier argument to the name it designates. Leading and trailing blanks are
+// ignored. A quoted identifier is taken literally, with doubled quotes standing for one quote
+// character. An unquoted identifier has its trailing blanks removed and is folded according to
+// `case`.
+pub(crate) fn identifier_to_name(value: &str, case: IdentifierCase) -> String {
+    let value = value.trim();
+    let mut chars = value.chars();
+    match (chars.next(), chars.next_back()) {
+        (Some(open), Some(close)) if open == close && IDENTIFIER_QUOTES.contains(&open) => {
+            let doubled_quote = format!("{open}{open}");
+            chars.as_str().replace(&doubled_quote, &open.to_string())
+        }
+        _ => match case {
+            IdentifierCase::Upper => value.to_uppercase(),
+            IdentifierCase::Lower => value.to_lowercase(),
+            IdentifierCase::Sensitive | IdentifierCase::Mixed => value.to_string(),
+        },
+    }
+}
+
+// Escapes the wildcards and the escape character of `name`, so that it can be used as a search
+// pattern matching only itself.
+pub(crate) fn escape_search_pattern(name: &str) -> String {
+    let mut pattern = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '%' | '_' | SEARCH_PATTERN_ESCAPE) {
+            pattern.push(SEARCH_PATTERN_ESCAPE);
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::util::{is_match, to_name_regex};
+
+    #[test]
+    fn metadata_id_false_keeps_the_argument() {
+        assert_eq!(
+            "%a_b",
+            catalog_argument_value("%a_b", CatalogArgument::PatternValue, false)
+        );
+        assert_eq!(
+            "\"Foo\" ",
+            catalog_argument_value("\"Foo\" ", CatalogArgument::Ordinary, false)
+        );
+    }
+
+    #[test]
+    fn unquoted_identifiers() {
+        assert_eq!(
+            "Foo",
+            identifier_to_name("  Foo  ", IdentifierCase::Sensitive)
+        );
+        assert_eq!("Foo", identifier_to_name("Foo", IdentifierCase::Mixed));
+        assert_eq!("FOO", identifier_to_name("Foo ", IdentifierCase::Upper));
+        assert_eq!("foo", identifier_to_name("Foo", IdentifierCase::Lower));
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        assert_eq!(
+            " Foo ",
+            identifier_to_name("\" Foo \"", IdentifierCase::Upper)
+        );
+        assert_eq!("Foo", identifier_to_name(" `Foo` ", IdentifierCase::Lower));
+        assert_eq!(
+            "a\"b",
+            identifier_to_name("\"a\"\"b\"", IdentifierCase::Upper)
+        );
+        assert_eq!("a`b", identifier_to_name("`a``b`", IdentifierCase::Upper));
+        // Mismatched or lone quotes do not delimit the identifier.
+        assert_eq!(
+            "\"Foo`",
+            identifier_to_name("\"Foo`", IdentifierCase::Sensitive)
+        );
+        assert_eq!("\"", identifier_to_name("\"", IdentifierCase::Sensitive));
+        assert_eq!("", identifier_to_name("\"\"", IdentifierCase::Sensitive));
+    }
+
+    #[test]
+    fn metadata_id_true_ignores_wildcards() {
+        let pattern = catalog_argument_value("a_b%", CatalogArgument::PatternValue, true);
+        assert_eq!(r"a\_b\%", pattern);
+        assert!(is_match("a_b%", &pattern, true));
+        assert!(!is_match("axb%", &pattern, true));
+        assert!(!is_match("a_b%c", &pattern, true));
+
+        let pattern = catalog_argument_value(r#""a\b%""#, CatalogArgument::PatternValue, true);
+        assert_eq!(r"a\\b\%", pattern);
+        assert!(to_name_regex(&pattern).unwrap().is_match(r"a\b%"));
+
+        assert_eq!(
+            "a_b%",
+            catalog_argument_value(" `a_b%` ", CatalogArgument::Ordinary, true)
+        );
+    }
+
+    #[test]
+    fn metadata_id_true_match_all_pattern_is_literal() {
+        let pattern = catalog_argument_value("%", CatalogArgument::PatternValue, true);
+        assert!(is_match("%", &pattern, true));
+        assert!(!is_match("foo", &pattern, true));
+    }
+}
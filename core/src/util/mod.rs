@@ -1,15 +1,38 @@
 use constants::SQL_ALL_TABLE_TYPES;
-use fancy_regex::Regex as FancyRegex;
 use lazy_static::lazy_static;
-use mongodb::results::CollectionType;
 use regex::{Regex, RegexSet, RegexSetBuilder};
 
+mod catalog_argument;
+pub use catalog_argument::{
+    catalog_argument_value, CatalogArgument, IdentifierCase, IDENTIFIER_CASE, SEARCH_PATTERN_ESCAPE,
+};
 mod test_connection;
 
 pub(crate) const TABLE: &str = "TABLE";
-pub(crate) const COLLECTION: &str = "collection";
-pub(crate) const TIMESERIES: &str = "timeseries";
-pub(crate) const VIEW: &str = "view";
+pub(crate) const VIEW: &str = "VIEW";
+pub(crate) const SYSTEM_TABLE: &str = "SYSTEM TABLE";
+pub(crate) const SYSTEM_COLLECTION_PREFIX: &str = "system.";
+
+// The TABLE_TYPE reported by SQLTables for a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TableType {
+    // Regular and time-series collections.
+    Table,
+    // Views, including Atlas Data Federation views.
+    View,
+    // system.* collections.
+    SystemTable,
+}
+
+impl TableType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            TableType::Table => TABLE,
+            TableType::View => VIEW,
+            TableType::SystemTable => SYSTEM_TABLE,
+        }
+    }
+}
 
 lazy_static! {
     pub(crate) static ref TABLE_VALUES: RegexSet = RegexSetBuilder::new(["^table$", "^\'table\'$"])
@@ -20,24 +43,69 @@ lazy_static! {
         .case_insensitive(true)
         .build()
         .unwrap();
-    static ref NON_ESCAPED_UNDERSCORE: FancyRegex = FancyRegex::new(r"(?<!\\\\)_").unwrap();
-    static ref NON_ESCAPED_PERCENT: FancyRegex = FancyRegex::new(r"(?<!\\\\)%").unwrap();
+    pub(crate) static ref SYSTEM_TABLE_VALUES: RegexSet =
+        RegexSetBuilder::new(["^system table$", "^\'system table\'$"])
+            .case_insensitive(true)
+            .build()
+            .unwrap();
 }
 
-// Converts SQL pattern characters (% and _) into proper regex patterns.
-// SQL-1308: Handle SQL_ATTR_METADATA_ID
+// Converts SQL pattern characters (% and _) into proper regex patterns. The
+// SQL_SEARCH_PATTERN_ESCAPE character makes the following %, _ or escape character match
+// literally; before any other character it is itself a literal.
 // Returns regex for a filter
 pub(crate) fn to_name_regex(filter: &str) -> Option<Regex> {
     match filter {
         "%" | "" => None,
         _ => {
-            let filter = regex::escape(filter);
-            let filter = NON_ESCAPED_UNDERSCORE.replace_all(&filter, ".");
-            let filter = NON_ESCAPED_PERCENT.replace_all(&filter, ".*");
-            let filter = &filter.replace("\\\\_", "_").replace("\\\\%", "%");
-            Some(Regex::new(&format!("^{filter}$")).unwrap())
+            let mut regex = String::with_capacity(filter.len() + 2);
+            regex.push('^');
+            let mut chars = filter.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    SEARCH_PATTERN_ESCAPE => match chars.peek() {
+                        Some(&next @ ('%' | '_' | SEARCH_PATTERN_ESCAPE)) => {
+                            chars.next();
+                            regex.push_str(&regex::escape(&next.to_string()));
+                        }
+                        _ => regex.push_str(&regex::escape(&c.to_string())),
+                    },
+                    '%' => regex.push_str(".*"),
+                    '_' => regex.push('.'),
+                    _ => regex.push_str(&regex::escape(&c.to_string())),
+                }
+            }
+            regex.push('$');
+            Some(Regex::new(&regex).unwrap())
+        }
+    }
+}
+
+// Whether some name starting with `prefix` matches the search pattern `filter`, e.g. whether an
+// array table of a collection, named after it, can match a table name filter. Empty filters
+// match everything.
+pub(crate) fn may_match_with_prefix(prefix: &str, filter: &str) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    let mut chars = filter.chars().peekable();
+    for p in prefix.chars() {
+        match chars.next() {
+            None => return false,
+            Some('%') => return true,
+            Some('_') => {}
+            Some(SEARCH_PATTERN_ESCAPE)
+                if matches!(chars.peek(), Some(&('%' | '_' | SEARCH_PATTERN_ESCAPE))) =>
+            {
+                if chars.next() != Some(p) {
+                    return false;
+                }
+            }
+            Some(c) if c != p => return false,
+            Some(_) => {}
         }
     }
+    true
 }
 
 /// is_match compares `name` to `filter` either directly or via regex, depending on
@@ -52,8 +120,8 @@ pub(crate) fn is_match(name: &str, filter: &str, accept_search_patterns: bool) -
     }
 }
 
-// Create the list of Collection types to filter on
-pub(crate) fn table_type_filter_to_vec(table_type: &str) -> Option<Vec<CollectionType>> {
+// Create the list of table types to filter on
+pub(crate) fn table_type_filter_to_vec(table_type: &str) -> Option<Vec<TableType>> {
     match table_type {
         SQL_ALL_TABLE_TYPES | "" => None,
         _ => {
@@ -61,15 +129,15 @@ pub(crate) fn table_type_filter_to_vec(table_type: &str) -> Option<Vec<Collectio
                 .split(',')
                 .map(|attr| attr.trim())
                 .collect::<Vec<&str>>();
-            let mut table_type_filters: Vec<CollectionType> = Vec::new();
+            let mut table_type_filters: Vec<TableType> = Vec::new();
             for table_type_entry in &table_type_entries {
                 if TABLE_VALUES.is_match(table_type_entry) {
-                    // Collection and Timeseries types should be mapped to table
-                    // The Rust driver doesn't seem to deserialize timeseries at the moment because
-                    // there is no CollectionType::Timeseries
-                    table_type_filters.push(CollectionType::Collection);
+                    // Collection and Timeseries types are both mapped to table
+                    table_type_filters.push(TableType::Table);
                 } else if VIEW_VALUES.is_match(table_type_entry) {
-                    table_type_filters.push(CollectionType::View);
+                    table_type_filters.push(TableType::View);
+                } else if SYSTEM_TABLE_VALUES.is_match(table_type_entry) {
+                    table_type_filters.push(TableType::SystemTable);
                 }
             }
 
@@ -102,24 +170,28 @@ macro_rules! set {
 
 #[cfg(test)]
 mod filtering {
-    use super::{is_match, table_type_filter_to_vec, to_name_regex};
+    use super::{is_match, may_match_with_prefix, table_type_filter_to_vec, to_name_regex};
 
     mod table_type_filter_to_vec {
         use super::table_type_filter_to_vec;
-        use mongodb::results::CollectionType;
+        use crate::util::TableType;
         #[test]
         fn test_table_type_filter_to_vec() {
             assert_eq!(
                 table_type_filter_to_vec("table"),
-                Some(vec![CollectionType::Collection])
+                Some(vec![TableType::Table])
             );
             assert_eq!(
                 table_type_filter_to_vec("view"),
-                Some(vec![CollectionType::View])
+                Some(vec![TableType::View])
             );
             assert_eq!(
                 table_type_filter_to_vec("table,view"),
-                Some(vec![CollectionType::Collection, CollectionType::View])
+                Some(vec![TableType::Table, TableType::View])
+            );
+            assert_eq!(
+                table_type_filter_to_vec("'SYSTEM TABLE', TABLE"),
+                Some(vec![TableType::SystemTable, TableType::Table])
             );
             assert_eq!(table_type_filter_to_vec(""), None);
             assert_eq!(table_type_filter_to_vec("%"), None);
@@ -136,6 +208,24 @@ mod filtering {
         }
     }
 
+    mod may_match_with_prefix {
+        use super::may_match_with_prefix;
+
+        #[test]
+        fn test_may_match_with_prefix() {
+            assert!(may_match_with_prefix("orders_", ""));
+            assert!(may_match_with_prefix("orders_", "%"));
+            assert!(may_match_with_prefix("orders_", "orders_items"));
+            assert!(may_match_with_prefix("orders_", "ord%"));
+            assert!(may_match_with_prefix("orders_", "o_ders%"));
+            assert!(may_match_with_prefix("orders_", r"orders\_%"));
+            assert!(!may_match_with_prefix("orders_", "orders"));
+            assert!(!may_match_with_prefix("orders_", "customers%"));
+            assert!(!may_match_with_prefix("orders_", r"orders\%"));
+            assert!(may_match_with_prefix("orders_", "%s_x"));
+        }
+    }
+
     mod is_match {
         use super::is_match;
 
@@ -188,6 +278,14 @@ mod filtering {
             assert!(!is_match("myiphone", r"my\_phone", true));
             assert!(is_match("conversion%2022", r"conversion\%2022", true));
             assert!(!is_match("conversions2022", r"conversion\%2022", true));
+            assert!(is_match(r"back\slash", r"back\\slash", true));
+            assert!(is_match(r"back\_slash", r"back\\\_slash", true));
+            assert!(!is_match(r"back\xslash", r"back\\\_slash", true));
+            // An escaped escape character does not escape the following wildcard.
+            assert!(is_match(r"back\xslash", r"back\\_slash", true));
+            // The escape character is literal before other characters and at the end.
+            assert!(is_match(r"a\b", r"a\b", true));
+            assert!(is_match(r"a\", r"a\", true));
         }
     }
 }
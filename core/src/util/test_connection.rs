@@ -1,5 +1,8 @@
 #![allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
-use crate::{odbc_uri::ODBCUri, MongoConnection, TypeMode};
+use crate::{
+    client_pool::PoolOptions, odbc_uri::ODBCUri, CatalogFilter, MongoConnection,
+    PolymorphicColumns, RetryPolicy, SchemaDrift, TypeMode,
+};
 use cstr::{input_text_to_string_w, write_string_to_buffer, WideChar};
 use definitions::{Integer, SQL_NTS_ISIZE};
 
@@ -40,6 +43,12 @@ pub unsafe extern "C" fn atlas_sql_test_connection(
                     TypeMode::Standard,
                     Some(runtime),
                     None,
+                    CatalogFilter::default(),
+                    false,
+                    PolymorphicColumns::default(),
+                    SchemaDrift::default(),
+                    RetryPolicy::default(),
+                    PoolOptions::default(),
                 ) {
                     Ok(_) => true,
                     Err(e) => {
@@ -166,6 +166,64 @@ pub enum StatementAttribute {
     SQL_ATTR_IMP_ROW_DESC = 10012,
     SQL_ATTR_IMP_PARAM_DESC = 10013,
     SQL_ATTR_METADATA_ID = SQL_ATTR_METADATA_ID,
+    // Driver-specific attributes start at SQL_DRIVER_STMT_ATTR_BASE (0x00004000).
+    // Read-only: the number of fetched values whose BSON type did not match their column.
+    SQL_ATTR_MONGODB_SCHEMA_DRIFT_COUNT = 0x4000,
+    // The read preference of the statement, an AttrMongoDbReadPreference.
+    SQL_ATTR_MONGODB_READ_PREFERENCE = 0x4001,
+    // The read concern level of the statement, an AttrMongoDbReadConcernLevel.
+    SQL_ATTR_MONGODB_READ_CONCERN_LEVEL = 0x4002,
+    // The JSON format of the statement, an AttrMongoDbJsonFormat.
+    SQL_ATTR_MONGODB_JSON_FORMAT = 0x4003,
+}
+
+/// Possible values for the driver-specific `SQL_ATTR_MONGODB_READ_PREFERENCE` statement attribute,
+/// which overrides the read preference of the connection for the queries of the statement. The
+/// tag sets and max staleness of the connection are kept for the modes other than primary.
+#[allow(non_camel_case_types)]
+#[repr(usize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive, Default)]
+pub enum AttrMongoDbReadPreference {
+    /// The read preference of the connection is used. This is the default.
+    #[default]
+    SQL_MONGODB_READ_PREFERENCE_DEFAULT = 0,
+    SQL_MONGODB_READ_PREFERENCE_PRIMARY = 1,
+    SQL_MONGODB_READ_PREFERENCE_PRIMARY_PREFERRED = 2,
+    SQL_MONGODB_READ_PREFERENCE_SECONDARY = 3,
+    SQL_MONGODB_READ_PREFERENCE_SECONDARY_PREFERRED = 4,
+    SQL_MONGODB_READ_PREFERENCE_NEAREST = 5,
+}
+
+/// Possible values for the driver-specific `SQL_ATTR_MONGODB_READ_CONCERN_LEVEL` statement
+/// attribute, which overrides the read concern level of the connection for the queries of the
+/// statement.
+#[allow(non_camel_case_types)]
+#[repr(usize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive, Default)]
+pub enum AttrMongoDbReadConcernLevel {
+    /// The read concern of the connection is used. This is the default.
+    #[default]
+    SQL_MONGODB_READ_CONCERN_DEFAULT = 0,
+    SQL_MONGODB_READ_CONCERN_LOCAL = 1,
+    SQL_MONGODB_READ_CONCERN_MAJORITY = 2,
+    SQL_MONGODB_READ_CONCERN_LINEARIZABLE = 3,
+    SQL_MONGODB_READ_CONCERN_AVAILABLE = 4,
+    SQL_MONGODB_READ_CONCERN_SNAPSHOT = 5,
+}
+
+/// Possible values for the driver-specific `SQL_ATTR_MONGODB_JSON_FORMAT` statement attribute,
+/// which overrides the json_format of the connection for the documents, arrays and other BSON
+/// values of the statement converted to character or binary data.
+#[allow(non_camel_case_types)]
+#[repr(usize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive, Default)]
+pub enum AttrMongoDbJsonFormat {
+    /// The json_format of the connection is used. This is the default.
+    #[default]
+    SQL_MONGODB_JSON_FORMAT_DEFAULT = 0,
+    SQL_MONGODB_JSON_FORMAT_RELAXED = 1,
+    SQL_MONGODB_JSON_FORMAT_CANONICAL = 2,
+    SQL_MONGODB_JSON_FORMAT_SIMPLIFIED = 3,
 }
 
 /// Connection attributes for `SQLSetConnectAttr`
@@ -196,4 +254,18 @@ pub enum ConnectionAttribute {
     SQL_ATTR_APP_WCHAR_TYPE = 1061,
     SQL_ATTR_AUTO_IPD = 10001,
     SQL_ATTR_METADATA_ID = SQL_ATTR_METADATA_ID,
+    // Driver-specific attributes start at SQL_DRIVER_CONN_ATTR_BASE (0x00004000).
+    // The session time zone, an IANA time zone name, 'local' or 'utc'.
+    SQL_ATTR_MONGODB_TIMEZONE = 0x4000,
+    // The OidcNoticeCallback SQLDriverConnect calls with the OIDC notices, like the user code of
+    // the device flow, while the authentication waits on the user.
+    SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK = 0x4001,
+    // The pointer passed as context to the SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK.
+    SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT = 0x4002,
 }
+
+/// The type of the driver-specific `SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK` connection attribute.
+/// It is called with the `SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT` pointer, and the null-terminated
+/// notice and its length in characters, which are only valid during the call.
+pub type OidcNoticeCallback =
+    unsafe extern "C" fn(context: Pointer, notice: *const crate::WChar, length: crate::Integer);
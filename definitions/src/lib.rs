@@ -178,6 +178,25 @@ pub struct IntervalStruct {
     pub interval_value: IntervalUnion,
 }
 
+impl std::fmt::Debug for IntervalStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("IntervalStruct");
+        s.field("interval_type", &self.interval_type)
+            .field("interval_sign", &self.interval_sign);
+        // The interval type selects the member of the union that is in use.
+        match self.interval_type {
+            t if t == Interval::SQL_IS_YEAR as c_int
+                || t == Interval::SQL_IS_MONTH as c_int
+                || t == Interval::SQL_IS_YEAR_TO_MONTH as c_int =>
+            {
+                s.field("interval_value", unsafe { &self.interval_value.year_month })
+            }
+            _ => s.field("interval_value", unsafe { &self.interval_value.day_second }),
+        };
+        s.finish()
+    }
+}
+
 /// SQL_DATE_STRUCT
 #[repr(C)]
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
@@ -6,7 +6,7 @@
 
 use crate::{handles::definitions::*, SQLCancel};
 use definitions::SqlReturn;
-use mongo_odbc_core::MongoConnection;
+use mongo_odbc_core::{CatalogFilter, MongoConnection, PolymorphicColumns, SchemaDrift};
 use mongodb::Client;
 use std::env;
 
@@ -147,6 +147,12 @@ mod integration {
             uuid_repr: None,
             runtime: runtime.into(),
             cluster_type: MongoClusterType::AtlasDataFederation,
+            catalog_filter: CatalogFilter::default(),
+            array_tables: false,
+            polymorphic_columns: PolymorphicColumns::default(),
+            schema_drift: SchemaDrift::default(),
+            liveness: Default::default(),
+            retry_policy: Default::default(),
         };
         *conn_handle.mongo_connection.write().unwrap() = Some(mongo_connection);
         let conn = &mut MongoHandle::Connection(conn_handle);
@@ -17,6 +17,7 @@ mod unit {
     use definitions::{
         AccessMode, ConnectionAttribute, HandleType, Integer, Pointer, SqlReturn, UInteger, WChar,
     };
+    use mongo_odbc_core::DatabaseAs;
     use std::ffi::c_void;
     use std::mem::size_of;
     use std::sync::RwLock;
@@ -157,6 +158,70 @@ mod unit {
             expected_value = 1u32,
             actual_value_modifier = modify_numeric_attr,
         );
+
+        // An unreachable server does not answer the ping within the operation timeout.
+        #[test]
+        fn connection_dead_after_ping() {
+            use mongo_odbc_core::{
+                cluster_type::MongoClusterType, CatalogFilter, MongoConnection, PolymorphicColumns,
+                SchemaDrift,
+            };
+            use mongodb::{options::ClientOptions, Client};
+            use std::time::Duration;
+
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let client = {
+                let _guard = runtime.enter();
+                let mut client_options = ClientOptions::default();
+                client_options.hosts = vec!["localhost:1".parse().unwrap()];
+                Client::with_options(client_options).unwrap()
+            };
+            let conn = Connection::with_state(std::ptr::null_mut(), ConnectionState::Connected);
+            *conn.mongo_connection.write().unwrap() = Some(MongoConnection {
+                client,
+                operation_timeout: Some(Duration::from_millis(200)),
+                uuid_repr: None,
+                runtime: runtime.into(),
+                cluster_type: MongoClusterType::AtlasDataFederation,
+                catalog_filter: CatalogFilter::default(),
+                array_tables: false,
+                polymorphic_columns: PolymorphicColumns::default(),
+                schema_drift: SchemaDrift::default(),
+                liveness: Default::default(),
+                retry_policy: Default::default(),
+            });
+            let mongo_handle: *mut _ = &mut MongoHandle::Connection(conn);
+
+            unsafe {
+                let connection_dead = &mut 0u32;
+                assert_eq!(
+                    SqlReturn::SUCCESS,
+                    SQLGetConnectAttrW(
+                        mongo_handle as *mut _,
+                        ConnectionAttribute::SQL_ATTR_CONNECTION_DEAD as i32,
+                        connection_dead as *mut u32 as Pointer,
+                        0,
+                        &mut 0,
+                    )
+                );
+                assert_eq!(1, *connection_dead);
+
+                // The next statement fails with 08S01.
+                let conn = (*mongo_handle).as_connection().unwrap();
+                let e = conn
+                    .mongo_connection
+                    .read()
+                    .unwrap()
+                    .as_ref()
+                    .unwrap()
+                    .check_alive()
+                    .unwrap_err();
+                assert_eq!("08S01", ODBCError::Core(e).get_sql_state().odbc_3_state);
+            }
+        }
     }
 
     // Test setting LoginTimeout attribute.
@@ -208,6 +273,163 @@ mod unit {
         }
     }
 
+    // Test setting the session time zone attribute, which is then read back by name.
+    #[test]
+    fn get_set_timezone() {
+        unsafe {
+            let conn = Connection::with_state(std::ptr::null_mut(), ConnectionState::Connected);
+            let mongo_handle: *mut _ = &mut MongoHandle::Connection(conn);
+
+            let timezone_ptr = cstr::to_widechar_ptr("America/New_York");
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLSetConnectAttrW(
+                    mongo_handle as *mut _,
+                    ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE as i32,
+                    timezone_ptr.0 as *mut _,
+                    timezone_ptr.1.len() as i32
+                )
+            );
+            let invalid_ptr = cstr::to_widechar_ptr("Mars/Olympus_Mons");
+            assert_eq!(
+                SqlReturn::ERROR,
+                SQLSetConnectAttrW(
+                    mongo_handle as *mut _,
+                    ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE as i32,
+                    invalid_ptr.0 as *mut _,
+                    invalid_ptr.1.len() as i32
+                )
+            );
+
+            let value_ptr: *mut c_void = Box::into_raw(Box::new([0u8; 80])) as *mut _;
+            let out_length = &mut 0;
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetConnectAttrW(
+                    mongo_handle as *mut _,
+                    ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE as i32,
+                    value_ptr,
+                    80,
+                    out_length,
+                )
+            );
+            assert_eq!(
+                "America/New_York",
+                input_text_to_string_w(
+                    value_ptr as *const _,
+                    (*out_length as usize / size_of::<WideChar>()) as isize,
+                )
+            );
+            let _ = Box::from_raw(value_ptr as *mut [u8; 80]);
+        }
+    }
+
+    // Test setting the OIDC notice callback and its context, which are then read back.
+    #[test]
+    fn get_set_oidc_notice_callback() {
+        unsafe extern "C" fn callback(context: Pointer, notice: *const WChar, length: Integer) {
+            *context.cast::<String>() = input_text_to_string_w(notice, length as isize);
+        }
+
+        unsafe {
+            let conn = Connection::with_state(std::ptr::null_mut(), ConnectionState::Connected);
+            let mongo_handle: *mut _ = &mut MongoHandle::Connection(conn);
+            let mut delivered = String::new();
+            let context: Pointer = (&mut delivered as *mut String).cast();
+            for (attribute, value) in [
+                (
+                    ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK,
+                    callback as Pointer,
+                ),
+                (
+                    ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT,
+                    context,
+                ),
+            ] {
+                assert_eq!(
+                    SqlReturn::SUCCESS,
+                    SQLSetConnectAttrW(mongo_handle as *mut _, attribute as i32, value, 0)
+                );
+                let mut out: Pointer = std::ptr::null_mut();
+                assert_eq!(
+                    SqlReturn::SUCCESS,
+                    SQLGetConnectAttrW(
+                        mongo_handle as *mut _,
+                        attribute as i32,
+                        (&mut out as *mut Pointer).cast(),
+                        0,
+                        &mut 0,
+                    )
+                );
+                assert_eq!(value, out);
+            }
+
+            let attributes = (*mongo_handle)
+                .as_connection()
+                .unwrap()
+                .attributes
+                .read()
+                .unwrap();
+            let notice = cstr::to_widechar_ptr("enter the code ABCD");
+            attributes.oidc_notice_callback.unwrap()(
+                attributes.oidc_notice_context.unwrap(),
+                notice.0,
+                (notice.1.len() - 1) as Integer,
+            );
+            drop(attributes);
+            assert_eq!("enter the code ABCD", delivered);
+
+            // A null pointer removes the callback.
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLSetConnectAttrW(
+                    mongo_handle as *mut _,
+                    ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK as i32,
+                    std::ptr::null_mut(),
+                    0
+                )
+            );
+            assert!((*mongo_handle)
+                .as_connection()
+                .unwrap()
+                .attributes
+                .read()
+                .unwrap()
+                .oidc_notice_callback
+                .is_none());
+        }
+    }
+
+    // Test that the current catalog cannot be set when databases are mapped to schemas.
+    #[test]
+    fn set_current_catalog_database_as_schema() {
+        unsafe {
+            let conn = Connection::with_state(std::ptr::null_mut(), ConnectionState::Connected);
+            *conn.database_as.write().unwrap() = DatabaseAs::Schema;
+            let mongo_handle: *mut _ = &mut MongoHandle::Connection(conn);
+
+            let current_catalog_ptr = cstr::to_widechar_ptr("test");
+
+            assert_eq!(
+                SqlReturn::ERROR,
+                SQLSetConnectAttrW(
+                    mongo_handle as *mut _,
+                    ConnectionAttribute::SQL_ATTR_CURRENT_CATALOG as i32,
+                    current_catalog_ptr.0 as *mut _,
+                    current_catalog_ptr.1.len() as i32
+                )
+            );
+            let conn_handle = (*mongo_handle).as_connection().unwrap();
+            assert_eq!(None, conn_handle.attributes.read().unwrap().current_catalog);
+            assert_eq!(
+                "HYC00",
+                conn_handle.errors.read().unwrap()[0]
+                    .get_sql_state()
+                    .odbc_3_state
+            );
+        }
+    }
+
     // Test setting the access mode attribute
     #[test]
     fn get_set_access_mode() {
@@ -1,19 +1,27 @@
 use crate::{
     add_diag_with_function,
+    api::{
+        decimal::{Decimal, Rounding},
+        interval::{interval_type, IntervalError, IntervalValue},
+        json_format::{to_simplified_json_val, JsonFormat},
+        timezone::SessionTimeZone,
+    },
     errors::ODBCError,
-    handles::definitions::{CachedData, MongoHandle, Statement},
+    handles::definitions::{CachedData, DescriptorRecord, MongoHandle, Statement},
 };
 use chrono::{
-    offset::Utc, DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
+    offset::Utc, DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone,
+    Timelike,
 };
 use cstr::{
     write_binary_slice_to_buffer, write_fixed_data, write_string_slice_to_buffer,
     write_wstring_slice_to_buffer, WideChar,
 };
 use definitions::{
-    CDataType, Char, Date, Integer, Len, Pointer, SmallInt, SqlReturn, Time, Timestamp, USmallInt,
+    CDataType, Char, Date, Integer, Interval, IntervalStruct, Len, Numeric, Pointer, SmallInt,
+    SqlReturn, Time, Timestamp, USmallInt,
 };
-use mongodb::bson::{spec::BinarySubtype, Bson, UuidRepresentation};
+use mongodb::bson::{spec::BinarySubtype, Bson, Decimal128, UuidRepresentation};
 use regex::Regex;
 use serde_json::{json, Value};
 use std::{mem::size_of, str::FromStr};
@@ -23,17 +31,38 @@ const INT32: &str = "Int32";
 const INT64: &str = "Int64";
 const UINT32: &str = "UInt32";
 const UINT64: &str = "UInt64";
+const INT16: &str = "Int16";
+const UINT16: &str = "UInt16";
+const INT8: &str = "Int8";
+const UINT8: &str = "UInt8";
 const BIT: &str = "Bit";
 const DATETIME: &str = "DateTime";
 const GUID: &str = "GUID";
+const NUMERIC: &str = "Numeric";
+const INTERVAL: &str = "Interval";
 
 type Result<T> = std::result::Result<T, ODBCError>;
 
 /// IntoCData is just used for adding methods to bson::Bson.
 trait IntoCData {
-    fn to_json(self, uuid_repr: Option<UuidRepresentation>) -> String;
-    fn to_json_val(self, uuid_repr: Option<UuidRepresentation>) -> Value;
-    fn to_binary(self, uuid_repr: Option<UuidRepresentation>) -> Result<Vec<u8>>;
+    fn to_json(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> String;
+    fn to_json_val(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> Value;
+    fn to_binary(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> Result<Vec<u8>>;
     fn to_guid(self, uuid_repr: Option<UuidRepresentation>) -> Result<Vec<u8>>;
     fn to_f64(&self) -> Result<(f64, Option<ODBCError>)>;
     fn to_f32(&self) -> Result<(f32, Option<ODBCError>)>;
@@ -41,7 +70,13 @@ trait IntoCData {
     fn to_i32(&self) -> Result<(i32, Option<ODBCError>)>;
     fn to_u64(&self) -> Result<(u64, Option<ODBCError>)>;
     fn to_u32(&self) -> Result<(u32, Option<ODBCError>)>;
+    fn to_i16(&self) -> Result<(i16, Option<ODBCError>)>;
+    fn to_u16(&self) -> Result<(u16, Option<ODBCError>)>;
+    fn to_i8(&self) -> Result<(i8, Option<ODBCError>)>;
+    fn to_u8(&self) -> Result<(u8, Option<ODBCError>)>;
     fn to_bit(&self) -> Result<(u8, Option<ODBCError>)>;
+    fn to_numeric(&self, record: DescriptorRecord) -> Result<(Numeric, Option<ODBCError>)>;
+    fn to_interval(&self, interval: Interval) -> Result<(IntervalStruct, Option<ODBCError>)>;
     fn to_datetime(&self) -> Result<(DateTime<Utc>, Option<ODBCError>)>;
     fn to_date(&self) -> Result<(NaiveDate, Option<ODBCError>)>;
     fn to_time(&self) -> Result<(NaiveTime, Option<ODBCError>)>;
@@ -70,6 +105,81 @@ fn i64_to_bit(i: i64) -> Result<(u8, Option<ODBCError>)> {
     }
 }
 
+fn decimal128_to_integer<T: TryFrom<i128>>(d: &Decimal128) -> Result<(T, Option<ODBCError>)> {
+    Decimal::from(d)
+        .to_i128()
+        .and_then(|(i, inexact)| Some((T::try_from(i).ok()?, inexact)))
+        .map(|(i, inexact)| {
+            (
+                i,
+                inexact.then(|| ODBCError::FractionalTruncation(d.to_string())),
+            )
+        })
+        .ok_or_else(|| ODBCError::IntegralTruncation(d.to_string()))
+}
+
+// Converts decimal to a SQL_NUMERIC_STRUCT with the precision and scale of the descriptor record.
+// Digits beyond the scale are rounded, with a fractional truncation warning, and values needing
+// more than precision digits are out of range.
+fn decimal_to_numeric(
+    decimal: Decimal,
+    record: DescriptorRecord,
+    value: impl Fn() -> String,
+) -> Result<(Numeric, Option<ODBCError>)> {
+    let max = u32::try_from(record.precision)
+        .ok()
+        .and_then(|precision| 10u128.checked_pow(precision));
+    let rescaled = decimal
+        .rescale(i32::from(record.scale), Rounding::HalfUp)
+        .filter(|rescaled| max.is_none_or(|max| rescaled.unscaled < max))
+        .ok_or_else(|| ODBCError::IntegralTruncation(value()))?;
+    // The precision and scale are validated when they are set on the descriptor.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let numeric = Numeric {
+        precision: record.precision as Char,
+        scale: record.scale as i8,
+        sign: Char::from(!rescaled.negative),
+        val: rescaled.unscaled.to_le_bytes(),
+    };
+    Ok((
+        numeric,
+        rescaled
+            .inexact
+            .then(|| ODBCError::FractionalTruncation(value())),
+    ))
+}
+
+// Converts bson to an integer type narrower than 32 bits. The value is converted with to_i64, so
+// that fractional truncation is reported the same way, and then range checked. Negative values,
+// including those that would truncate to zero, are rejected for unsigned types. Conversion errors
+// are reported against `type_name`.
+fn bson_to_small_integer<T: TryFrom<i64>>(
+    bson: &Bson,
+    type_name: &'static str,
+    unsigned: bool,
+) -> Result<(T, Option<ODBCError>)> {
+    if unsigned {
+        if let Ok((f, _)) = bson.to_f64() {
+            if f < 0f64 {
+                return Err(ODBCError::IntegralTruncation(f.to_string()));
+            }
+        }
+    }
+    bson.to_i64().map_or_else(
+        |e| {
+            Err(match e {
+                ODBCError::RestrictedDataType(s, _) => ODBCError::RestrictedDataType(s, type_name),
+                ODBCError::InvalidCharacterValue(_) => ODBCError::InvalidCharacterValue(type_name),
+                _ => e,
+            })
+        },
+        |(i, w)| match T::try_from(i) {
+            Ok(i) => Ok((i, w)),
+            Err(_) => Err(ODBCError::IntegralTruncation(i.to_string())),
+        },
+    )
+}
+
 fn string_contains_fractional_precision_micros(s: &str) -> bool {
     // pull out microseconds by splitting on decimal place (only one in valid time[stamp])
     // use regex to separate fractional seconds from any timezone formatting
@@ -84,35 +194,82 @@ fn from_string(s: &str, conversion_error_type: &'static str) -> Result<f64> {
 }
 
 impl IntoCData for Bson {
-    fn to_json_val(self, uuid_repr: Option<UuidRepresentation>) -> Value {
-        match self {
-            Bson::Array(v) => {
-                Value::Array(v.into_iter().map(|b| b.to_json_val(uuid_repr)).collect())
+    fn to_json_val(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> Value {
+        let uuid = match &self {
+            Bson::Binary(b) if b.subtype == BinarySubtype::Uuid => {
+                Some(b.to_uuid().unwrap().to_string())
             }
-            Bson::Document(v) => Value::Object(
+            Bson::Binary(b) if b.subtype == BinarySubtype::UuidOld => Some(
+                b.to_uuid_with_representation(
+                    uuid_repr.unwrap_or(UuidRepresentation::PythonLegacy),
+                )
+                .unwrap()
+                .to_string(),
+            ),
+            _ => None,
+        };
+        match (self, uuid) {
+            (Bson::Array(v), _) => Value::Array(
                 v.into_iter()
-                    .map(|(k, v)| (k, v.to_json_val(uuid_repr)))
+                    .map(|b| b.to_json_val(uuid_repr, timezone, json_format))
                     .collect(),
             ),
-            Bson::String(s) => Value::String(s),
-            Bson::Binary(b) if b.subtype == BinarySubtype::Uuid => {
-                json!({"$uuid": b.to_uuid().unwrap().to_string()})
-            }
-            Bson::Binary(b) if b.subtype == BinarySubtype::UuidOld => {
-                json!({"$uuid": b.to_uuid_with_representation(uuid_repr.unwrap_or(UuidRepresentation::PythonLegacy)).unwrap().to_string()})
-            }
-            _ => self.into_relaxed_extjson(),
+            (Bson::Document(v), _) => Value::Object(
+                v.into_iter()
+                    .map(|(k, v)| (k, v.to_json_val(uuid_repr, timezone, json_format)))
+                    .collect(),
+            ),
+            (Bson::String(s), _) => Value::String(s),
+            (_, Some(uuid)) if json_format == JsonFormat::Simplified => Value::String(uuid),
+            (_, Some(uuid)) => json!({ "$uuid": uuid }),
+            (data, None) => match json_format {
+                JsonFormat::Relaxed => match data {
+                    // Dates the bson crate writes as date strings are written in the session time zone.
+                    Bson::DateTime(d)
+                        if timezone != SessionTimeZone::Utc
+                            && d.timestamp_millis() >= 0
+                            && d.to_chrono().year() <= 9999 =>
+                    {
+                        json!({"$date": timezone.to_session_time(d.to_chrono()).to_rfc3339_opts(SecondsFormat::AutoSi, false)})
+                    }
+                    data => data.into_relaxed_extjson(),
+                },
+                JsonFormat::Canonical => data.into_canonical_extjson(),
+                JsonFormat::Simplified => to_simplified_json_val(data, timezone),
+            },
         }
     }
-    fn to_json(self, uuid_repr: Option<UuidRepresentation>) -> String {
+    fn to_json(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> String {
         match self {
             Bson::String(s) => s,
-            _ => self.to_json_val(uuid_repr).to_string(),
+            // The format only applies to documents, arrays and the extended BSON types, numbers
+            // and booleans are always written as plain JSON.
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Boolean(_) => {
+                self.into_relaxed_extjson().to_string()
+            }
+            _ => self
+                .to_json_val(uuid_repr, timezone, json_format)
+                .to_string(),
         }
     }
 
-    fn to_binary(self, uuid_repr: Option<UuidRepresentation>) -> Result<Vec<u8>> {
-        Ok(self.to_json(uuid_repr).into_bytes())
+    fn to_binary(
+        self,
+        uuid_repr: Option<UuidRepresentation>,
+        timezone: SessionTimeZone,
+        json_format: JsonFormat,
+    ) -> Result<Vec<u8>> {
+        Ok(self.to_json(uuid_repr, timezone, json_format).into_bytes())
     }
 
     fn to_guid(self, uuid_repr: Option<UuidRepresentation>) -> Result<Vec<u8>> {
@@ -120,7 +277,10 @@ impl IntoCData for Bson {
             Bson::Binary(b) if b.subtype != BinarySubtype::Uuid => Err(
                 ODBCError::RestrictedDataType("binary with non-uuid subtype", GUID),
             ),
-            Bson::Binary(_) => Ok(self.to_json(uuid_repr).into_bytes()),
+            // Binary data contains no dates, so the time zone does not matter.
+            Bson::Binary(_) => Ok(self
+                .to_json(uuid_repr, SessionTimeZone::Utc, JsonFormat::Relaxed)
+                .into_bytes()),
             o => Err(ODBCError::RestrictedDataType(o.to_type_str(), GUID)),
         }
     }
@@ -190,29 +350,7 @@ impl IntoCData for Bson {
             Bson::Boolean(b) => Ok((i64::from(*b), None)),
             Bson::Int32(i) => Ok((i64::from(*i), None)),
             Bson::Int64(i) => Ok((*i, None)),
-            // Note that this isn't perfect because there are some 64bit integer values that are
-            // not representable as doubles. There *could* be a specific value where we will get a
-            // different result here than if we had a conversion from Decimal128 to i64 directly.
-            // We should update this when the bson crate supports Decimal128 entirely.
-            Bson::Decimal128(_) => {
-                let (out, _) = self.to_f64()?;
-                // there is no try_from implementation for f64 to i64
-                #[allow(clippy::cast_precision_loss)]
-                if out > i64::MAX as f64 || out < i64::MIN as f64 {
-                    Err(ODBCError::IntegralTruncation(out.to_string()))
-                } else {
-                    // This is safe because we've already checked that the value is within the range of i64
-                    #[allow(clippy::cast_possible_truncation)]
-                    Ok((
-                        out as i64,
-                        if out.floor() != out {
-                            Some(ODBCError::FractionalTruncation(out.to_string()))
-                        } else {
-                            None
-                        },
-                    ))
-                }
-            }
+            Bson::Decimal128(d) => decimal128_to_integer(d),
             o => Err(ODBCError::RestrictedDataType(o.to_type_str(), INT64)),
         }
     }
@@ -225,24 +363,7 @@ impl IntoCData for Bson {
             Bson::Int64(i) if *i > i64::from(i32::MAX) || *i < i64::from(i32::MIN) => {
                 Err(ODBCError::IntegralTruncation(i.to_string()))
             }
-            Bson::Decimal128(_) => {
-                let (out, _) = self.to_f64()?;
-                if out > f64::from(i32::MAX) || out < f64::from(i32::MIN) {
-                    Err(ODBCError::IntegralTruncation(out.to_string()))
-                } else {
-                    // This is safe because we've already checked that the value is within the range of i32
-                    // there is no try_from implementation for f64 to i32
-                    #[allow(clippy::cast_possible_truncation)]
-                    Ok((
-                        out as i32,
-                        if out.floor() != out {
-                            Some(ODBCError::FractionalTruncation(out.to_string()))
-                        } else {
-                            None
-                        },
-                    ))
-                }
-            }
+            Bson::Decimal128(d) => decimal128_to_integer(d),
             Bson::String(s) => {
                 Bson::Double(f64::from_str(s).map_err(|_| ODBCError::InvalidCharacterValue(INT32))?)
                     .to_i32()
@@ -306,27 +427,7 @@ impl IntoCData for Bson {
                     Ok((*i as u64, None))
                 }
             }
-            // Note that this isn't perfect because there are some 64bit integer values that are
-            // not representable as doubles. There *could* be a specific value where we will get a
-            // different result here than if we had a conversion from Decimal128 to i64 directly.
-            // We should update this when the bson crate supports Decimal128 entirely.
-            Bson::Decimal128(_) => {
-                let (out, _) = self.to_f64()?;
-                // there is no try_from implementation for f64 to u64
-                #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
-                if out > u64::MAX as f64 || out < u64::MIN as f64 {
-                    Err(ODBCError::IntegralTruncation(out.to_string()))
-                } else {
-                    Ok((
-                        out as u64,
-                        if out.floor() != out {
-                            Some(ODBCError::FractionalTruncation(out.to_string()))
-                        } else {
-                            None
-                        },
-                    ))
-                }
-            }
+            Bson::Decimal128(d) => decimal128_to_integer(d),
             o => Err(ODBCError::RestrictedDataType(o.to_type_str(), UINT64)),
         }
     }
@@ -339,24 +440,7 @@ impl IntoCData for Bson {
             Bson::Int64(i) if *i > i64::from(u32::MAX) || *i < 0i64 => {
                 Err(ODBCError::IntegralTruncation(i.to_string()))
             }
-            Bson::Decimal128(_) => {
-                let (out, _) = self.to_f64()?;
-                if out > f64::from(u32::MAX) || out < f64::from(u32::MIN) {
-                    Err(ODBCError::IntegralTruncation(out.to_string()))
-                } else {
-                    // This is safe because we've already checked that the value is within the range of u32
-                    // there is no try_from implementation for f64 to u32
-                    #[allow(clippy::cast_possible_truncation)]
-                    Ok((
-                        out as u32,
-                        if out.trunc() != out {
-                            Some(ODBCError::FractionalTruncation(out.to_string()))
-                        } else {
-                            None
-                        },
-                    ))
-                }
-            }
+            Bson::Decimal128(d) => decimal128_to_integer(d),
             Bson::Int32(i) if *i < 0i32 => Err(ODBCError::IntegralTruncation(i.to_string())),
             Bson::String(s) => Bson::Double(
                 f64::from_str(s).map_err(|_| ODBCError::InvalidCharacterValue(UINT32))?,
@@ -382,6 +466,22 @@ impl IntoCData for Bson {
         }
     }
 
+    fn to_i16(&self) -> Result<(i16, Option<ODBCError>)> {
+        bson_to_small_integer(self, INT16, false)
+    }
+
+    fn to_u16(&self) -> Result<(u16, Option<ODBCError>)> {
+        bson_to_small_integer(self, UINT16, true)
+    }
+
+    fn to_i8(&self) -> Result<(i8, Option<ODBCError>)> {
+        bson_to_small_integer(self, INT8, false)
+    }
+
+    fn to_u8(&self) -> Result<(u8, Option<ODBCError>)> {
+        bson_to_small_integer(self, UINT8, true)
+    }
+
     fn to_bit(&self) -> Result<(u8, Option<ODBCError>)> {
         match self {
             Bson::Double(f) => f64_to_bit(*f),
@@ -400,6 +500,63 @@ impl IntoCData for Bson {
         }
     }
 
+    fn to_numeric(&self, record: DescriptorRecord) -> Result<(Numeric, Option<ODBCError>)> {
+        let decimal = match self {
+            Bson::Decimal128(d) => Decimal::from(d),
+            Bson::Int32(i) => Decimal::from(i64::from(*i)),
+            Bson::Int64(i) => Decimal::from(*i),
+            Bson::Boolean(b) => Decimal::from(i64::from(*b)),
+            // The shortest representation that round trips is exact in decimal.
+            Bson::Double(f) => Decimal::from(
+                &Decimal128::from_str(&format!("{f:e}"))
+                    .map_err(|_| ODBCError::IntegralTruncation(f.to_string()))?,
+            ),
+            Bson::String(s) => Decimal::from(
+                &Decimal128::from_str(s.trim())
+                    .map_err(|_| ODBCError::InvalidCharacterValue(NUMERIC))?,
+            ),
+            o => return Err(ODBCError::RestrictedDataType(o.to_type_str(), NUMERIC)),
+        };
+        decimal_to_numeric(decimal, record, || match self {
+            Bson::Decimal128(d) => d.to_string(),
+            Bson::String(s) => s.clone(),
+            o => o.to_string(),
+        })
+    }
+
+    fn to_interval(&self, interval: Interval) -> Result<(IntervalStruct, Option<ODBCError>)> {
+        let value = match self {
+            Bson::Decimal128(d) => IntervalValue::from_decimal(interval, Decimal::from(d)),
+            Bson::Int32(i) => IntervalValue::from_decimal(interval, Decimal::from(i64::from(*i))),
+            Bson::Int64(i) => IntervalValue::from_decimal(interval, Decimal::from(*i)),
+            Bson::Double(f) => match Decimal128::from_str(&format!("{f:e}")) {
+                Ok(d) => IntervalValue::from_decimal(interval, Decimal::from(&d)),
+                // NaN and the infinities do not fit in any interval field.
+                Err(_) => Err(IntervalError::Overflow),
+            },
+            Bson::String(s) => IntervalValue::parse(interval, s),
+            o => return Err(ODBCError::RestrictedDataType(o.to_type_str(), INTERVAL)),
+        };
+        let value_string = || match self {
+            Bson::Decimal128(d) => d.to_string(),
+            Bson::String(s) => s.clone(),
+            o => o.to_string(),
+        };
+        match value {
+            Ok((value, inexact)) => Ok((
+                value.into(),
+                inexact.then(|| ODBCError::FractionalTruncation(value_string())),
+            )),
+            Err(IntervalError::Overflow) => Err(ODBCError::IntervalFieldOverflow(value_string())),
+            Err(IntervalError::InvalidCharacterValue) => {
+                Err(ODBCError::InvalidCharacterValue(INTERVAL))
+            }
+            Err(IntervalError::RestrictedDataType) => {
+                Err(ODBCError::RestrictedDataType(self.to_type_str(), INTERVAL))
+            }
+        }
+    }
+
     fn to_datetime(&self) -> Result<(DateTime<Utc>, Option<ODBCError>)> {
         match self {
             Bson::DateTime(d) => Ok(((*d).into(), None)),
@@ -847,6 +1004,7 @@ pub unsafe fn format_cached_data(
         }
     }
 }
+// The precision and scale of SQL_C_NUMERIC data are those of ard_record.
 #[allow(clippy::too_many_arguments)]
 pub unsafe fn format_bson_data(
     mongo_handle: &mut MongoHandle,
@@ -856,8 +1014,51 @@ pub unsafe fn format_bson_data(
     buffer_len: Len,
     str_len_or_ind_ptr: *mut Len,
     data: Bson,
+    ard_record: DescriptorRecord,
     function_name: &str,
 ) -> SqlReturn {
+    let uuid_repr = match (*mongo_handle).as_statement_connection() {
+        Some(conn) => match conn.mongo_connection.read() {
+            Ok(conn) => {
+                if conn.as_ref().is_some() {
+                    conn.as_ref().unwrap().uuid_repr
+                } else {
+                    None
+                }
+            }
+            Err(_) => None,
+        },
+        None => None,
+    };
+    let (timezone, date_out_of_range, json_format) = (*mongo_handle)
+        .as_statement_connection()
+        .map(|conn| {
+            (
+                *conn.timezone.read().unwrap(),
+                *conn.date_out_of_range.read().unwrap(),
+                *conn.json_format.read().unwrap(),
+            )
+        })
+        .unwrap_or_default();
+    let json_format = match (*mongo_handle).as_statement() {
+        Some(stmt) => json_format.for_statement(stmt.attributes.read().unwrap().json_format),
+        None => json_format,
+    };
+
+    // Dates outside of the years 0001 to 9999 may be replaced before being converted to
+    // timestamp, date, time or character data.
+    let (data, date_warning) = match target_type {
+        CDataType::SQL_C_TIMESTAMP
+        | CDataType::SQL_C_TYPE_TIMESTAMP
+        | CDataType::SQL_C_DATE
+        | CDataType::SQL_C_TYPE_DATE
+        | CDataType::SQL_C_TIME
+        | CDataType::SQL_C_TYPE_TIME
+        | CDataType::SQL_C_CHAR
+        | CDataType::SQL_C_WCHAR => date_out_of_range.apply(data, timezone),
+        _ => (data, None),
+    };
+
     // If the data is null or undefined we immediately return NULL_DATA indicator.
     match data {
         Bson::Null | Bson::Undefined => {
@@ -872,31 +1073,21 @@ pub unsafe fn format_bson_data(
             }
             *str_len_or_ind_ptr = definitions::SQL_NULL_DATA;
             stmt.insert_var_data_cache(col_num, CachedData::Fixed);
+            if let Some(warning) = date_warning {
+                stmt.errors.write().unwrap().push(warning);
+                return SqlReturn::SUCCESS_WITH_INFO;
+            }
             return SqlReturn::SUCCESS;
         }
         _ => {}
     }
 
-    let uuid_repr = match (*mongo_handle).as_statement_connection() {
-        Some(conn) => match conn.mongo_connection.read() {
-            Ok(conn) => {
-                if conn.as_ref().is_some() {
-                    conn.as_ref().unwrap().uuid_repr
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        },
-        None => None,
-    };
-
     match target_type {
         CDataType::SQL_C_BINARY | CDataType::SQL_C_GUID => {
             let data = if target_type == CDataType::SQL_C_GUID {
                 data.to_guid(uuid_repr)
             } else {
-                data.to_binary(uuid_repr)
+                data.to_binary(uuid_repr, timezone, json_format)
             };
             match data {
                 Ok(data) => format_binary(
@@ -918,7 +1109,10 @@ pub unsafe fn format_bson_data(
             }
         }
         CDataType::SQL_C_CHAR => {
-            let data = data.to_json(uuid_repr).bytes().collect::<Vec<u8>>();
+            let data = data
+                .to_json(uuid_repr, timezone, json_format)
+                .bytes()
+                .collect::<Vec<u8>>();
             char_data!(
                 mongo_handle,
                 col_num,
@@ -932,7 +1126,7 @@ pub unsafe fn format_bson_data(
             )
         }
         CDataType::SQL_C_WCHAR => {
-            let data = cstr::to_widechar_vec(&data.to_json(uuid_repr));
+            let data = cstr::to_widechar_vec(&data.to_json(uuid_repr, timezone, json_format));
             char_data!(
                 mongo_handle,
                 col_num,
@@ -1008,26 +1202,93 @@ pub unsafe fn format_bson_data(
                 str_len_or_ind_ptr
             )
         }
+        CDataType::SQL_C_SSHORT | CDataType::SQL_C_SHORT => {
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_i16(),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
+        CDataType::SQL_C_USHORT => {
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_u16(),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
+        CDataType::SQL_C_STINYINT | CDataType::SQL_C_TINYINT => {
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_i8(),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
+        CDataType::SQL_C_UTINYINT => {
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_u8(),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
+        CDataType::SQL_C_NUMERIC => {
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_numeric(ard_record),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
+        CDataType::SQL_C_INTERVAL_YEAR
+        | CDataType::SQL_C_INTERVAL_MONTH
+        | CDataType::SQL_C_INTERVAL_DAY
+        | CDataType::SQL_C_INTERVAL_HOUR
+        | CDataType::SQL_C_INTERVAL_MINUTE
+        | CDataType::SQL_C_INTERVAL_SECOND
+        | CDataType::SQL_C_INTERVAL_YEAR_TO_MONTH
+        | CDataType::SQL_C_INTERVAL_DAY_TO_HOUR
+        | CDataType::SQL_C_INTERVAL_DAY_TO_MINUTE
+        | CDataType::SQL_C_INTERVAL_DAY_TO_SECOND
+        | CDataType::SQL_C_INTERVAL_HOUR_TO_MINUTE
+        | CDataType::SQL_C_INTERVAL_HOUR_TO_SECOND
+        | CDataType::SQL_C_INTERVAL_MINUTE_TO_SECOND => {
+            let interval = interval_type(target_type).unwrap();
+            fixed_data_with_warnings!(
+                mongo_handle,
+                col_num,
+                data.to_interval(interval),
+                target_value_ptr,
+                str_len_or_ind_ptr
+            )
+        }
         CDataType::SQL_C_TIMESTAMP | CDataType::SQL_C_TYPE_TIMESTAMP => format_datetime(
             mongo_handle,
             col_num,
             target_value_ptr,
             str_len_or_ind_ptr,
-            data,
+            timezone.localize(data),
         ),
         CDataType::SQL_C_TIME | CDataType::SQL_C_TYPE_TIME => format_time(
             mongo_handle,
             col_num,
             target_value_ptr,
             str_len_or_ind_ptr,
-            data,
+            timezone.localize(data),
         ),
         CDataType::SQL_C_DATE | CDataType::SQL_C_TYPE_DATE => format_date(
             mongo_handle,
             col_num,
             target_value_ptr,
             str_len_or_ind_ptr,
-            data,
+            timezone.localize(data),
         ),
         other => {
             add_diag_with_function!(
@@ -1667,6 +1928,70 @@ mod unit {
                                 info = info
                             );
                         }
+                        (&"i16", Ok(()), info) => {
+                            test_conversion_ok!(
+                                input = $bson,
+                                method = to_i16,
+                                expected = *expected as i16,
+                                info = info
+                            );
+                        }
+                        (&"i16", Err(()), info) => {
+                            test_conversion_err!(
+                                input = $bson,
+                                method = to_i16,
+                                expected = *expected as i16,
+                                info = info
+                            );
+                        }
+                        (&"u16", Ok(()), info) => {
+                            test_conversion_ok!(
+                                input = $bson,
+                                method = to_u16,
+                                expected = *expected as u16,
+                                info = info
+                            );
+                        }
+                        (&"u16", Err(()), info) => {
+                            test_conversion_err!(
+                                input = $bson,
+                                method = to_u16,
+                                expected = *expected as u16,
+                                info = info
+                            );
+                        }
+                        (&"i8", Ok(()), info) => {
+                            test_conversion_ok!(
+                                input = $bson,
+                                method = to_i8,
+                                expected = *expected as i8,
+                                info = info
+                            );
+                        }
+                        (&"i8", Err(()), info) => {
+                            test_conversion_err!(
+                                input = $bson,
+                                method = to_i8,
+                                expected = *expected as i8,
+                                info = info
+                            );
+                        }
+                        (&"u8", Ok(()), info) => {
+                            test_conversion_ok!(
+                                input = $bson,
+                                method = to_u8,
+                                expected = *expected as u8,
+                                info = info
+                            );
+                        }
+                        (&"u8", Err(()), info) => {
+                            test_conversion_err!(
+                                input = $bson,
+                                method = to_u8,
+                                expected = *expected as u8,
+                                info = info
+                            );
+                        }
                         _ => unimplemented!(),
                     },
                 );
@@ -1829,6 +2154,71 @@ mod unit {
             })
         }
 
+        #[test]
+        fn conversions_to_small_integers() {
+            type V = Vec<(
+                &'static str,
+                i32,
+                Result<(), ()>,
+                Option<OdbcState<'static>>,
+            )>;
+            let int_32s: HashMap<i32, V> = map! {
+                300 => vec![
+                    ("i16", 300, Ok(()), None),
+                    ("u16", 300, Ok(()), None),
+                    ("i8", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u8", 0, Err(()), Some(INTEGRAL_TRUNCATION))
+                ],
+                200 => vec![
+                    ("i16", 200, Ok(()), None),
+                    ("u16", 200, Ok(()), None),
+                    ("i8", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u8", 200, Ok(()), None)
+                ],
+                -128 => vec![
+                    ("i16", -128, Ok(()), None),
+                    ("u16", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("i8", -128, Ok(()), None),
+                    ("u8", 0, Err(()), Some(INTEGRAL_TRUNCATION))
+                ],
+                40000 => vec![
+                    ("i16", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u16", 40000, Ok(()), None),
+                    ("i8", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u8", 0, Err(()), Some(INTEGRAL_TRUNCATION))
+                ],
+                70000 => vec![
+                    ("i16", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u16", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("i8", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("u8", 0, Err(()), Some(INTEGRAL_TRUNCATION))
+                ]
+            };
+            int_32s.iter().for_each(|(k, v)| {
+                let bson = Bson::Int32(*k);
+                test_it!(bson, v);
+            });
+
+            let strings: HashMap<String, V> = map! {
+                (-PI).to_string() => vec![
+                    ("i16", -3, Ok(()), Some(FRACTIONAL_TRUNCATION)),
+                    ("u16", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                    ("i8", -3, Ok(()), Some(FRACTIONAL_TRUNCATION)),
+                    ("u8", 0, Err(()), Some(INTEGRAL_TRUNCATION)),
+                ],
+                "foo".to_string() => vec![
+                    ("i16", 0, Err(()), Some(INVALID_CHARACTER_VALUE)),
+                    ("u16", 0, Err(()), Some(INVALID_CHARACTER_VALUE)),
+                    ("i8", 0, Err(()), Some(INVALID_CHARACTER_VALUE)),
+                    ("u8", 0, Err(()), Some(INVALID_CHARACTER_VALUE)),
+                ],
+            };
+            strings.iter().for_each(|(k, v)| {
+                let bson = Bson::String(k.to_string());
+                test_it!(bson, v);
+            });
+        }
+
         #[test]
         fn string_conversions_to_datetimes() {
             use chrono::TimeZone;
@@ -7,7 +7,12 @@
 use std::str::FromStr;
 
 use crate::{
-    api::functions::{SQLFetch, SQLMoreResults},
+    api::{
+        date_range::DateOutOfRange,
+        functions::{SQLFetch, SQLMoreResults},
+        json_format::JsonFormat,
+        timezone::SessionTimeZone,
+    },
     handles::definitions::{
         Connection, ConnectionState, Env, EnvState, MongoHandle, Statement, StatementState,
     },
@@ -18,7 +23,9 @@ use bson::{
 };
 use chrono::prelude::*;
 use cstr::WideChar;
-use definitions::{Date, Nullability, SqlReturn, Time, Timestamp, WChar};
+use definitions::{
+    AttrMongoDbJsonFormat, Date, Nullability, Pointer, SqlReturn, Time, Timestamp, USmallInt, WChar,
+};
 use lazy_static::lazy_static;
 use mongo_odbc_core::{
     col_metadata::MongoColMetadata,
@@ -27,7 +34,7 @@ use mongo_odbc_core::{
         BsonTypeName,
     },
     mock_query::MongoQuery,
-    TypeMode,
+    DatabaseAs, SchemaDrift, TypeMode,
 };
 
 const ARRAY_COL: u16 = 1;
@@ -2301,6 +2308,347 @@ fn sql_get_u32_data(mq: MongoQuery) {
     }
 }
 
+fn sql_get_small_int_data(mq: MongoQuery) {
+    use crate::api::functions::SQLGetData;
+    use definitions::CDataType;
+    use std::{fmt::Debug, mem::size_of};
+
+    // Reads col as target_type and checks the value, or the first diagnostic message for
+    // SUCCESS_WITH_INFO and ERROR.
+    unsafe fn small_int_val_test<T: Copy + Debug + PartialEq>(
+        stmt_handle: *mut MongoHandle,
+        target_type: CDataType,
+        col: u16,
+        expected: T,
+        code: SqlReturn,
+        expected_error: &str,
+    ) {
+        let buffer: *mut std::ffi::c_void = Box::into_raw(Box::new([0u8; 8])) as *mut _;
+        let out_len_or_ind = &mut 0;
+        stmt_handle.as_mut().unwrap().clear_diagnostics();
+        // Forget that the column was read, so that it can be read again as another type.
+        (*stmt_handle)
+            .as_statement()
+            .unwrap()
+            .var_data_cache
+            .write()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .remove(&col);
+        assert_eq!(
+            code,
+            SQLGetData(
+                stmt_handle as *mut _,
+                col,
+                target_type as i16,
+                buffer,
+                0,
+                out_len_or_ind,
+            )
+        );
+        if code != SqlReturn::ERROR {
+            assert_eq!(size_of::<T>() as isize, *out_len_or_ind);
+            assert_eq!(expected, *(buffer as *const T));
+        }
+        if code != SqlReturn::SUCCESS {
+            assert_eq!(
+                expected_error.to_string(),
+                format!(
+                    "{}",
+                    (*stmt_handle)
+                        .as_statement()
+                        .unwrap()
+                        .errors
+                        .read()
+                        .unwrap()[0]
+                ),
+            );
+        }
+        let _ = Box::from_raw(buffer as *mut [u8; 8]);
+    }
+
+    let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+        EnvState::ConnectionAllocated,
+    ))));
+    let conn = Box::into_raw(Box::new(MongoHandle::Connection(Connection::with_state(
+        env as *mut _,
+        ConnectionState::Connected,
+    ))));
+    let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+    *stmt.mongo_statement.write().unwrap() = Some(Box::new(mq));
+
+    let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+    unsafe {
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+
+        for target_type in [CDataType::SQL_C_SSHORT, CDataType::SQL_C_SHORT] {
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                BOOL_COL,
+                1i16,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                I32_COL,
+                1i16,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                I64_COL,
+                0i16,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                NEGATIVE_COL,
+                -1i16,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                DOUBLE_COL,
+                1i16,
+                SqlReturn::SUCCESS_WITH_INFO,
+                "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                NUMBER_DECIMAL_COL,
+                1i16,
+                SqlReturn::SUCCESS_WITH_INFO,
+                "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                STRING_COL,
+                0i16,
+                SqlReturn::ERROR,
+                "[MongoDB][API] invalid character value for cast to type: Int16",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                ARRAY_COL,
+                0i16,
+                SqlReturn::ERROR,
+                "[MongoDB][API] BSON type array cannot be converted to ODBC type Int16",
+            );
+        }
+
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_USHORT,
+            I32_COL,
+            1u16,
+            SqlReturn::SUCCESS,
+            "",
+        );
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_USHORT,
+            NEGATIVE_COL,
+            0u16,
+            SqlReturn::ERROR,
+            "[MongoDB][API] integral data \"-1\" was truncated due to overflow",
+        );
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_USHORT,
+            DOC_COL,
+            0u16,
+            SqlReturn::ERROR,
+            "[MongoDB][API] BSON type object cannot be converted to ODBC type UInt16",
+        );
+
+        for target_type in [CDataType::SQL_C_STINYINT, CDataType::SQL_C_TINYINT] {
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                BOOL_COL,
+                1i8,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                NEGATIVE_COL,
+                -1i8,
+                SqlReturn::SUCCESS,
+                "",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                DOUBLE_COL,
+                1i8,
+                SqlReturn::SUCCESS_WITH_INFO,
+                "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+            );
+            small_int_val_test(
+                stmt_handle,
+                target_type,
+                STRING_COL,
+                0i8,
+                SqlReturn::ERROR,
+                "[MongoDB][API] invalid character value for cast to type: Int8",
+            );
+        }
+
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_UTINYINT,
+            I32_COL,
+            1u8,
+            SqlReturn::SUCCESS,
+            "",
+        );
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_UTINYINT,
+            NUMBER_DECIMAL_COL,
+            1u8,
+            SqlReturn::SUCCESS_WITH_INFO,
+            "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+        );
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_UTINYINT,
+            NEGATIVE_COL,
+            0u8,
+            SqlReturn::ERROR,
+            "[MongoDB][API] integral data \"-1\" was truncated due to overflow",
+        );
+        small_int_val_test(
+            stmt_handle,
+            CDataType::SQL_C_UTINYINT,
+            OID_COL,
+            0u8,
+            SqlReturn::ERROR,
+            "[MongoDB][API] BSON type objectId cannot be converted to ODBC type UInt8",
+        );
+
+        for col in [NULL_COL, UNDEFINED_COL] {
+            let out_len_or_ind = &mut 0;
+            let buffer: *mut std::ffi::c_void = Box::into_raw(Box::new(0i16)) as *mut _;
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetData(
+                    stmt_handle as *mut _,
+                    col,
+                    CDataType::SQL_C_SSHORT as i16,
+                    buffer,
+                    0,
+                    out_len_or_ind,
+                )
+            );
+            assert_eq!(definitions::SQL_NULL_DATA, *out_len_or_ind);
+            let _ = Box::from_raw(buffer as *mut i16);
+        }
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+    }
+}
+
+fn sql_get_negative_unsigned_small_int_data(type_mode: TypeMode) {
+    use crate::api::functions::SQLGetData;
+    use definitions::CDataType;
+
+    // Negative values that would truncate to zero are out of range for unsigned types, whatever
+    // the BSON type of the value.
+    let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+        EnvState::ConnectionAllocated,
+    ))));
+    let conn = Box::into_raw(Box::new(MongoHandle::Connection(Connection::with_state(
+        env as *mut _,
+        ConnectionState::Connected,
+    ))));
+    let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+    *stmt.mongo_statement.write().unwrap() = Some(Box::new(MongoQuery::new(
+        vec![doc! {"a": {
+            "d": -0.5,
+            "s": "-0.5",
+            "n": bson::Decimal128::from_str("-0.5").unwrap(),
+        }}],
+        [
+            ("d", BsonTypeName::Double),
+            ("s", BsonTypeName::String),
+            ("n", BsonTypeName::Decimal),
+        ]
+        .into_iter()
+        .map(|(name, bson_type)| {
+            MongoColMetadata::new(
+                "",
+                "a".to_string(),
+                name.to_string(),
+                Schema::Atomic(Atomic::Scalar(bson_type)),
+                Nullability::SQL_NO_NULLS,
+                type_mode,
+                None,
+            )
+        })
+        .collect(),
+    )));
+    let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+    unsafe {
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+        let buffer: Pointer = Box::into_raw(Box::new([0u8; 8])) as *mut _;
+        for target_type in [CDataType::SQL_C_UTINYINT, CDataType::SQL_C_USHORT] {
+            for col in 1..=3 {
+                stmt_handle.as_mut().unwrap().clear_diagnostics();
+                (*stmt_handle)
+                    .as_statement()
+                    .unwrap()
+                    .var_data_cache
+                    .write()
+                    .unwrap()
+                    .as_mut()
+                    .unwrap()
+                    .remove(&col);
+                assert_eq!(
+                    SqlReturn::ERROR,
+                    SQLGetData(
+                        stmt_handle as *mut _,
+                        col,
+                        target_type as i16,
+                        buffer,
+                        0,
+                        &mut 0,
+                    )
+                );
+                let errors = (*stmt_handle)
+                    .as_statement()
+                    .unwrap()
+                    .errors
+                    .read()
+                    .unwrap();
+                assert_eq!("22003", errors[0].get_sql_state().odbc_3_state);
+                assert_eq!(
+                    "[MongoDB][API] integral data \"-0.5\" was truncated due to overflow",
+                    errors[0].to_string()
+                );
+            }
+        }
+        let _ = Box::from_raw(buffer as *mut [u8; 8]);
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+    }
+}
+
 fn sql_get_f64_data(mq: MongoQuery) {
     use crate::api::functions::SQLGetData;
     use definitions::CDataType;
@@ -2675,6 +3023,399 @@ fn sql_get_f32_data(mq: MongoQuery) {
     }
 }
 
+fn sql_get_numeric_data(mq: MongoQuery) {
+    use crate::api::functions::{
+        SQLBindCol, SQLFreeStmt, SQLGetData, SQLGetDescFieldW, SQLSetDescFieldW,
+    };
+    use definitions::{CDataType, Desc, FreeStmtOption, Numeric};
+    use std::mem::size_of;
+
+    let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+        EnvState::ConnectionAllocated,
+    ))));
+    let conn = Box::into_raw(Box::new(MongoHandle::Connection(Connection::with_state(
+        env as *mut _,
+        ConnectionState::Connected,
+    ))));
+    let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+    *stmt.mongo_statement.write().unwrap() = Some(Box::new(mq));
+    let ard = stmt.attributes.read().unwrap().app_row_desc;
+
+    let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+    unsafe {
+        // The ARD of the statement describes the columns bound with SQLBindCol.
+        (*conn)
+            .as_connection()
+            .unwrap()
+            .statements
+            .write()
+            .unwrap()
+            .insert(stmt_handle);
+        let set_desc_field = |col: u16, field: Desc, value: isize| {
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLSetDescFieldW(ard as *mut _, col as i16, field as i16, value as *mut _, 0,)
+            );
+        };
+        let get_desc_field = |col: u16, field: Desc| {
+            let mut value: isize = 0;
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetDescFieldW(
+                    ard as *mut _,
+                    col as i16,
+                    field as i16,
+                    &mut value as *mut _ as *mut _,
+                    0,
+                    std::ptr::null_mut(),
+                )
+            );
+            value
+        };
+        let bound_buffer = Box::into_raw(Box::new(Numeric::default()));
+        let bound_len_or_ind = &mut 0;
+        assert_eq!(
+            SqlReturn::SUCCESS,
+            SQLBindCol(
+                stmt_handle as *mut _,
+                DOUBLE_COL,
+                CDataType::SQL_C_NUMERIC as i16,
+                bound_buffer as *mut _,
+                size_of::<Numeric>() as isize,
+                bound_len_or_ind,
+            )
+        );
+        assert_eq!(
+            CDataType::SQL_C_NUMERIC as isize,
+            get_desc_field(DOUBLE_COL, Desc::SQL_DESC_CONCISE_TYPE)
+        );
+        set_desc_field(DOUBLE_COL, Desc::SQL_DESC_PRECISION, 5);
+        set_desc_field(DOUBLE_COL, Desc::SQL_DESC_SCALE, 2);
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+        // The bound column uses the precision and scale of its ARD record.
+        assert_eq!(
+            Numeric {
+                precision: 5,
+                scale: 2,
+                sign: 1,
+                val: 130u128.to_le_bytes(),
+            },
+            *bound_buffer
+        );
+        assert_eq!(size_of::<Numeric>() as isize, *bound_len_or_ind);
+        assert_eq!(
+            SqlReturn::SUCCESS,
+            SQLFreeStmt(stmt_handle as *mut _, FreeStmtOption::SQL_UNBIND as i16)
+        );
+        let _ = Box::from_raw(bound_buffer);
+
+        let buffer: *mut std::ffi::c_void = Box::into_raw(Box::new(Numeric::default())) as *mut _;
+        let out_len_or_ind = &mut 0;
+        {
+            let set_precision_scale = |col: u16, precision: isize, scale: isize| {
+                set_desc_field(col, Desc::SQL_DESC_TYPE, CDataType::SQL_C_NUMERIC as isize);
+                set_desc_field(col, Desc::SQL_DESC_PRECISION, precision);
+                set_desc_field(col, Desc::SQL_DESC_SCALE, scale);
+            };
+            let mut numeric_val_test =
+                |target_type: CDataType,
+                 col: u16,
+                 expected: Numeric,
+                 code: SqlReturn,
+                 expected_error: &str| {
+                    stmt_handle.as_mut().unwrap().clear_diagnostics();
+                    // Forget that the column was read, so that it can be read again with another
+                    // precision and scale.
+                    (*stmt_handle)
+                        .as_statement()
+                        .unwrap()
+                        .var_data_cache
+                        .write()
+                        .unwrap()
+                        .as_mut()
+                        .unwrap()
+                        .remove(&col);
+                    assert_eq!(
+                        code,
+                        SQLGetData(
+                            stmt_handle as *mut _,
+                            col,
+                            target_type as i16,
+                            buffer,
+                            0,
+                            out_len_or_ind,
+                        )
+                    );
+                    if code != SqlReturn::ERROR {
+                        assert_eq!(size_of::<Numeric>() as isize, *out_len_or_ind);
+                        assert_eq!(expected, *(buffer as *const Numeric));
+                    }
+                    if code != SqlReturn::SUCCESS {
+                        assert_eq!(
+                            expected_error.to_string(),
+                            format!(
+                                "{}",
+                                (*stmt_handle)
+                                    .as_statement()
+                                    .unwrap()
+                                    .errors
+                                    .read()
+                                    .unwrap()[0]
+                            ),
+                        );
+                    }
+                };
+            let numeric = |precision: u8, scale: i8, sign: u8, val: u128| Numeric {
+                precision,
+                scale,
+                sign,
+                val: val.to_le_bytes(),
+            };
+
+            // The default precision and scale are 38 and 0.
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                NUMBER_DECIMAL_COL,
+                numeric(38, 0, 1, 1),
+                SqlReturn::SUCCESS_WITH_INFO,
+                "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                I32_COL,
+                numeric(38, 0, 1, 1),
+                SqlReturn::SUCCESS,
+                "",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                BOOL_COL,
+                numeric(38, 0, 1, 1),
+                SqlReturn::SUCCESS,
+                "",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                NEGATIVE_COL,
+                numeric(38, 0, 0, 1),
+                SqlReturn::SUCCESS,
+                "",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                ARRAY_COL,
+                Numeric::default(),
+                SqlReturn::ERROR,
+                "[MongoDB][API] BSON type array cannot be converted to ODBC type Numeric",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                STRING_COL,
+                Numeric::default(),
+                SqlReturn::ERROR,
+                "[MongoDB][API] invalid character value for cast to type: Numeric",
+            );
+
+            // The ARD precision and scale only apply with SQL_ARD_TYPE.
+            set_precision_scale(NUMBER_DECIMAL_COL, 10, 4);
+            numeric_val_test(
+                CDataType::SQL_ARD_TYPE,
+                NUMBER_DECIMAL_COL,
+                numeric(10, 4, 1, 13000),
+                SqlReturn::SUCCESS,
+                "",
+            );
+            numeric_val_test(
+                CDataType::SQL_C_NUMERIC,
+                NUMBER_DECIMAL_COL,
+                numeric(38, 0, 1, 1),
+                SqlReturn::SUCCESS_WITH_INFO,
+                "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+            );
+            set_precision_scale(DOUBLE_COL, 5, 2);
+            numeric_val_test(
+                CDataType::SQL_ARD_TYPE,
+                DOUBLE_COL,
+                numeric(5, 2, 1, 130),
+                SqlReturn::SUCCESS,
+                "",
+            );
+            set_precision_scale(NUMBER_DECIMAL_COL, 1, 1);
+            numeric_val_test(
+                CDataType::SQL_ARD_TYPE,
+                NUMBER_DECIMAL_COL,
+                Numeric::default(),
+                SqlReturn::ERROR,
+                "[MongoDB][API] integral data \"1.3\" was truncated due to overflow",
+            );
+        }
+        let _ = Box::from_raw(buffer as *mut Numeric);
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+    }
+}
+
+fn sql_get_interval_data(mq: MongoQuery) {
+    use crate::api::functions::SQLGetData;
+    use definitions::{CDataType, DaySecond, Interval, IntervalStruct, YearMonth};
+    use std::mem::size_of;
+
+    // Reads col as target_type and checks the return code, and the first diagnostic message for
+    // SUCCESS_WITH_INFO and ERROR. Returns the interval that was read.
+    unsafe fn interval_val_test(
+        stmt_handle: *mut MongoHandle,
+        target_type: CDataType,
+        col: u16,
+        code: SqlReturn,
+        expected_error: &str,
+    ) -> IntervalStruct {
+        let mut interval = std::mem::zeroed::<IntervalStruct>();
+        let out_len_or_ind = &mut 0;
+        stmt_handle.as_mut().unwrap().clear_diagnostics();
+        // Forget that the column was read, so that it can be read again as another type.
+        (*stmt_handle)
+            .as_statement()
+            .unwrap()
+            .var_data_cache
+            .write()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .remove(&col);
+        assert_eq!(
+            code,
+            SQLGetData(
+                stmt_handle as *mut _,
+                col,
+                target_type as i16,
+                &mut interval as *mut _ as *mut _,
+                0,
+                out_len_or_ind,
+            )
+        );
+        if code != SqlReturn::ERROR {
+            assert_eq!(size_of::<IntervalStruct>() as isize, *out_len_or_ind);
+        }
+        if code != SqlReturn::SUCCESS {
+            assert_eq!(
+                expected_error.to_string(),
+                format!(
+                    "{}",
+                    (*stmt_handle)
+                        .as_statement()
+                        .unwrap()
+                        .errors
+                        .read()
+                        .unwrap()[0]
+                ),
+            );
+        }
+        interval
+    }
+
+    let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+        EnvState::ConnectionAllocated,
+    ))));
+    let conn = Box::into_raw(Box::new(MongoHandle::Connection(Connection::with_state(
+        env as *mut _,
+        ConnectionState::Connected,
+    ))));
+    let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+    *stmt.mongo_statement.write().unwrap() = Some(Box::new(mq));
+
+    let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+    unsafe {
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+
+        let interval = interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_DAY,
+            I32_COL,
+            SqlReturn::SUCCESS,
+            "",
+        );
+        assert_eq!(Interval::SQL_IS_DAY as i32, interval.interval_type);
+        assert_eq!(0, interval.interval_sign);
+        assert_eq!(
+            DaySecond {
+                day: 1,
+                ..Default::default()
+            },
+            interval.interval_value.day_second
+        );
+
+        let interval = interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_YEAR,
+            NEGATIVE_COL,
+            SqlReturn::SUCCESS,
+            "",
+        );
+        assert_eq!(Interval::SQL_IS_YEAR as i32, interval.interval_type);
+        assert_eq!(1, interval.interval_sign);
+        assert_eq!(
+            YearMonth { year: 1, month: 0 },
+            interval.interval_value.year_month
+        );
+
+        let interval = interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_SECOND,
+            DOUBLE_COL,
+            SqlReturn::SUCCESS,
+            "",
+        );
+        assert_eq!(
+            DaySecond {
+                second: 1,
+                fraction: 300_000,
+                ..Default::default()
+            },
+            interval.interval_value.day_second
+        );
+
+        let interval = interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_HOUR,
+            NUMBER_DECIMAL_COL,
+            SqlReturn::SUCCESS_WITH_INFO,
+            "[MongoDB][API] floating point data \"1.3\" was truncated to fixed point",
+        );
+        assert_eq!(
+            DaySecond {
+                hour: 1,
+                ..Default::default()
+            },
+            interval.interval_value.day_second
+        );
+
+        interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_DAY_TO_SECOND,
+            I32_COL,
+            SqlReturn::ERROR,
+            "[MongoDB][API] BSON type int cannot be converted to ODBC type Interval",
+        );
+        interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_MINUTE,
+            STRING_COL,
+            SqlReturn::ERROR,
+            "[MongoDB][API] invalid character value for cast to type: Interval",
+        );
+        interval_val_test(
+            stmt_handle,
+            CDataType::SQL_C_INTERVAL_YEAR_TO_MONTH,
+            ARRAY_COL,
+            SqlReturn::ERROR,
+            "[MongoDB][API] BSON type array cannot be converted to ODBC type Interval",
+        );
+
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+    }
+}
+
 fn sql_get_datetime_data(mq: MongoQuery) {
     use crate::api::functions::SQLGetData;
     use definitions::CDataType;
@@ -2892,6 +3633,280 @@ fn sql_get_datetime_data(mq: MongoQuery) {
     }
 }
 
+fn sql_get_datetime_data_with_timezone(mq: MongoQuery) {
+    use crate::api::functions::SQLGetData;
+    use chrono_tz::Tz;
+    use definitions::CDataType;
+
+    let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+        EnvState::ConnectionAllocated,
+    ))));
+    let conn = Connection::with_state(env as *mut _, ConnectionState::Connected);
+    // Kolkata is 5 hours 30 minutes ahead of UTC.
+    *conn.timezone.write().unwrap() = SessionTimeZone::Named(Tz::Asia__Kolkata);
+    let conn = Box::into_raw(Box::new(MongoHandle::Connection(conn)));
+    let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+    *stmt.mongo_statement.write().unwrap() = Some(Box::new(mq));
+
+    let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+    unsafe {
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+        let buffer: *mut std::ffi::c_void = Box::into_raw(Box::new([0u8; 200])) as *mut _;
+        let out_len_or_ind = &mut 0;
+        let mut get_datetime = |target_type: CDataType, code: SqlReturn| {
+            // Forget that the column was read, so that it can be read again as another type.
+            (*stmt_handle)
+                .as_statement()
+                .unwrap()
+                .var_data_cache
+                .write()
+                .unwrap()
+                .as_mut()
+                .unwrap()
+                .remove(&DATETIME_COL);
+            assert_eq!(
+                code,
+                SQLGetData(
+                    stmt_handle as *mut _,
+                    DATETIME_COL,
+                    target_type as i16,
+                    buffer,
+                    200,
+                    out_len_or_ind,
+                )
+            );
+        };
+
+        get_datetime(CDataType::SQL_C_TYPE_TIMESTAMP, SqlReturn::SUCCESS);
+        assert_eq!(
+            Timestamp {
+                year: 2014,
+                month: 11,
+                day: 28,
+                hour: 17,
+                minute: 30,
+                second: 9,
+                fraction: 0,
+            },
+            *(buffer as *const Timestamp)
+        );
+        // The time of day is truncated.
+        get_datetime(CDataType::SQL_C_TYPE_DATE, SqlReturn::SUCCESS_WITH_INFO);
+        assert_eq!(
+            Date {
+                year: 2014,
+                month: 11,
+                day: 28,
+            },
+            *(buffer as *const Date)
+        );
+        get_datetime(CDataType::SQL_C_TYPE_TIME, SqlReturn::SUCCESS);
+        assert_eq!(
+            Time {
+                hour: 17,
+                minute: 30,
+                second: 9,
+            },
+            *(buffer as *const Time)
+        );
+        get_datetime(CDataType::SQL_C_CHAR, SqlReturn::SUCCESS);
+        assert_eq!(
+            "{\"$date\":\"2014-11-28T17:30:09+05:30\"}",
+            std::str::from_utf8(std::slice::from_raw_parts(
+                buffer as *const u8,
+                *out_len_or_ind as usize
+            ))
+            .unwrap()
+        );
+
+        let _ = Box::from_raw(buffer as *mut [u8; 200]);
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+    }
+}
+
+fn sql_get_out_of_range_date_data(type_mode: TypeMode) {
+    use crate::api::functions::SQLGetData;
+    use definitions::CDataType;
+
+    // Reads the date column of a single row holding the last BSON date with the given policy
+    // and session time zone.
+    unsafe fn get_date(
+        type_mode: TypeMode,
+        date_out_of_range: DateOutOfRange,
+        timezone: SessionTimeZone,
+        target_type: CDataType,
+        buffer: Pointer,
+        out_len_or_ind: &mut isize,
+    ) -> (SqlReturn, Vec<String>) {
+        let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+            EnvState::ConnectionAllocated,
+        ))));
+        let conn = Connection::with_state(env as *mut _, ConnectionState::Connected);
+        *conn.date_out_of_range.write().unwrap() = date_out_of_range;
+        *conn.timezone.write().unwrap() = timezone;
+        let conn = Box::into_raw(Box::new(MongoHandle::Connection(conn)));
+        let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+        *stmt.mongo_statement.write().unwrap() = Some(Box::new(MongoQuery::new(
+            vec![doc! {"a": {"d": DateTime::MAX}}],
+            vec![MongoColMetadata::new(
+                "",
+                "a".to_string(),
+                "d".to_string(),
+                Schema::Atomic(Atomic::Scalar(BsonTypeName::Date)),
+                Nullability::SQL_NO_NULLS,
+                type_mode,
+                None,
+            )],
+        )));
+        let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+        let sql_return = SQLGetData(
+            stmt_handle as *mut _,
+            1,
+            target_type as i16,
+            buffer,
+            200,
+            out_len_or_ind,
+        );
+        let errors = (*stmt_handle)
+            .as_statement()
+            .unwrap()
+            .errors
+            .read()
+            .unwrap()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+        (sql_return, errors)
+    }
+
+    unsafe {
+        let buffer: Pointer = Box::into_raw(Box::new([0u8; 200])) as *mut _;
+        let out_len_or_ind = &mut 0;
+
+        let (sql_return, _) = get_date(
+            type_mode,
+            DateOutOfRange::Error,
+            SessionTimeZone::Utc,
+            CDataType::SQL_C_TYPE_TIMESTAMP,
+            buffer,
+            out_len_or_ind,
+        );
+        assert_eq!(SqlReturn::ERROR, sql_return);
+
+        for target_type in [
+            CDataType::SQL_C_TYPE_TIMESTAMP,
+            CDataType::SQL_C_TYPE_DATE,
+            CDataType::SQL_C_TYPE_TIME,
+            CDataType::SQL_C_CHAR,
+        ] {
+            let (sql_return, errors) = get_date(
+                type_mode,
+                DateOutOfRange::Null,
+                SessionTimeZone::Utc,
+                target_type,
+                buffer,
+                out_len_or_ind,
+            );
+            assert_eq!(SqlReturn::SUCCESS_WITH_INFO, sql_return);
+            assert_eq!(definitions::SQL_NULL_DATA, *out_len_or_ind);
+            assert_eq!(
+                vec![format!(
+                    "[MongoDB][API] date {} (milliseconds since the Unix epoch) is outside of the years 0001 to 9999 and was returned as NULL",
+                    i64::MAX
+                )],
+                errors
+            );
+        }
+
+        let (sql_return, _) = get_date(
+            type_mode,
+            DateOutOfRange::Clamp,
+            SessionTimeZone::Utc,
+            CDataType::SQL_C_TYPE_TIMESTAMP,
+            buffer,
+            out_len_or_ind,
+        );
+        assert_eq!(SqlReturn::SUCCESS, sql_return);
+        assert_eq!(
+            Timestamp {
+                year: 9999,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 59,
+                fraction: 999_000_000,
+            },
+            *(buffer as *const Timestamp)
+        );
+
+        let (sql_return, _) = get_date(
+            type_mode,
+            DateOutOfRange::Clamp,
+            SessionTimeZone::Utc,
+            CDataType::SQL_C_CHAR,
+            buffer,
+            out_len_or_ind,
+        );
+        assert_eq!(SqlReturn::SUCCESS, sql_return);
+        assert_eq!(
+            "{\"$date\":\"9999-12-31T23:59:59.999Z\"}",
+            std::str::from_utf8(std::slice::from_raw_parts(
+                buffer as *const u8,
+                *out_len_or_ind as usize
+            ))
+            .unwrap()
+        );
+
+        let (sql_return, _) = get_date(
+            type_mode,
+            DateOutOfRange::Clamp,
+            SessionTimeZone::Utc,
+            CDataType::SQL_C_TYPE_TIME,
+            buffer,
+            out_len_or_ind,
+        );
+        assert_eq!(SqlReturn::SUCCESS_WITH_INFO, sql_return);
+        assert_eq!(
+            Time {
+                hour: 23,
+                minute: 59,
+                second: 59,
+            },
+            *(buffer as *const Time)
+        );
+
+        // The date is clamped to the last date in the session time zone.
+        let (sql_return, _) = get_date(
+            type_mode,
+            DateOutOfRange::Clamp,
+            SessionTimeZone::Named(chrono_tz::Tz::Asia__Tokyo),
+            CDataType::SQL_C_TYPE_TIMESTAMP,
+            buffer,
+            out_len_or_ind,
+        );
+        assert_eq!(SqlReturn::SUCCESS, sql_return);
+        assert_eq!(
+            Timestamp {
+                year: 9999,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 59,
+                fraction: 999_000_000,
+            },
+            *(buffer as *const Timestamp)
+        );
+
+        let _ = Box::from_raw(buffer as *mut [u8; 200]);
+    }
+}
+
 fn sql_get_date_data(mq: MongoQuery) {
     use crate::api::functions::SQLGetData;
     use definitions::CDataType;
@@ -3311,6 +4326,134 @@ fn sql_get_time_data(mq: MongoQuery) {
     }
 }
 
+fn sql_get_json_format_data(type_mode: TypeMode) {
+    use crate::api::functions::SQLGetData;
+    use definitions::CDataType;
+
+    // Reads a document column holding an ObjectId, a date, a decimal and a UUID, or an int
+    // column, as character data.
+    unsafe fn get_json(
+        type_mode: TypeMode,
+        json_format: JsonFormat,
+        stmt_json_format: AttrMongoDbJsonFormat,
+        col_num: USmallInt,
+    ) -> String {
+        let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+            EnvState::ConnectionAllocated,
+        ))));
+        let conn = Connection::with_state(env as *mut _, ConnectionState::Connected);
+        *conn.json_format.write().unwrap() = json_format;
+        let conn = Box::into_raw(Box::new(MongoHandle::Connection(conn)));
+        let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+        stmt.attributes.write().unwrap().json_format = stmt_json_format;
+        *stmt.mongo_statement.write().unwrap() = Some(Box::new(MongoQuery::new(
+            vec![doc! {"a": {
+                "d": {
+                    "o": ObjectId::parse_str("63448dfed38427a35d534e40").unwrap(),
+                    "t": DateTime::from_millis(1_417_176_009_123),
+                    "n": bson::Decimal128::from_str("1.5").unwrap(),
+                    "i": 7i64,
+                    "u": Binary {
+                        subtype: BinarySubtype::Uuid,
+                        bytes: vec![0x11; 16],
+                    },
+                },
+                "n": 5,
+            }}],
+            vec![
+                MongoColMetadata::new(
+                    "",
+                    "a".to_string(),
+                    "d".to_string(),
+                    Schema::Atomic(Atomic::Scalar(BsonTypeName::Object)),
+                    Nullability::SQL_NO_NULLS,
+                    type_mode,
+                    None,
+                ),
+                MongoColMetadata::new(
+                    "",
+                    "a".to_string(),
+                    "n".to_string(),
+                    Schema::Atomic(Atomic::Scalar(BsonTypeName::Int)),
+                    Nullability::SQL_NO_NULLS,
+                    type_mode,
+                    None,
+                ),
+            ],
+        )));
+        let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+        assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _,));
+        let buffer: Pointer = Box::into_raw(Box::new([0u8; 400])) as *mut _;
+        let out_len_or_ind = &mut 0;
+        assert_eq!(
+            SqlReturn::SUCCESS,
+            SQLGetData(
+                stmt_handle as *mut _,
+                col_num,
+                CDataType::SQL_C_CHAR as i16,
+                buffer,
+                400,
+                out_len_or_ind,
+            )
+        );
+        let json = std::str::from_utf8(std::slice::from_raw_parts(
+            buffer as *const u8,
+            *out_len_or_ind as usize,
+        ))
+        .unwrap()
+        .to_string();
+        let _ = Box::from_raw(buffer as *mut [u8; 400]);
+        let _ = Box::from_raw(conn as *mut WChar);
+        let _ = Box::from_raw(env as *mut WChar);
+        json
+    }
+
+    let default = AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_DEFAULT;
+    unsafe {
+        assert_eq!(
+            "{\"o\":{\"$oid\":\"63448dfed38427a35d534e40\"},\"t\":{\"$date\":\"2014-11-28T12:00:09.123Z\"},\"n\":{\"$numberDecimal\":\"1.5\"},\"i\":7,\"u\":{\"$uuid\":\"11111111-1111-1111-1111-111111111111\"}}",
+            get_json(type_mode, JsonFormat::Relaxed, default, 1)
+        );
+        let canonical = "{\"o\":{\"$oid\":\"63448dfed38427a35d534e40\"},\"t\":{\"$date\":{\"$numberLong\":\"1417176009123\"}},\"n\":{\"$numberDecimal\":\"1.5\"},\"i\":{\"$numberLong\":\"7\"},\"u\":{\"$uuid\":\"11111111-1111-1111-1111-111111111111\"}}";
+        assert_eq!(
+            canonical,
+            get_json(type_mode, JsonFormat::Canonical, default, 1)
+        );
+        let simplified = "{\"o\":\"63448dfed38427a35d534e40\",\"t\":\"2014-11-28T12:00:09.123Z\",\"n\":\"1.5\",\"i\":7,\"u\":\"11111111-1111-1111-1111-111111111111\"}";
+        assert_eq!(
+            simplified,
+            get_json(type_mode, JsonFormat::Simplified, default, 1)
+        );
+        // The format of the statement overrides that of the connection.
+        assert_eq!(
+            canonical,
+            get_json(
+                type_mode,
+                JsonFormat::Simplified,
+                AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_CANONICAL,
+                1
+            )
+        );
+        assert_eq!(
+            simplified,
+            get_json(
+                type_mode,
+                JsonFormat::Canonical,
+                AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_SIMPLIFIED,
+                1
+            )
+        );
+        // Numbers are not rendered as extended JSON in any format.
+        for json_format in [
+            JsonFormat::Relaxed,
+            JsonFormat::Canonical,
+            JsonFormat::Simplified,
+        ] {
+            assert_eq!("5", get_json(type_mode, json_format, default, 2));
+        }
+    }
+}
+
 mod unit_tests {
 
     use super::*;
@@ -3369,6 +4512,10 @@ mod unit_tests {
             errors: RwLock::new(vec![]),
             type_mode: RwLock::new(TypeMode::Simple),
             max_string_length: RwLock::new(Some(6)),
+            database_as: RwLock::new(DatabaseAs::Catalog),
+            timezone: RwLock::new(SessionTimeZone::Utc),
+            date_out_of_range: RwLock::new(DateOutOfRange::Error),
+            json_format: RwLock::new(JsonFormat::Relaxed),
         })));
 
         // use simple type mode to test string columns for complex types
@@ -3517,6 +4664,18 @@ mod unit_tests {
         sql_get_u32_data(SIMPLE_BSON_TYPE_MQ.clone());
     }
 
+    #[test]
+    fn sql_get_small_int_data_test() {
+        sql_get_small_int_data(STANDARD_BSON_TYPE_MQ.clone());
+        sql_get_small_int_data(SIMPLE_BSON_TYPE_MQ.clone());
+    }
+
+    #[test]
+    fn sql_get_negative_unsigned_small_int_data_test() {
+        sql_get_negative_unsigned_small_int_data(TypeMode::Standard);
+        sql_get_negative_unsigned_small_int_data(TypeMode::Simple);
+    }
+
     #[test]
     fn sql_get_f64_data_test() {
         sql_get_f64_data(STANDARD_BSON_TYPE_MQ.clone());
@@ -3529,12 +4688,121 @@ mod unit_tests {
         sql_get_f32_data(SIMPLE_BSON_TYPE_MQ.clone());
     }
 
+    #[test]
+    fn sql_get_numeric_data_test() {
+        sql_get_numeric_data(STANDARD_BSON_TYPE_MQ.clone());
+        sql_get_numeric_data(SIMPLE_BSON_TYPE_MQ.clone());
+    }
+
+    #[test]
+    fn sql_get_interval_data_test() {
+        sql_get_interval_data(STANDARD_BSON_TYPE_MQ.clone());
+        sql_get_interval_data(SIMPLE_BSON_TYPE_MQ.clone());
+    }
+
     #[test]
     fn sql_get_datetime_data_test() {
         sql_get_datetime_data(STANDARD_BSON_TYPE_MQ.clone());
         sql_get_datetime_data(SIMPLE_BSON_TYPE_MQ.clone());
     }
 
+    #[test]
+    fn sql_get_datetime_data_with_timezone_test() {
+        sql_get_datetime_data_with_timezone(STANDARD_BSON_TYPE_MQ.clone());
+        sql_get_datetime_data_with_timezone(SIMPLE_BSON_TYPE_MQ.clone());
+    }
+
+    #[test]
+    fn sql_get_out_of_range_date_data_test() {
+        sql_get_out_of_range_date_data(TypeMode::Standard);
+        sql_get_out_of_range_date_data(TypeMode::Simple);
+    }
+
+    #[test]
+    fn sql_get_json_format_data_test() {
+        sql_get_json_format_data(TypeMode::Standard);
+        sql_get_json_format_data(TypeMode::Simple);
+    }
+
+    #[test]
+    fn sql_fetch_reports_schema_drift() {
+        use crate::{api::functions::SQLGetData, SQLGetStmtAttrW};
+        use definitions::{CDataType, StatementAttribute};
+
+        let env = Box::into_raw(Box::new(MongoHandle::Env(Env::with_state(
+            EnvState::ConnectionAllocated,
+        ))));
+        let conn = Box::into_raw(Box::new(MongoHandle::Connection(Connection::with_state(
+            env as *mut _,
+            ConnectionState::Connected,
+        ))));
+        let stmt = Statement::with_state(conn as *mut _, StatementState::Allocated);
+        *stmt.mongo_statement.write().unwrap() = Some(Box::new(
+            MongoQuery::new(
+                vec![doc! {"a": {"i": 1}}, doc! {"a": {"i": "one"}}],
+                vec![MongoColMetadata::new(
+                    "",
+                    "a".to_string(),
+                    "i".to_string(),
+                    Schema::Atomic(Atomic::Scalar(BsonTypeName::Int)),
+                    Nullability::SQL_NO_NULLS,
+                    TypeMode::Standard,
+                    None,
+                )],
+            )
+            .with_schema_drift(SchemaDrift::Warn),
+        ));
+        let stmt_handle: *mut _ = &mut MongoHandle::Statement(stmt);
+        let drift_count = || unsafe {
+            let mut count: definitions::ULen = 0;
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetStmtAttrW(
+                    stmt_handle as *mut _,
+                    StatementAttribute::SQL_ATTR_MONGODB_SCHEMA_DRIFT_COUNT as i32,
+                    &mut count as *mut definitions::ULen as Pointer,
+                    0,
+                    std::ptr::null_mut(),
+                )
+            );
+            count
+        };
+
+        unsafe {
+            assert_eq!(SqlReturn::SUCCESS, SQLFetch(stmt_handle as *mut _));
+            assert_eq!(0, drift_count());
+            assert_eq!(
+                SqlReturn::SUCCESS_WITH_INFO,
+                SQLFetch(stmt_handle as *mut _)
+            );
+            assert_eq!(
+                "[MongoDB][API] Value of column 'i' in row 2 is of type string instead of its schema type int",
+                (*stmt_handle).as_statement().unwrap().errors.read().unwrap()[0].to_string()
+            );
+            assert_eq!(1, drift_count());
+
+            // The value is still returned with the warn policy
+            let buffer: Pointer = Box::into_raw(Box::new([0u8; 20])) as *mut _;
+            let out_len_or_ind = &mut 0;
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetData(
+                    stmt_handle as *mut _,
+                    1,
+                    CDataType::SQL_C_CHAR as i16,
+                    buffer,
+                    20,
+                    out_len_or_ind,
+                )
+            );
+            assert_eq!(3, *out_len_or_ind);
+
+            let _ = Box::from_raw(buffer as *mut [u8; 20]);
+            let _ = Box::from_raw(conn as *mut WChar);
+            let _ = Box::from_raw(env as *mut WChar);
+        }
+    }
+
     #[test]
     fn sql_get_date_data_test() {
         sql_get_date_data(STANDARD_BSON_TYPE_MQ.clone());
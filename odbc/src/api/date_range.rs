@@ -0,0 +1,179 @@
+use crate::api::{errors::ODBCError, timezone::SessionTimeZone};
+use mongo_odbc_core::Error;
+use mongodb::bson::{self, Bson};
+use std::str::FromStr;
+
+// 0001-01-01T00:00:00Z and 9999-12-31T23:59:59.999Z, the first and last milliseconds of the
+// years SQL_TIMESTAMP_STRUCT and most client libraries accept.
+const MIN_DATE_MILLIS: i64 = -62_135_596_800_000;
+const MAX_DATE_MILLIS: i64 = 253_402_300_799_999;
+
+/// DateOutOfRange is how BSON dates outside of the years 0001 to 9999, in the session time zone,
+/// are converted to timestamp, date and character data. It comes from the date_out_of_range
+/// connection string/DSN option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateOutOfRange {
+    /// The date is converted as any other date, which fails when the year does not fit.
+    #[default]
+    Error,
+    /// The date is returned as NULL, with a warning.
+    Null,
+    /// The date is replaced by the first or last representable date.
+    Clamp,
+}
+
+impl FromStr for DateOutOfRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(DateOutOfRange::Error),
+            "null" => Ok(DateOutOfRange::Null),
+            "clamp" => Ok(DateOutOfRange::Clamp),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid date_out_of_range value '{s}', expected 'error', 'null' or 'clamp'"
+            ))),
+        }
+    }
+}
+
+impl DateOutOfRange {
+    /// Applies this policy to a BSON date outside of the years 0001 to 9999 in the session time
+    /// zone. Returns the value to convert instead, and the warning to report. Other values are
+    /// returned unchanged.
+    pub(crate) fn apply(&self, data: Bson, timezone: SessionTimeZone) -> (Bson, Option<ODBCError>) {
+        let Bson::DateTime(d) = data else {
+            return (data, None);
+        };
+        let local_millis = d
+            .timestamp_millis()
+            .saturating_add(timezone.offset_millis(d));
+        let bound = if local_millis < MIN_DATE_MILLIS {
+            MIN_DATE_MILLIS
+        } else if local_millis > MAX_DATE_MILLIS {
+            MAX_DATE_MILLIS
+        } else {
+            return (data, None);
+        };
+        match self {
+            DateOutOfRange::Error => (data, None),
+            DateOutOfRange::Null => (
+                Bson::Null,
+                Some(ODBCError::DateOutOfRange(d.timestamp_millis())),
+            ),
+            DateOutOfRange::Clamp => (Bson::DateTime(to_utc(bound, timezone)), None),
+        }
+    }
+}
+
+// Returns the date whose date and time in the time zone are the given UTC milliseconds. The
+// offset is taken at the approximate date first, since it may differ from the offset at the
+// given milliseconds read as UTC.
+fn to_utc(local_millis: i64, timezone: SessionTimeZone) -> bson::DateTime {
+    let offset = timezone.offset_millis(bson::DateTime::from_millis(local_millis));
+    let offset = timezone.offset_millis(bson::DateTime::from_millis(local_millis - offset));
+    bson::DateTime::from_millis(local_millis - offset)
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use chrono_tz::Tz;
+
+    fn date(s: &str) -> Bson {
+        Bson::DateTime(bson::DateTime::from_chrono(
+            s.parse::<DateTime<Utc>>().unwrap(),
+        ))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(DateOutOfRange::Error, "error".parse().unwrap());
+        assert_eq!(DateOutOfRange::Null, "NULL".parse().unwrap());
+        assert_eq!(DateOutOfRange::Clamp, "Clamp".parse().unwrap());
+        assert!("saturate".parse::<DateOutOfRange>().is_err());
+    }
+
+    #[test]
+    fn range_bounds() {
+        assert_eq!(
+            date("0001-01-01T00:00:00Z"),
+            Bson::DateTime(bson::DateTime::from_millis(MIN_DATE_MILLIS))
+        );
+        assert_eq!(
+            date("9999-12-31T23:59:59.999Z"),
+            Bson::DateTime(bson::DateTime::from_millis(MAX_DATE_MILLIS))
+        );
+    }
+
+    #[test]
+    fn in_range_dates_are_unchanged() {
+        for policy in [
+            DateOutOfRange::Error,
+            DateOutOfRange::Null,
+            DateOutOfRange::Clamp,
+        ] {
+            for d in [
+                "0001-01-01T00:00:00Z",
+                "2024-05-06T07:08:09Z",
+                "9999-12-31T23:59:59.999Z",
+            ] {
+                let (data, warning) = policy.apply(date(d), SessionTimeZone::Utc);
+                assert_eq!(date(d), data);
+                assert!(warning.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_dates() {
+        let too_late = Bson::DateTime(bson::DateTime::from_millis(MAX_DATE_MILLIS + 1));
+        let too_early = Bson::DateTime(bson::DateTime::MIN);
+
+        let (data, warning) = DateOutOfRange::Error.apply(too_late.clone(), SessionTimeZone::Utc);
+        assert_eq!(too_late, data);
+        assert!(warning.is_none());
+
+        let (data, warning) = DateOutOfRange::Null.apply(too_early, SessionTimeZone::Utc);
+        assert_eq!(Bson::Null, data);
+        assert_eq!("01S07", warning.unwrap().get_sql_state().odbc_3_state);
+
+        let (data, warning) = DateOutOfRange::Clamp.apply(too_late, SessionTimeZone::Utc);
+        assert_eq!(date("9999-12-31T23:59:59.999Z"), data);
+        assert!(warning.is_none());
+        assert_eq!(
+            date("0001-01-01T00:00:00Z"),
+            DateOutOfRange::Clamp
+                .apply(Bson::DateTime(bson::DateTime::MIN), SessionTimeZone::Utc)
+                .0
+        );
+    }
+
+    #[test]
+    fn out_of_range_dates_in_the_session_time_zone() {
+        let tokyo = SessionTimeZone::Named(Tz::Asia__Tokyo);
+        let new_york = SessionTimeZone::Named(Tz::America__New_York);
+
+        // 9999-12-31T20:00:00Z is in the year 10000 in Tokyo.
+        let (data, warning) = DateOutOfRange::Null.apply(date("9999-12-31T20:00:00Z"), tokyo);
+        assert_eq!(Bson::Null, data);
+        assert!(warning.is_some());
+        let (data, _) = DateOutOfRange::Clamp.apply(date("9999-12-31T20:00:00Z"), tokyo);
+        assert_eq!(date("9999-12-31T14:59:59.999Z"), data);
+        assert_eq!(date("9999-12-31T23:59:59.999Z"), tokyo.localize(data));
+
+        // 0001-01-01T02:00:00Z is in the year 0 in New York, whose offset was then -4:56:02.
+        let (data, _) = DateOutOfRange::Clamp.apply(date("0001-01-01T02:00:00Z"), new_york);
+        assert_eq!(date("0001-01-01T04:56:02Z"), data);
+        assert_eq!(date("0001-01-01T00:00:00Z"), new_york.localize(data));
+
+        // Dates after the last UTC date are in range in a time zone behind UTC.
+        let late = Bson::DateTime(bson::DateTime::from_millis(MAX_DATE_MILLIS + 3_600_000));
+        let (data, warning) = DateOutOfRange::Null.apply(late.clone(), new_york);
+        assert_eq!(late, data);
+        assert!(warning.is_none());
+        let (data, _) = DateOutOfRange::Clamp.apply(Bson::DateTime(bson::DateTime::MAX), new_york);
+        assert_eq!(date("9999-12-31T23:59:59.999Z"), new_york.localize(data));
+    }
+}
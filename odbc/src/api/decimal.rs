@@ -0,0 +1,249 @@
+use mongodb::bson::Decimal128;
+
+// Decimal128 values are stored in the BID encoding described in
+// https://github.com/mongodb/specifications/blob/master/source/bson-decimal128/decimal128.md
+const EXPONENT_BIAS: i32 = 6176;
+const MAX_COEFFICIENT: u128 = 10u128.pow(34) - 1;
+// The largest number of decimal digits a u128 can hold.
+const MAX_U128_DIGITS: u32 = 38;
+
+/// Decimal is the exact value of a Decimal128.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Decimal {
+    /// (-1)^negative * coefficient * 10^exponent
+    Finite {
+        negative: bool,
+        coefficient: u128,
+        exponent: i32,
+    },
+    Infinity {
+        negative: bool,
+    },
+    NaN,
+}
+
+/// A finite Decimal rescaled to a fixed number of digits after the decimal point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rescaled {
+    pub negative: bool,
+    /// The absolute value multiplied by 10^scale.
+    pub unscaled: u128,
+    /// True if nonzero digits were dropped to fit the scale.
+    pub inexact: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rounding {
+    /// Drop the extra digits.
+    Truncate,
+    /// Round to the nearest value, with ties away from zero.
+    HalfUp,
+}
+
+impl From<&Decimal128> for Decimal {
+    fn from(d: &Decimal128) -> Self {
+        let bits = u128::from_le_bytes(d.bytes());
+        let negative = bits >> 127 == 1;
+        // The two bits after the sign select between the two encodings of finite values, and
+        // the special values.
+        let (exponent_bits, coefficient) = if (bits >> 125) & 0b11 == 0b11 {
+            match (bits >> 122) & 0b1_1111 {
+                0b1_1110 => return Decimal::Infinity { negative },
+                0b1_1111 => return Decimal::NaN,
+                // The coefficient of this form always exceeds MAX_COEFFICIENT, which makes the
+                // value non-canonical. Non-canonical coefficients are interpreted as zero.
+                _ => ((bits >> 111) & 0x3fff, 0),
+            }
+        } else {
+            ((bits >> 113) & 0x3fff, bits & ((1 << 113) - 1))
+        };
+        Decimal::Finite {
+            negative,
+            coefficient: if coefficient > MAX_COEFFICIENT {
+                0
+            } else {
+                coefficient
+            },
+            // The exponent is 14 bits wide, so this cannot truncate.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            exponent: exponent_bits as i32 - EXPONENT_BIAS,
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(i: i64) -> Self {
+        Decimal::Finite {
+            negative: i < 0,
+            coefficient: u128::from(i.unsigned_abs()),
+            exponent: 0,
+        }
+    }
+}
+
+impl Decimal {
+    /// Returns this value with exactly `scale` digits after the decimal point, or None if it is
+    /// not finite or its magnitude does not fit in a u128.
+    pub(crate) fn rescale(&self, scale: i32, rounding: Rounding) -> Option<Rescaled> {
+        let Decimal::Finite {
+            negative,
+            coefficient,
+            exponent,
+        } = *self
+        else {
+            return None;
+        };
+        let shift = exponent + scale;
+        let (unscaled, inexact) = if coefficient == 0 {
+            (0, false)
+        } else if shift >= 0 {
+            let factor = 10u128.checked_pow(u32::try_from(shift).ok()?)?;
+            (coefficient.checked_mul(factor)?, false)
+        } else if shift.unsigned_abs() > MAX_U128_DIGITS {
+            // The divisor is larger than twice any coefficient, so the result rounds to zero.
+            (0, true)
+        } else {
+            let divisor = 10u128.pow(shift.unsigned_abs());
+            let (quotient, remainder) = (coefficient / divisor, coefficient % divisor);
+            let round_up = rounding == Rounding::HalfUp && remainder >= divisor - remainder;
+            (quotient + u128::from(round_up), remainder != 0)
+        };
+        Some(Rescaled {
+            negative: negative && unscaled != 0,
+            unscaled,
+            inexact,
+        })
+    }
+
+    /// Returns the integral part of this value, and whether a fractional part was dropped. None is
+    /// returned if the value is not finite or does not fit in an i128.
+    pub(crate) fn to_i128(self) -> Option<(i128, bool)> {
+        let rescaled = self.rescale(0, Rounding::Truncate)?;
+        let magnitude = i128::try_from(rescaled.unscaled).ok()?;
+        Some((
+            if rescaled.negative {
+                -magnitude
+            } else {
+                magnitude
+            },
+            rescaled.inexact,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use std::str::FromStr;
+
+    fn decimal(s: &str) -> Decimal {
+        Decimal::from(&Decimal128::from_str(s).unwrap())
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            Decimal::Finite {
+                negative: false,
+                coefficient: 12345,
+                exponent: -2
+            },
+            decimal("123.45")
+        );
+        assert_eq!(
+            Decimal::Finite {
+                negative: true,
+                coefficient: 1,
+                exponent: 3
+            },
+            decimal("-1E+3")
+        );
+        assert_eq!(
+            Decimal::Finite {
+                negative: false,
+                coefficient: MAX_COEFFICIENT,
+                exponent: 6111
+            },
+            decimal(&format!("{MAX_COEFFICIENT}E+6111"))
+        );
+        assert_eq!(Decimal::Infinity { negative: true }, decimal("-Infinity"));
+        assert_eq!(Decimal::NaN, decimal("NaN"));
+    }
+
+    #[test]
+    fn non_canonical_coefficient_is_zero() {
+        // The second encoding of finite values, with the largest coefficient bits set.
+        let bits: u128 = (0b11 << 125) | (0x1820 << 111) | ((1 << 111) - 1);
+        assert_eq!(
+            Decimal::Finite {
+                negative: false,
+                coefficient: 0,
+                exponent: 0
+            },
+            Decimal::from(&Decimal128::from_bytes(bits.to_le_bytes()))
+        );
+    }
+
+    #[test]
+    fn rescale() {
+        let d = decimal("-123.456");
+        assert_eq!(
+            Some(Rescaled {
+                negative: true,
+                unscaled: 12346,
+                inexact: true
+            }),
+            d.rescale(2, Rounding::HalfUp)
+        );
+        assert_eq!(
+            Some(Rescaled {
+                negative: true,
+                unscaled: 12345,
+                inexact: true
+            }),
+            d.rescale(2, Rounding::Truncate)
+        );
+        assert_eq!(
+            Some(Rescaled {
+                negative: true,
+                unscaled: 1234560,
+                inexact: false
+            }),
+            d.rescale(4, Rounding::HalfUp)
+        );
+        assert_eq!(
+            Some(Rescaled {
+                negative: false,
+                unscaled: 0,
+                inexact: true
+            }),
+            decimal("-0.0004").rescale(3, Rounding::HalfUp)
+        );
+        assert_eq!(
+            Some(Rescaled {
+                negative: false,
+                unscaled: 1,
+                inexact: true
+            }),
+            decimal("0.0005").rescale(3, Rounding::HalfUp)
+        );
+        assert_eq!(None, decimal("1E+39").rescale(0, Rounding::HalfUp));
+        assert_eq!(None, decimal("NaN").rescale(0, Rounding::HalfUp));
+        assert_eq!(
+            Some(Rescaled {
+                negative: false,
+                unscaled: 0,
+                inexact: false
+            }),
+            decimal("0E+6000").rescale(10, Rounding::HalfUp)
+        );
+    }
+
+    #[test]
+    fn to_i128() {
+        assert_eq!(Some((-12, true)), decimal("-12.9").to_i128());
+        assert_eq!(Some((1200, false)), decimal("1.2E+3").to_i128());
+        assert_eq!(None, decimal("1E+40").to_i128());
+        assert_eq!(None, decimal("Infinity").to_i128());
+    }
+}
@@ -114,19 +114,53 @@ mod unit {
                 EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING,
                 map! {
                     AttrConnectionPooling::SQL_CP_OFF as i32 => SqlReturn::SUCCESS,
-                    AttrConnectionPooling::SQL_CP_ONE_PER_HENV as i32 => SqlReturn::SUCCESS_WITH_INFO,
-                    AttrConnectionPooling::SQL_CP_ONE_PER_DRIVER as i32 => SqlReturn::SUCCESS_WITH_INFO,
-                    AttrConnectionPooling:: SQL_CP_DRIVER_AWARE as i32 => SqlReturn::SUCCESS_WITH_INFO,
+                    AttrConnectionPooling::SQL_CP_ONE_PER_HENV as i32 => SqlReturn::SUCCESS,
+                    AttrConnectionPooling::SQL_CP_ONE_PER_DRIVER as i32 => SqlReturn::SUCCESS,
                 },
                 AttrConnectionPooling::SQL_CP_OFF as i32,
             );
 
+            // The driver is not pool aware, so SQL_CP_DRIVER_AWARE is one pool per environment.
+            let attr_buffer = &mut 0i32;
+            assert_eq!(
+                SqlReturn::SUCCESS_WITH_INFO,
+                SQLSetEnvAttr(
+                    env_handle as HEnv,
+                    EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING as i32,
+                    AttrConnectionPooling::SQL_CP_DRIVER_AWARE as i32 as Pointer,
+                    0
+                )
+            );
+            assert_eq!(
+                SqlReturn::SUCCESS,
+                SQLGetEnvAttr(
+                    env_handle as *mut _,
+                    EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING as i32,
+                    attr_buffer as *mut i32 as Pointer,
+                    0,
+                    &mut 0
+                )
+            );
+            assert_eq!(
+                AttrConnectionPooling::SQL_CP_ONE_PER_HENV as i32,
+                *attr_buffer
+            );
+            assert_eq!(
+                SqlReturn::ERROR,
+                SQLSetEnvAttr(
+                    env_handle as HEnv,
+                    EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING as i32,
+                    42 as Pointer,
+                    0
+                )
+            );
+
             get_set_env_attr(
                 env_handle,
                 EnvironmentAttribute::SQL_ATTR_CP_MATCH,
                 map! {
                     AttrCpMatch:: SQL_CP_STRICT_MATCH as i32 => SqlReturn::SUCCESS,
-                    AttrCpMatch:: SQL_CP_RELAXED_MATCH as i32 => SqlReturn::SUCCESS_WITH_INFO,
+                    AttrCpMatch:: SQL_CP_RELAXED_MATCH as i32 => SqlReturn::SUCCESS,
                 },
                 AttrCpMatch::SQL_CP_STRICT_MATCH as i32,
             );
@@ -170,15 +204,15 @@ mod unit {
                 SqlReturn::SUCCESS_WITH_INFO,
                 SQLSetEnvAttr(
                     handle as HEnv,
-                    EnvironmentAttribute::SQL_ATTR_CP_MATCH as i32,
-                    AttrCpMatch::SQL_CP_RELAXED_MATCH as i32 as Pointer,
+                    EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING as i32,
+                    AttrConnectionPooling::SQL_CP_DRIVER_AWARE as i32 as Pointer,
                     0
                 )
             );
 
             let mut sql_state: [WideChar; 6] = [0; 6];
             let sql_state = &mut sql_state as *mut WideChar;
-            let mut message_text: [WideChar; 93] = [0; 93];
+            let mut message_text: [WideChar; 103] = [0; 103];
             let message_text = &mut message_text as *mut WideChar;
             assert_eq!(
                 SqlReturn::SUCCESS,
@@ -189,7 +223,7 @@ mod unit {
                     sql_state,
                     &mut 0,
                     message_text,
-                    93,
+                    103,
                     &mut 0
                 )
             );
@@ -198,8 +232,8 @@ mod unit {
                 cstr::from_widechar_ref_lossy(&*(sql_state as *const [WideChar; 6]))
             );
             assert_eq!(
-             "[MongoDB][API] Invalid value for attribute SQL_ATTR_CP_MATCH, changed to SQL_CP_STRICT_MATCH\0",
-                cstr::from_widechar_ref_lossy(&*(message_text as *const [WideChar; 93]))
+             "[MongoDB][API] Invalid value for attribute SQL_ATTR_CONNECTION_POOLING, changed to SQL_CP_ONE_PER_HENV\0",
+                cstr::from_widechar_ref_lossy(&*(message_text as *const [WideChar; 103]))
             );
         }
     }
@@ -1,11 +1,11 @@
 use constants::{
     OdbcState, CONNECTION_NOT_OPEN, FETCH_TYPE_OUT_OF_RANGE, FRACTIONAL_TRUNCATION, GENERAL_ERROR,
-    GENERAL_WARNING, INDICATOR_VARIABLE_REQUIRED, INTEGRAL_TRUNCATION,
+    GENERAL_WARNING, INDICATOR_VARIABLE_REQUIRED, INTEGRAL_TRUNCATION, INTERVAL_FIELD_OVERFLOW,
     INVALID_ATTRIBUTE_OR_OPTION_IDENTIFIER, INVALID_ATTR_VALUE, INVALID_CHARACTER_VALUE,
     INVALID_COLUMN_NUMBER, INVALID_CURSOR_STATE, INVALID_DATETIME_FORMAT, INVALID_DESCRIPTOR_INDEX,
     INVALID_DRIVER_COMPLETION, INVALID_FIELD_DESCRIPTOR, INVALID_INFO_TYPE_VALUE, INVALID_SQL_TYPE,
-    NOT_IMPLEMENTED, NO_DSN_OR_DRIVER, NO_RESULTSET, OPTION_CHANGED, PROGRAM_TYPE_OUT_OF_RANGE,
-    RESTRICTED_DATATYPE, RIGHT_TRUNCATED, VENDOR_IDENTIFIER,
+    INVALID_USE_OF_NULL_POINTER, NOT_IMPLEMENTED, NO_DSN_OR_DRIVER, NO_RESULTSET, OPTION_CHANGED,
+    PROGRAM_TYPE_OUT_OF_RANGE, RESTRICTED_DATATYPE, RIGHT_TRUNCATED, VENDOR_IDENTIFIER,
 };
 use thiserror::Error;
 
@@ -71,6 +71,11 @@ pub enum ODBCError {
     InvalidHandleType(&'static str),
     #[error("[{vendor}][API] Invalid value for attribute {0}", vendor = VENDOR_IDENTIFIER)]
     InvalidAttrValue(&'static str),
+    #[error(
+        "[{vendor}][API] The {0} argument cannot be a null pointer when SQL_ATTR_METADATA_ID is SQL_TRUE",
+        vendor = VENDOR_IDENTIFIER
+    )]
+    InvalidUseOfNullPointer(&'static str),
     #[error("[{vendor}][API] Invalid attribute identifier {0}", vendor = VENDOR_IDENTIFIER)]
     InvalidAttrIdentifier(i32),
     #[error("[{vendor}][API] Fetch type out of range {0}", vendor = VENDOR_IDENTIFIER)]
@@ -109,11 +114,21 @@ pub enum ODBCError {
         vendor = VENDOR_IDENTIFIER
     )]
     TimeTruncation(String),
+    #[error(
+        "[{vendor}][API] date {0} (milliseconds since the Unix epoch) is outside of the years 0001 to 9999 and was returned as NULL",
+        vendor = VENDOR_IDENTIFIER
+    )]
+    DateOutOfRange(i64),
     #[error(
         "[{vendor}][API] integral data \"{0}\" was truncated due to overflow",
         vendor = VENDOR_IDENTIFIER
     )]
     IntegralTruncation(String),
+    #[error(
+        "[{vendor}][API] interval data \"{0}\" overflowed an interval field",
+        vendor = VENDOR_IDENTIFIER
+    )]
+    IntervalFieldOverflow(String),
     #[error("[{vendor}][API] invalid datetime format", vendor = VENDOR_IDENTIFIER)]
     InvalidDatetimeFormat,
     #[error(
@@ -158,6 +173,7 @@ impl ODBCError {
             ODBCError::GeneralWarning(_) => GENERAL_WARNING,
             ODBCError::Core(c) => c.get_sql_state(),
             ODBCError::InvalidAttrValue(_) => INVALID_ATTR_VALUE,
+            ODBCError::InvalidUseOfNullPointer(_) => INVALID_USE_OF_NULL_POINTER,
             ODBCError::InvalidAttrIdentifier(_) => INVALID_ATTRIBUTE_OR_OPTION_IDENTIFIER,
             ODBCError::FetchTypeOutOfRange(_) => FETCH_TYPE_OUT_OF_RANGE,
             ODBCError::InvalidCursorState => INVALID_CURSOR_STATE,
@@ -176,7 +192,9 @@ impl ODBCError {
             ODBCError::FractionalSecondsTruncation(_) => FRACTIONAL_TRUNCATION,
             ODBCError::SecondsTruncation(_) => FRACTIONAL_TRUNCATION,
             ODBCError::TimeTruncation(_) => FRACTIONAL_TRUNCATION,
+            ODBCError::DateOutOfRange(_) => FRACTIONAL_TRUNCATION,
             ODBCError::IntegralTruncation(_) => INTEGRAL_TRUNCATION,
+            ODBCError::IntervalFieldOverflow(_) => INTERVAL_FIELD_OVERFLOW,
             ODBCError::InvalidDatetimeFormat => INVALID_DATETIME_FORMAT,
             ODBCError::InvalidCharacterValue(_) => INVALID_CHARACTER_VALUE,
             ODBCError::IndicatorVariableRequiredButNotSupplied => INDICATOR_VARIABLE_REQUIRED,
@@ -197,6 +215,7 @@ impl ODBCError {
             | ODBCError::Panic(_)
             | ODBCError::UnimplementedDataType(_)
             | ODBCError::InvalidAttrValue(_)
+            | ODBCError::InvalidUseOfNullPointer(_)
             | ODBCError::InvalidAttrIdentifier(_)
             | ODBCError::FetchTypeOutOfRange(_)
             | ODBCError::InvalidCursorState
@@ -218,7 +237,9 @@ impl ODBCError {
             | ODBCError::FractionalSecondsTruncation(_)
             | ODBCError::SecondsTruncation(_)
             | ODBCError::TimeTruncation(_)
+            | ODBCError::DateOutOfRange(_)
             | ODBCError::IntegralTruncation(_)
+            | ODBCError::IntervalFieldOverflow(_)
             | ODBCError::InvalidDatetimeFormat
             | ODBCError::InvalidSqlType(_)
             | ODBCError::UnsupportedFieldDescriptor(_)
@@ -2,8 +2,11 @@ use crate::{
     add_diag_with_function,
     api::{
         data::{i16_len, i32_len, ptr_safe_write},
+        date_range::DateOutOfRange,
         diag::{get_diag_fieldw, get_diag_recw, get_stmt_diag_field},
         errors::{ODBCError, Result},
+        json_format::JsonFormat,
+        timezone::SessionTimeZone,
         util::{connection_attribute_to_string, handle_sql_type, statement_attribute_to_string},
     },
     handles::definitions::*,
@@ -19,16 +22,27 @@ use definitions::{
     BindType, CDataType, Concurrency, ConnectionAttribute, CursorScrollable, CursorSensitivity,
     CursorType, Desc, DiagType, DriverConnectOption, EnvironmentAttribute, FetchOrientation,
     FreeStmtOption, HDbc, HDesc, HEnv, HStmt, HWnd, Handle, HandleType, Integer, Len, NoScan,
-    Pointer, RetCode, RetrieveData, RowStatus, SmallInt, SqlBool, SqlDataType, SqlReturn,
-    StatementAttribute, ULen, USmallInt, UseBookmarks, SQL_NTS,
+    OidcNoticeCallback, Pointer, RetCode, RetrieveData, RowStatus, SmallInt, SqlBool, SqlDataType,
+    SqlReturn, StatementAttribute, ULen, USmallInt, UseBookmarks, SQL_NTS,
 };
 use function_name::named;
 use log::{debug, error, info};
 use logger::Logger;
 use mongo_odbc_core::{
-    odbc_uri::ODBCUri, Error, MongoColMetadata, MongoCollections, MongoConnection, MongoDatabases,
-    MongoFields, MongoForeignKeys, MongoPrimaryKeys, MongoQuery, MongoStatement, MongoTableTypes,
-    MongoTypesInfo, TypeMode,
+    client_pool::{self, PoolOptions},
+    odbc_uri::{
+        ODBCUri, DATABASE_AS, DATE_OUT_OF_RANGE, ENABLE_ARRAY_TABLES, EXCLUDE_COLLECTIONS,
+        EXCLUDE_DATABASES, FLATTEN_DEPTH, INCLUDE_COLLECTIONS, INCLUDE_DATABASES, JSON_FORMAT,
+        POLYMORPHIC_COLUMNS, SCHEMA_DRIFT, TIMEZONE, TRANSIENT_ERROR_BACKOFF_MS,
+        TRANSIENT_ERROR_RETRIES,
+    },
+    util::{
+        catalog_argument_value, CatalogArgument, IdentifierCase, IDENTIFIER_CASE,
+        SEARCH_PATTERN_ESCAPE,
+    },
+    CatalogFilter, DatabaseAs, Error, MongoColMetadata, MongoCollections, MongoConnection,
+    MongoDatabases, MongoFields, MongoForeignKeys, MongoPrimaryKeys, MongoQuery, MongoStatement,
+    MongoTableTypes, MongoTypesInfo, PolymorphicColumns, RetryPolicy, SchemaDrift, TypeMode,
 };
 use num_traits::FromPrimitive;
 use std::ptr::null_mut;
@@ -262,6 +276,7 @@ fn sql_alloc_handle(
             unsafe {
                 *output_handle = Box::into_raw(mh).cast();
             }
+            client_pool::env_allocated();
 
             // Read the log level from the driver settings and set the logger level accordingly
             let driver_settings: DriverSettings =
@@ -421,6 +436,18 @@ pub unsafe extern "C" fn SQLBindCol(
             }
             // Bind column or rebind column with a new value
             else {
+                // Binding a column sets the type of its application row descriptor record, and
+                // resets its SQL_C_NUMERIC precision and scale to the defaults.
+                let ard = stmt.attributes.read().unwrap().app_row_desc;
+                if let Some(ard) = ard.as_ref().and_then(MongoHandle::as_descriptor) {
+                    ard.attributes.write().unwrap().records.insert(
+                        col_number,
+                        DescriptorRecord {
+                            concise_type: FromPrimitive::from_i16(target_type).unwrap(),
+                            ..Default::default()
+                        },
+                    );
+                }
                 let bound_col_info = BoundColInfo {
                     target_type,
                     target_buffer: target_value,
@@ -831,8 +858,8 @@ pub unsafe extern "C" fn SQLColumnsW(
     statement_handle: HStmt,
     catalog_name: *const WideChar,
     catalog_name_length: SmallInt,
-    _schema_name: *const WideChar,
-    _schema_name_length: SmallInt,
+    schema_name: *const WideChar,
+    schema_name_length: SmallInt,
     table_name: *const WideChar,
     table_name_length: SmallInt,
     column_name: *const WideChar,
@@ -844,29 +871,62 @@ pub unsafe extern "C" fn SQLColumnsW(
             let mongo_handle = try_mongo_handle!(statement_handle);
             let odbc_3_data_types = has_odbc_3_behavior!(mongo_handle);
             let stmt = must_be_valid!((*mongo_handle).as_statement());
-            let catalog_string =
-                input_text_to_string_w_allow_null(catalog_name, catalog_name_length.into());
-            let catalog = if catalog_name.is_null() || catalog_string.is_empty() {
+            let connection = must_be_valid!((*stmt.connection).as_connection());
+            let database_as = *connection.database_as.read().unwrap();
+            let metadata_id = stmt.attributes.read().unwrap().metadata_id == SqlBool::SQL_TRUE;
+            odbc_unwrap!(
+                check_identifier_arguments(
+                    metadata_id,
+                    database_as,
+                    catalog_name,
+                    schema_name,
+                    &[("TableName", table_name), ("ColumnName", column_name)],
+                ),
+                mongo_handle
+            );
+            // The database comes from the catalog or the schema argument depending on how
+            // databases are mapped. The other argument is ignored. The catalog is an ordinary
+            // argument while the schema accepts search patterns.
+            let (db_name, db_name_length, db_name_kind) = match database_as {
+                DatabaseAs::Catalog => {
+                    (catalog_name, catalog_name_length, CatalogArgument::Ordinary)
+                }
+                DatabaseAs::Schema => (
+                    schema_name,
+                    schema_name_length,
+                    CatalogArgument::PatternValue,
+                ),
+            };
+            let catalog_string = catalog_argument_value(
+                &input_text_to_string_w_allow_null(db_name, db_name_length.into()),
+                db_name_kind,
+                metadata_id,
+            );
+            let catalog = if db_name.is_null() || catalog_string.is_empty() {
                 None
             } else {
                 Some(catalog_string.as_str())
             };
-            // ignore schema
-            let table_string =
-                input_text_to_string_w_allow_null(table_name, table_name_length.into());
+            let table_string = catalog_argument_value(
+                &input_text_to_string_w_allow_null(table_name, table_name_length.into()),
+                CatalogArgument::PatternValue,
+                metadata_id,
+            );
             let table = if table_name.is_null() {
                 None
             } else {
                 Some(table_string.as_str())
             };
-            let column_name_string =
-                input_text_to_string_w_allow_null(column_name, column_name_length.into());
+            let column_name_string = catalog_argument_value(
+                &input_text_to_string_w_allow_null(column_name, column_name_length.into()),
+                CatalogArgument::PatternValue,
+                metadata_id,
+            );
             let column = if column_name.is_null() {
                 None
             } else {
                 Some(column_name_string.as_str())
             };
-            let connection = must_be_valid!((*stmt.connection).as_connection());
             let type_mode = *connection.type_mode.read().unwrap();
             let max_string_length = *connection.max_string_length.read().unwrap();
             let mongo_statement = Box::new(MongoFields::list_columns(
@@ -890,6 +950,7 @@ pub unsafe extern "C" fn SQLColumnsW(
                 type_mode,
                 max_string_length,
                 odbc_3_data_types,
+                database_as,
             ));
             *stmt.mongo_statement.write().unwrap() = Some(mongo_statement);
             SqlReturn::SUCCESS
@@ -1123,12 +1184,78 @@ fn sql_driver_connect(conn: &Connection, odbc_uri_string: &str) -> Result<MongoC
         }
     }
 
+    // Flattening nested documents reports simple types, whatever simple_types_only is.
+    if let Some(flatten_depth) = odbc_uri.remove(&[FLATTEN_DEPTH]) {
+        let depth = flatten_depth.parse::<u16>().map_err(|_| {
+            mongo_odbc_core::Error::InvalidUriFormat(format!(
+                "invalid flatten_depth value '{flatten_depth}', expected a non-negative integer"
+            ))
+        })?;
+        if depth > 0 {
+            *conn.type_mode.write().unwrap() = TypeMode::Flatten(depth);
+        }
+    }
+
     if let Some(enable_max_string_length) = odbc_uri.remove(&["enable_max_string_length"]) {
         if enable_max_string_length.eq("1") {
             *conn.max_string_length.write().unwrap() = Some(constants::DEFAULT_MAX_STRING_LENGTH);
         }
     }
 
+    *conn.database_as.write().unwrap() = odbc_uri
+        .remove(&[DATABASE_AS])
+        .map(|database_as| database_as.parse::<DatabaseAs>())
+        .transpose()?
+        .unwrap_or_default();
+
+    let timezone = odbc_uri
+        .remove(&[TIMEZONE])
+        .map(|timezone| timezone.parse::<SessionTimeZone>())
+        .transpose()?;
+    *conn.timezone.write().unwrap() = conn
+        .attributes
+        .read()
+        .unwrap()
+        .timezone
+        .or(timezone)
+        .unwrap_or_default();
+
+    *conn.date_out_of_range.write().unwrap() = odbc_uri
+        .remove(&[DATE_OUT_OF_RANGE])
+        .map(|date_out_of_range| date_out_of_range.parse::<DateOutOfRange>())
+        .transpose()?
+        .unwrap_or_default();
+
+    *conn.json_format.write().unwrap() = odbc_uri
+        .remove(&[JSON_FORMAT])
+        .map(|json_format| json_format.parse::<JsonFormat>())
+        .transpose()?
+        .unwrap_or_default();
+
+    let catalog_filter = CatalogFilter::new(
+        odbc_uri.remove(&[INCLUDE_DATABASES]).as_deref(),
+        odbc_uri.remove(&[EXCLUDE_DATABASES]).as_deref(),
+        odbc_uri.remove(&[INCLUDE_COLLECTIONS]).as_deref(),
+        odbc_uri.remove(&[EXCLUDE_COLLECTIONS]).as_deref(),
+    );
+    let array_tables = odbc_uri
+        .remove(&[ENABLE_ARRAY_TABLES])
+        .is_some_and(|enable_array_tables| enable_array_tables.eq("1"));
+    let polymorphic_columns = odbc_uri
+        .remove(&[POLYMORPHIC_COLUMNS])
+        .map(|polymorphic_columns| polymorphic_columns.parse::<PolymorphicColumns>())
+        .transpose()?
+        .unwrap_or_default();
+    let schema_drift = odbc_uri
+        .remove(&[SCHEMA_DRIFT])
+        .map(|schema_drift| schema_drift.parse::<SchemaDrift>())
+        .transpose()?
+        .unwrap_or_default();
+    let retry_policy = RetryPolicy::new(
+        odbc_uri.remove(&[TRANSIENT_ERROR_RETRIES]).as_deref(),
+        odbc_uri.remove(&[TRANSIENT_ERROR_BACKOFF_MS]).as_deref(),
+    )?;
+
     let mut conn_attrs = conn.attributes.write().unwrap();
     let database = if conn_attrs.current_catalog.is_some() {
         conn_attrs.current_catalog.as_deref().map(|s| s.to_string())
@@ -1139,10 +1266,30 @@ fn sql_driver_connect(conn: &Connection, odbc_uri_string: &str) -> Result<MongoC
     };
     let connection_timeout = conn_attrs.connection_timeout;
     let login_timeout = conn_attrs.login_timeout;
-    // ODBCError has an impl From mongo_odbc_core::Error, but that does not
-    // create an impl From Result<T, mongo_odbc_core::Error> to Result<T, ODBCError>
-    // hence this bizarre Ok(func?) pattern.
-    Ok(mongo_odbc_core::MongoConnection::connect(
+    let pool_options = {
+        let env = unsafe { (*conn.env).as_env() }
+            .ok_or(ODBCError::InvalidHandleType(HANDLE_MUST_BE_ENV_ERROR))?;
+        let env_attrs = env.attributes.read().unwrap();
+        PoolOptions::new(
+            env_attrs.connection_pooling,
+            env_attrs.cp_match,
+            conn.env as usize,
+        )
+    };
+    // The notices of the OIDC authentication, like the user code of the device flow, are
+    // reported as warnings, whether the connection succeeds or not. They are also delivered to
+    // the SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK while the authentication waits on the user.
+    let oidc_notices = client_options.oidc_notices.clone();
+    if let Some(callback) = conn_attrs.oidc_notice_callback {
+        // The context is only used during this call, on this thread.
+        let context = conn_attrs.oidc_notice_context.unwrap_or(null_mut()) as usize;
+        oidc_notices.set_listener(Some(Box::new(move |notice| {
+            let (notice_ptr, notice) = cstr::to_widechar_ptr(notice);
+            let length = Integer::try_from(notice.len() - 1).unwrap_or(Integer::MAX);
+            unsafe { callback(context as Pointer, notice_ptr, length) }
+        })));
+    }
+    let mongo_connection = mongo_odbc_core::MongoConnection::connect(
         client_options,
         database,
         connection_timeout,
@@ -1150,7 +1297,25 @@ fn sql_driver_connect(conn: &Connection, odbc_uri_string: &str) -> Result<MongoC
         *conn.type_mode.read().unwrap(),
         Some(runtime),
         *conn.max_string_length.read().unwrap(),
-    )?)
+        catalog_filter,
+        array_tables,
+        polymorphic_columns,
+        schema_drift,
+        retry_policy,
+        pool_options,
+    );
+    // The Client may authenticate again later, after the connection attributes changed.
+    oidc_notices.set_listener(None);
+    conn.errors.write().unwrap().extend(
+        oidc_notices
+            .take()
+            .into_iter()
+            .map(ODBCError::GeneralWarning),
+    );
+    // ODBCError has an impl From mongo_odbc_core::Error, but that does not
+    // create an impl From Result<T, mongo_odbc_core::Error> to Result<T, ODBCError>
+    // hence this bizarre Ok(func?) pattern.
+    Ok(mongo_connection?)
 }
 
 ///
@@ -1203,6 +1368,12 @@ pub unsafe extern "C" fn SQLDriverConnectW(
             let mongo_connection =
                 odbc_unwrap!(sql_driver_connect(conn, &odbc_uri_string), conn_handle);
             *conn.mongo_connection.write().unwrap() = Some(mongo_connection);
+            // Warnings, like the OIDC notices, are the only diagnostics of a successful connection.
+            let connect_return = if conn.errors.read().unwrap().is_empty() {
+                SqlReturn::SUCCESS
+            } else {
+                SqlReturn::SUCCESS_WITH_INFO
+            };
             // We know the mysql ODBC driver returns SUCCESS if the out_connection_string is NULL.
             // We can also just return SUCCESS if the buffer_len is 0. Likely, users are not
             // expecting to get back a warning when they pass an empty buffer to this, especially
@@ -1216,7 +1387,7 @@ pub unsafe extern "C" fn SQLDriverConnectW(
                         .try_into()
                         .expect("odbc_uri_string.len exceeds i16");
                 }
-                return SqlReturn::SUCCESS;
+                return connect_return;
             }
             let buffer_len = usize::try_from(buffer_length).unwrap();
             let sql_return = i16_len::set_output_wstring(
@@ -1227,8 +1398,9 @@ pub unsafe extern "C" fn SQLDriverConnectW(
             );
             if sql_return == SqlReturn::SUCCESS_WITH_INFO {
                 add_diag_info!(conn_handle, ODBCError::OutStringTruncated(buffer_len));
+                return sql_return;
             }
-            sql_return
+            connect_return
         },
         connection_handle
     );
@@ -1310,7 +1482,7 @@ pub unsafe extern "C" fn SQLExecDirectW(
             // return the statement state to its original value
             *stmt.state.write().unwrap() = StatementState::Allocated;
 
-            SqlReturn::SUCCESS
+            report_statement_warnings(statement_handle, function_name!())
         },
         statement_handle
     );
@@ -1336,12 +1508,37 @@ pub unsafe extern "C" fn SQLExecute(statement_handle: HStmt) -> SqlReturn {
             odbc_unwrap!(sql_execute(stmt, connection), mongo_handle);
             // return the statement state to its original value
             *stmt.state.write().unwrap() = StatementState::Allocated;
-            SqlReturn::SUCCESS
+            report_statement_warnings(statement_handle, function_name!())
         },
         statement_handle
     );
 }
 
+// Reports the warnings of the last prepare or execute of the statement, like the retries of
+// transient errors, and returns SUCCESS_WITH_INFO if there are any.
+unsafe fn report_statement_warnings(statement_handle: HStmt, function_name: &str) -> SqlReturn {
+    let mongo_handle = try_mongo_handle!(statement_handle);
+    let stmt = must_be_valid!(mongo_handle.as_statement());
+    let warnings = stmt
+        .mongo_statement
+        .write()
+        .unwrap()
+        .as_mut()
+        .map_or_else(Vec::new, |mongo_statement| mongo_statement.take_warnings());
+    if warnings.is_empty() {
+        return SqlReturn::SUCCESS;
+    }
+    let mongo_handle = try_mongo_handle!(statement_handle);
+    warnings.iter().for_each(|warning| {
+        add_diag_with_function!(
+            mongo_handle,
+            ODBCError::GeneralWarning(warning.to_string()),
+            function_name.to_string()
+        );
+    });
+    SqlReturn::SUCCESS_WITH_INFO
+}
+
 unsafe fn sql_execute(stmt: &Statement, connection: &Connection) -> Result<bool> {
     let stmt_id = stmt.statement_id.read().unwrap().clone();
     let mongo_statement = {
@@ -1350,13 +1547,14 @@ unsafe fn sql_execute(stmt: &Statement, connection: &Connection) -> Result<bool>
                 Ok(size) => size,
                 Err(_) => unreachable!("Err should be impossible since SQLSetStmtAttrW sets row_array_size to u32::MAX if it's outside of the u32 range"),
             };
+            let read_options = stmt.attributes.read().unwrap().read_options;
 
             stmt.mongo_statement
                 .write()
                 .unwrap()
                 .as_mut()
                 .unwrap()
-                .execute(mongo_connection, stmt_id, rowset_size)
+                .execute(mongo_connection, stmt_id, rowset_size, read_options)
                 .map_err(|e| e.into())
         } else {
             Err(ODBCError::InvalidCursorState)
@@ -1582,6 +1780,11 @@ unsafe fn sql_fetch_bound_buffers(
         let len_ind_buffer =
             (bound_col_info.length_or_indicator as ULen + (index * size_of::<isize>())) as *mut Len;
 
+        // Bound SQL_C_NUMERIC columns use the precision and scale of their ARD record.
+        let ard_record = mongo_handle_for_sql_get_data_helper
+            .as_statement()
+            .map(|stmt| stmt.get_ard_record(*col))
+            .unwrap_or_default();
         let sql_return = sql_get_data_helper(
             mongo_handle_for_sql_get_data_helper,
             *col,
@@ -1589,6 +1792,7 @@ unsafe fn sql_fetch_bound_buffers(
             target_buffer,
             bound_col_info.buffer_length,
             len_ind_buffer,
+            ard_record,
             function_name,
         );
 
@@ -1665,17 +1869,17 @@ pub unsafe extern "C" fn SQLFetchScroll(
 #[no_mangle]
 pub unsafe extern "C" fn SQLForeignKeysW(
     statement_handle: HStmt,
-    _pk_catalog_name: *const WideChar,
+    pk_catalog_name: *const WideChar,
     _pk_catalog_name_length: SmallInt,
-    _pk_schema_name: *const WideChar,
+    pk_schema_name: *const WideChar,
     _pk_schema_name_length: SmallInt,
-    _pk_table_name: *const WideChar,
+    pk_table_name: *const WideChar,
     _pk_table_name_length: SmallInt,
-    _fk_catalog_name: *const WideChar,
+    fk_catalog_name: *const WideChar,
     _fk_catalog_name_length: SmallInt,
-    _fk_schema_name: *const WideChar,
+    fk_schema_name: *const WideChar,
     _fk_schema_name_length: SmallInt,
-    _fk_table_name: *const WideChar,
+    fk_table_name: *const WideChar,
     _fk_table_name_length: SmallInt,
 ) -> SqlReturn {
     panic_safe_exec_clear_diagnostics!(
@@ -1683,6 +1887,26 @@ pub unsafe extern "C" fn SQLForeignKeysW(
         || {
             let mongo_handle = try_mongo_handle!(statement_handle);
             let stmt = must_be_valid!((*mongo_handle).as_statement());
+            let connection = must_be_valid!((*stmt.connection).as_connection());
+            let database_as = *connection.database_as.read().unwrap();
+            let metadata_id = stmt.attributes.read().unwrap().metadata_id == SqlBool::SQL_TRUE;
+            odbc_unwrap!(
+                check_identifier_arguments(
+                    metadata_id,
+                    database_as,
+                    pk_catalog_name,
+                    pk_schema_name,
+                    &[("PKTableName", pk_table_name)],
+                )
+                .and(check_identifier_arguments(
+                    metadata_id,
+                    database_as,
+                    fk_catalog_name,
+                    fk_schema_name,
+                    &[("FKTableName", fk_table_name)],
+                )),
+                mongo_handle
+            );
             let max_string_length = stmt.get_max_string_length();
             let mongo_statement = MongoForeignKeys::empty(max_string_length);
             *stmt.mongo_statement.write().unwrap() = Some(Box::new(mongo_statement));
@@ -1729,6 +1953,8 @@ fn sql_free_handle(handle_type: HandleType, handle: *mut MongoHandle) -> Result<
                     .as_env()
                     .ok_or(ODBCError::InvalidHandleType(HANDLE_MUST_BE_ENV_ERROR))?
             };
+            // The Clients pooled for this environment are no longer reachable.
+            client_pool::env_freed(handle as usize);
         }
         HandleType::SQL_HANDLE_DBC => {
             let conn = unsafe {
@@ -1884,7 +2110,12 @@ unsafe fn sql_get_connect_attrw_helper(
 
         match attribute {
             ConnectionAttribute::SQL_ATTR_CURRENT_CATALOG => {
-                let current_catalog = attributes.current_catalog.as_deref();
+                // There is no current catalog when databases are mapped to schemas. The
+                // database is still used as the default database for queries.
+                let current_catalog = match *conn.database_as.read().unwrap() {
+                    DatabaseAs::Catalog => attributes.current_catalog.as_deref(),
+                    DatabaseAs::Schema => None,
+                };
                 match current_catalog {
                     None => SqlReturn::NO_DATA,
                     Some(cc) => i32_len::set_output_wstring_as_bytes(
@@ -1899,11 +2130,12 @@ unsafe fn sql_get_connect_attrw_helper(
                 let login_timeout = attributes.login_timeout.unwrap_or(0);
                 i32_len::set_output_fixed_data(&login_timeout, value_ptr, string_length_ptr)
             }
-            // according to the spec, SQL_ATTR_CONNECTION_DEAD just returns the latest status of the connection, not the current status
+            // according to the spec, SQL_ATTR_CONNECTION_DEAD returns the latest status of the connection, which is
+            // the outcome of the last interaction with the server, or of a ping when the connection has been idle.
             ConnectionAttribute::SQL_ATTR_CONNECTION_DEAD => {
                 let connection_dead = match *conn.mongo_connection.read().unwrap() {
-                    Some(_) => SqlBool::SQL_FALSE,
-                    None => SqlBool::SQL_TRUE,
+                    Some(ref mongo_connection) if !mongo_connection.is_dead() => SqlBool::SQL_FALSE,
+                    _ => SqlBool::SQL_TRUE,
                 };
                 i32_len::set_output_fixed_data(&connection_dead, value_ptr, string_length_ptr)
             }
@@ -1914,6 +2146,22 @@ unsafe fn sql_get_connect_attrw_helper(
             ConnectionAttribute::SQL_ATTR_ACCESS_MODE => {
                 i32_len::set_output_fixed_data(&AccessMode::ReadOnly, value_ptr, string_length_ptr)
             }
+            ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE => i32_len::set_output_wstring_as_bytes(
+                &conn.timezone.read().unwrap().to_string(),
+                value_ptr,
+                buffer_length as usize,
+                string_length_ptr,
+            ),
+            ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK => {
+                let callback = attributes
+                    .oidc_notice_callback
+                    .map_or(null_mut(), |callback| callback as Pointer);
+                i32_len::set_output_fixed_data(&callback, value_ptr, string_length_ptr)
+            }
+            ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT => {
+                let context = attributes.oidc_notice_context.unwrap_or(null_mut());
+                i32_len::set_output_fixed_data(&context, value_ptr, string_length_ptr)
+            }
             _ => {
                 err = Some(ODBCError::UnsupportedConnectionAttribute(
                     connection_attribute_to_string(attribute),
@@ -1981,6 +2229,21 @@ pub unsafe extern "C" fn SQLGetData(
             }
 
             match FromPrimitive::from_i16(target_type) {
+                // SQL_ARD_TYPE reads the column as the type, precision and scale of its ARD
+                // record. Other types use the default precision and scale of SQL_C_NUMERIC.
+                Some(CDataType::SQL_ARD_TYPE) => {
+                    let ard_record = stmt.get_ard_record(col_or_param_num);
+                    sql_get_data_helper(
+                        mongo_handle,
+                        col_or_param_num,
+                        ard_record.concise_type,
+                        target_value_ptr,
+                        buffer_length,
+                        str_len_or_ind_ptr,
+                        ard_record,
+                        "SQLGetData",
+                    )
+                }
                 Some(valid_type) => sql_get_data_helper(
                     mongo_handle,
                     col_or_param_num,
@@ -1988,6 +2251,7 @@ pub unsafe extern "C" fn SQLGetData(
                     target_value_ptr,
                     buffer_length,
                     str_len_or_ind_ptr,
+                    DescriptorRecord::default(),
                     "SQLGetData",
                 ),
                 None => {
@@ -2000,6 +2264,7 @@ pub unsafe extern "C" fn SQLGetData(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 unsafe fn sql_get_data_helper(
     mongo_handle: &mut MongoHandle,
     col_or_param_num: USmallInt,
@@ -2007,6 +2272,7 @@ unsafe fn sql_get_data_helper(
     target_value_ptr: Pointer,
     buffer_length: Len,
     str_len_or_ind_ptr: *mut Len,
+    ard_record: DescriptorRecord,
     function_name: &str,
 ) -> SqlReturn {
     let mut error = None;
@@ -2064,6 +2330,7 @@ unsafe fn sql_get_data_helper(
         buffer_length,
         str_len_or_ind_ptr,
         ret,
+        ard_record,
         function_name,
     )
 }
@@ -2079,14 +2346,88 @@ unsafe fn sql_get_data_helper(
 #[no_mangle]
 #[named]
 pub unsafe extern "C" fn SQLGetDescFieldW(
-    _descriptor_handle: HDesc,
-    _record_number: SmallInt,
-    _field_identifier: SmallInt,
-    _value_ptr: Pointer,
+    descriptor_handle: HDesc,
+    record_number: SmallInt,
+    field_identifier: SmallInt,
+    value_ptr: Pointer,
     _buffer_length: Integer,
-    _string_length_ptr: *mut Integer,
+    string_length_ptr: *mut Integer,
 ) -> SqlReturn {
-    unsupported_function!(_descriptor_handle)
+    panic_safe_exec_clear_diagnostics!(
+        debug,
+        || {
+            let mongo_handle = try_mongo_handle!(descriptor_handle);
+            let desc = must_be_desc!(mongo_handle);
+            let field = match desc_record_field(record_number, field_identifier) {
+                Ok(field) => field,
+                Err(e) => {
+                    add_diag_info!(mongo_handle, e);
+                    return SqlReturn::ERROR;
+                }
+            };
+            let record = desc
+                .attributes
+                .read()
+                .unwrap()
+                .records
+                .get(&field.0)
+                .copied()
+                .unwrap_or_default();
+            match field.1 {
+                Desc::SQL_DESC_TYPE => i32_len::set_output_fixed_data(
+                    &verbose_c_data_type(record.concise_type),
+                    value_ptr,
+                    string_length_ptr,
+                ),
+                Desc::SQL_DESC_CONCISE_TYPE => i32_len::set_output_fixed_data(
+                    &(record.concise_type as SmallInt),
+                    value_ptr,
+                    string_length_ptr,
+                ),
+                Desc::SQL_DESC_PRECISION => {
+                    i32_len::set_output_fixed_data(&record.precision, value_ptr, string_length_ptr)
+                }
+                _ => i32_len::set_output_fixed_data(&record.scale, value_ptr, string_length_ptr),
+            }
+        },
+        descriptor_handle
+    );
+}
+
+// Returns the SQL_DESC_TYPE of a concise C type, which is SQL_DATETIME (9) for the datetime types
+// and SQL_INTERVAL (10) for the interval types.
+fn verbose_c_data_type(concise_type: CDataType) -> SmallInt {
+    match concise_type as SmallInt {
+        91..=95 => 9,
+        101..=113 => 10,
+        concise_type => concise_type,
+    }
+}
+
+// Validates the record number and field identifier of SQLGetDescField and SQLSetDescField. Only
+// SQL_DESC_TYPE, SQL_DESC_CONCISE_TYPE, SQL_DESC_PRECISION and SQL_DESC_SCALE of the descriptor
+// records are supported.
+fn desc_record_field(
+    record_number: SmallInt,
+    field_identifier: SmallInt,
+) -> Result<(USmallInt, Desc)> {
+    let field = match FromPrimitive::from_i16(field_identifier) {
+        Some(
+            field @ (Desc::SQL_DESC_TYPE
+            | Desc::SQL_DESC_CONCISE_TYPE
+            | Desc::SQL_DESC_PRECISION
+            | Desc::SQL_DESC_SCALE),
+        ) => field,
+        _ => {
+            return Err(ODBCError::UnsupportedFieldDescriptor(
+                field_identifier as u16,
+            ))
+        }
+    };
+    match USmallInt::try_from(record_number) {
+        Ok(record_number) if record_number > 0 => Ok((record_number, field)),
+        _ => Err(ODBCError::InvalidDescriptorIndex(record_number as u16)),
+    }
 }
 
 ///
@@ -2399,7 +2740,7 @@ macro_rules! sql_get_info_helper {
                     )
                 }
                 InfoType::SQL_SEARCH_PATTERN_ESCAPE => i16_len::set_output_wstring_as_bytes(
-                    r"\",
+                    &SEARCH_PATTERN_ESCAPE.to_string(),
                     info_value_ptr,
                     buffer_length as usize,
                     string_length_ptr,
@@ -2441,6 +2782,27 @@ macro_rules! sql_get_info_helper {
                     // the result is NULL. The return value indicates that.
                     i16_len::set_output_fixed_data(&SQL_CB_NULL, info_value_ptr, string_length_ptr)
                 }
+                InfoType::SQL_IDENTIFIER_CASE => {
+                    let identifier_case = match IDENTIFIER_CASE {
+                        IdentifierCase::Upper => SQL_IC_UPPER,
+                        IdentifierCase::Lower => SQL_IC_LOWER,
+                        IdentifierCase::Sensitive => SQL_IC_SENSITIVE,
+                        IdentifierCase::Mixed => SQL_IC_MIXED,
+                    };
+                    i16_len::set_output_fixed_data(
+                        &identifier_case,
+                        info_value_ptr,
+                        string_length_ptr,
+                    )
+                }
+                InfoType::SQL_QUOTED_IDENTIFIER_CASE => {
+                    // Quoted identifiers are always taken literally.
+                    i16_len::set_output_fixed_data(
+                        &SQL_IC_SENSITIVE,
+                        info_value_ptr,
+                        string_length_ptr,
+                    )
+                }
                 InfoType::SQL_IDENTIFIER_QUOTE_CHAR => {
                     // MongoSQL supports ` and " as identifier delimiters. The "
                     // character is the SQL-92 standard, but we instead return `
@@ -2461,10 +2823,15 @@ macro_rules! sql_get_info_helper {
                     // "collection" (which is equivalent to "table"). A "catalog"
                     // contains many "schemas" and a "schema" contains many tables.
                     // Therefore, a "schema" may map to MongoSQL's "database".
-                    // However, we choose to use "catalog" to represent MongoSQL
-                    // databases, and we omit support for "schema".
+                    // By default, we use "catalog" to represent MongoSQL databases,
+                    // and we omit support for "schema". The database_as option
+                    // swaps this mapping.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
                     i16_len::set_output_wstring_as_bytes(
-                        "",
+                        match *conn.database_as.read().unwrap() {
+                            DatabaseAs::Catalog => "",
+                            DatabaseAs::Schema => "database",
+                        },
                         info_value_ptr,
                         buffer_length as usize,
                         string_length_ptr,
@@ -2482,9 +2849,14 @@ macro_rules! sql_get_info_helper {
                     )
                 }
                 InfoType::SQL_CATALOG_NAME_SEPARATOR => {
-                    // The name separator used by MongoSQL is '.'.
+                    // The name separator used by MongoSQL is '.'. There is no separator
+                    // when catalogs are not supported.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
                     i16_len::set_output_wstring_as_bytes(
-                        ".",
+                        match *conn.database_as.read().unwrap() {
+                            DatabaseAs::Catalog => ".",
+                            DatabaseAs::Schema => "",
+                        },
                         info_value_ptr,
                         buffer_length as usize,
                         string_length_ptr,
@@ -2492,8 +2864,12 @@ macro_rules! sql_get_info_helper {
                 }
                 InfoType::SQL_CATALOG_TERM => {
                     // MongoSQL uses the term "database".
+                    let conn = must_be_valid!((*conn_handle).as_connection());
                     i16_len::set_output_wstring_as_bytes(
-                        "database",
+                        match *conn.database_as.read().unwrap() {
+                            DatabaseAs::Catalog => "database",
+                            DatabaseAs::Schema => "",
+                        },
                         info_value_ptr,
                         buffer_length as usize,
                         string_length_ptr,
@@ -2604,6 +2980,14 @@ macro_rules! sql_get_info_helper {
                     // SQL_CONVERT_FUNCTIONS alerts the client that we expect CAST and not CONVERT.
                     i16_len::set_output_fixed_data(&MONGO_CAST_SUPPORT, info_value_ptr, string_length_ptr)
                 }
+                InfoType::SQL_CONVERT_INTERVAL_YEAR_MONTH
+                | InfoType::SQL_CONVERT_INTERVAL_DAY_TIME
+                | InfoType::SQL_DATETIME_LITERALS => {
+                    // MongoSQL has no interval data type, so there are no interval values to
+                    // CAST and no interval literals. Interval C types can still be used to
+                    // retrieve numeric and string data.
+                    i16_len::set_output_fixed_data(&MAX_COLUMNS_U32_ZERO, info_value_ptr, string_length_ptr)
+                }
                 InfoType::SQL_GETDATA_EXTENSIONS => {
                     // GetData can be called on any column in any order.
                     const GETDATA_EXTENSIONS: u32 = SQL_GD_ANY_COLUMN | SQL_GD_ANY_ORDER;
@@ -2646,16 +3030,28 @@ macro_rules! sql_get_info_helper {
                     // ODBC versions. They use the same numeric value.
                     //
                     // As noted for InfoType::OwnerTerm, the MongoSQL ODBC driver
-                    // does not support "schema" in the data hierarchy.
-                    i16_len::set_output_fixed_data(&MAX_COLUMNS_U32_ZERO, info_value_ptr, string_length_ptr)
+                    // does not support "schema" in the data hierarchy unless databases
+                    // are mapped to schemas.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
+                    let schema_usage = match *conn.database_as.read().unwrap() {
+                        DatabaseAs::Catalog => MAX_COLUMNS_U32_ZERO,
+                        DatabaseAs::Schema => SQL_SU_DML_STATEMENTS,
+                    };
+                    i16_len::set_output_fixed_data(&schema_usage, info_value_ptr, string_length_ptr)
                 }
                 InfoType::SQL_CATALOG_USAGE => {
                     // This return value indicates support for SELECT as well as
                     // INSERT, UPDATE, and DELETE. In conjunction with the following
                     // InfoType, SQL_DATA_SOURCE_READ_ONLY, this return value is
-                    // valid.
+                    // valid. Catalogs are not supported when databases are mapped to
+                    // schemas.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
+                    let catalog_usage = match *conn.database_as.read().unwrap() {
+                        DatabaseAs::Catalog => SQL_CU_DML_STATEMENTS,
+                        DatabaseAs::Schema => MAX_COLUMNS_U32_ZERO,
+                    };
                     i16_len::set_output_fixed_data(
-                        &SQL_CU_DML_STATEMENTS,
+                        &catalog_usage,
                         info_value_ptr,
                         string_length_ptr,
                     )
@@ -2727,8 +3123,18 @@ macro_rules! sql_get_info_helper {
                 }
                 InfoType::SQL_CATALOG_LOCATION => {
                     // MongoSQL puts the catalog (database) at the start of a qualified
-                    // table name. As in, db.table.
-                    i16_len::set_output_fixed_data(&SQL_CL_START, info_value_ptr, string_length_ptr)
+                    // table name. As in, db.table. 0 is returned when catalogs are not
+                    // supported.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
+                    let catalog_location = match *conn.database_as.read().unwrap() {
+                        DatabaseAs::Catalog => SQL_CL_START,
+                        DatabaseAs::Schema => MAX_COLUMNS_U16_ZERO,
+                    };
+                    i16_len::set_output_fixed_data(
+                        &catalog_location,
+                        info_value_ptr,
+                        string_length_ptr,
+                    )
                 }
                 InfoType::SQL_SQL_CONFORMANCE => {
                     // MongoSQL is SQL-92 Entry level compliant.
@@ -2777,9 +3183,14 @@ macro_rules! sql_get_info_helper {
                     )
                 }
                 InfoType::SQL_CATALOG_NAME => {
-                    // MongoSQL does support catalog (database) names.
+                    // MongoSQL does support catalog (database) names, unless databases
+                    // are mapped to schemas.
+                    let conn = must_be_valid!((*conn_handle).as_connection());
                     i16_len::set_output_wstring_as_bytes(
-                        COLUMN_ALIAS_INFO_Y,
+                        match *conn.database_as.read().unwrap() {
+                            DatabaseAs::Catalog => COLUMN_ALIAS_INFO_Y,
+                            DatabaseAs::Schema => COLUMN_ALIAS_INFO_N,
+                        },
                         info_value_ptr,
                         buffer_length as usize,
                         string_length_ptr,
@@ -3155,9 +3566,30 @@ unsafe fn sql_get_stmt_attrw_helper(
                 SqlReturn::SUCCESS
             }
             StatementAttribute::SQL_ATTR_METADATA_ID => {
-                // False means that we treat arguments to catalog functions as case sensitive. This
-                // is a _requirement_ for mongodb where FOO and foo are distinct database names.
-                *value_ptr.cast::<ULen>() = SqlBool::SQL_FALSE as ULen;
+                *value_ptr.cast::<ULen>() = stmt.attributes.read().unwrap().metadata_id as ULen;
+                SqlReturn::SUCCESS
+            }
+            StatementAttribute::SQL_ATTR_MONGODB_SCHEMA_DRIFT_COUNT => {
+                *value_ptr.cast::<ULen>() = stmt
+                    .mongo_statement
+                    .read()
+                    .unwrap()
+                    .as_ref()
+                    .map_or(0, |mongo_stmt| mongo_stmt.schema_drift_count() as ULen);
+                SqlReturn::SUCCESS
+            }
+            StatementAttribute::SQL_ATTR_MONGODB_READ_PREFERENCE => {
+                *value_ptr.cast::<ULen>() =
+                    stmt.attributes.read().unwrap().read_options.read_preference as ULen;
+                SqlReturn::SUCCESS
+            }
+            StatementAttribute::SQL_ATTR_MONGODB_READ_CONCERN_LEVEL => {
+                *value_ptr.cast::<ULen>() =
+                    stmt.attributes.read().unwrap().read_options.read_concern_level as ULen;
+                SqlReturn::SUCCESS
+            }
+            StatementAttribute::SQL_ATTR_MONGODB_JSON_FORMAT => {
+                *value_ptr.cast::<ULen>() = stmt.attributes.read().unwrap().json_format as ULen;
                 SqlReturn::SUCCESS
             }
             // leave SQL_GET_BOOKMARK as unsupported since it is for ODBC < 3.0 drivers
@@ -3349,7 +3781,7 @@ pub unsafe extern "C" fn SQLPrepareW(
             );
 
             *stmt.mongo_statement.write().unwrap() = Some(Box::new(mongo_statement));
-            SqlReturn::SUCCESS
+            report_statement_warnings(statement_handle, function_name!())
         },
         statement_handle
     );
@@ -3369,6 +3801,9 @@ fn sql_prepare(
         )
     };
     query = query.strip_suffix(';').unwrap_or(&query).to_string();
+    // BSON dates are stored in UTC, so the timestamp literals are converted from the session
+    // time zone.
+    query = connection.timezone.read().unwrap().literals_to_utc(&query);
     let mongo_statement = {
         let type_mode = *connection.type_mode.read().unwrap();
         let max_string_length = *connection.max_string_length.read().unwrap();
@@ -3404,11 +3839,11 @@ fn sql_prepare(
 #[no_mangle]
 pub unsafe extern "C" fn SQLPrimaryKeysW(
     statement_handle: HStmt,
-    _catalog_name: *const WideChar,
+    catalog_name: *const WideChar,
     _catalog_name_length: SmallInt,
-    _schema_name: *const WideChar,
+    schema_name: *const WideChar,
     _schema_name_length: SmallInt,
-    _table_name: *const WideChar,
+    table_name: *const WideChar,
     _table_name_length: SmallInt,
 ) -> SqlReturn {
     panic_safe_exec_clear_diagnostics!(
@@ -3416,6 +3851,17 @@ pub unsafe extern "C" fn SQLPrimaryKeysW(
         || {
             let mongo_handle = try_mongo_handle!(statement_handle);
             let stmt = must_be_valid!((*mongo_handle).as_statement());
+            let connection = must_be_valid!((*stmt.connection).as_connection());
+            odbc_unwrap!(
+                check_identifier_arguments(
+                    stmt.attributes.read().unwrap().metadata_id == SqlBool::SQL_TRUE,
+                    *connection.database_as.read().unwrap(),
+                    catalog_name,
+                    schema_name,
+                    &[("TableName", table_name)],
+                ),
+                mongo_handle
+            );
             let max_string_length = stmt.get_max_string_length();
             let mongo_statement = MongoPrimaryKeys::empty(max_string_length);
             *stmt.mongo_statement.write().unwrap() = Some(Box::new(mongo_statement));
@@ -3565,6 +4011,16 @@ unsafe fn set_connect_attrw_helper(
                 SqlReturn::SUCCESS
             }
             ConnectionAttribute::SQL_ATTR_APP_WCHAR_TYPE => SqlReturn::SUCCESS,
+            // Catalogs are not supported once connected with databases mapped to schemas. Before
+            // connecting, the value is still accepted as the default database.
+            ConnectionAttribute::SQL_ATTR_CURRENT_CATALOG
+                if *conn.database_as.read().unwrap() == DatabaseAs::Schema =>
+            {
+                err = Some(ODBCError::Unimplemented(
+                    "SQL_ATTR_CURRENT_CATALOG when databases are mapped to schemas",
+                ));
+                SqlReturn::ERROR
+            }
             ConnectionAttribute::SQL_ATTR_CURRENT_CATALOG => {
                 let current_db = input_text_to_string_w(
                     value_ptr as *const _,
@@ -3575,6 +4031,36 @@ unsafe fn set_connect_attrw_helper(
                 conn.attributes.write().unwrap().current_catalog = Some(current_db);
                 SqlReturn::SUCCESS
             }
+            ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE => {
+                let timezone = input_text_to_string_w(
+                    value_ptr as *const _,
+                    SQL_NTS
+                        .try_into()
+                        .expect("i32 exceeded max isize on this platform"),
+                );
+                match timezone.parse::<SessionTimeZone>() {
+                    Ok(timezone) => {
+                        conn.attributes.write().unwrap().timezone = Some(timezone);
+                        *conn.timezone.write().unwrap() = timezone;
+                        SqlReturn::SUCCESS
+                    }
+                    Err(_) => {
+                        err = Some(ODBCError::InvalidAttrValue("SQL_ATTR_MONGODB_TIMEZONE"));
+                        SqlReturn::ERROR
+                    }
+                }
+            }
+            // A null pointer removes the callback.
+            ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK => {
+                conn.attributes.write().unwrap().oidc_notice_callback = (!value_ptr.is_null())
+                    .then(|| std::mem::transmute::<Pointer, OidcNoticeCallback>(value_ptr));
+                SqlReturn::SUCCESS
+            }
+            ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT => {
+                conn.attributes.write().unwrap().oidc_notice_context =
+                    (!value_ptr.is_null()).then_some(value_ptr);
+                SqlReturn::SUCCESS
+            }
             // we use 0 (no timeout throughout the driver); only allow the user to set this value if they are setting to 0
             ConnectionAttribute::SQL_ATTR_CONNECTION_TIMEOUT => match (value_ptr as u32) == 0 {
                 true => SqlReturn::SUCCESS,
@@ -3644,13 +4130,106 @@ pub unsafe extern "C" fn SQLSetCursorNameW(
 #[no_mangle]
 #[named]
 pub unsafe extern "C" fn SQLSetDescFieldW(
-    _desc_handle: HDesc,
-    _rec_number: SmallInt,
-    _field_identifier: SmallInt,
-    _value_ptr: Pointer,
+    desc_handle: HDesc,
+    rec_number: SmallInt,
+    field_identifier: SmallInt,
+    value_ptr: Pointer,
     _buffer_length: Integer,
 ) -> SqlReturn {
-    unsupported_function!(_desc_handle)
+    panic_safe_exec_clear_diagnostics!(
+        debug,
+        || {
+            let mongo_handle = try_mongo_handle!(desc_handle);
+            let desc = must_be_desc!(mongo_handle);
+            let (rec_number, field) = match desc_record_field(rec_number, field_identifier) {
+                Ok(field) => field,
+                Err(e) => {
+                    add_diag_info!(mongo_handle, e);
+                    return SqlReturn::ERROR;
+                }
+            };
+            match field {
+                Desc::SQL_DESC_TYPE | Desc::SQL_DESC_CONCISE_TYPE => {
+                    // SmallInt fields are passed by value in the pointer argument. The datetime
+                    // and interval types must be set by their concise type, since the driver
+                    // does not support SQL_DESC_DATETIME_INTERVAL_CODE.
+                    let concise_type = match SmallInt::try_from(value_ptr as isize)
+                        .ok()
+                        .and_then(<CDataType as FromPrimitive>::from_i16)
+                    {
+                        None | Some(CDataType::SQL_ARD_TYPE | CDataType::SQL_APD_TYPE) => None,
+                        Some(CDataType::SQL_C_DATE | CDataType::SQL_C_TIME)
+                            if field == Desc::SQL_DESC_TYPE =>
+                        {
+                            None
+                        }
+                        concise_type => concise_type,
+                    };
+                    let Some(concise_type) = concise_type else {
+                        add_diag_info!(
+                            mongo_handle,
+                            ODBCError::InvalidAttrValue(if field == Desc::SQL_DESC_TYPE {
+                                "SQL_DESC_TYPE"
+                            } else {
+                                "SQL_DESC_CONCISE_TYPE"
+                            })
+                        );
+                        return SqlReturn::ERROR;
+                    };
+                    // Setting the type resets the precision and scale to their defaults, and
+                    // changes the target type of the bound column.
+                    desc.attributes.write().unwrap().records.insert(
+                        rec_number,
+                        DescriptorRecord {
+                            concise_type,
+                            ..Default::default()
+                        },
+                    );
+                    if let Some(stmt) = desc.ard_statement(desc_handle.cast()) {
+                        let mut bound_cols = stmt.bound_cols.write().unwrap();
+                        if let Some(bound_col_info) = bound_cols
+                            .as_mut()
+                            .and_then(|bound_cols| bound_cols.get_mut(&rec_number))
+                        {
+                            bound_col_info.target_type = concise_type as SmallInt;
+                        }
+                    }
+                }
+                _ => {
+                    // SmallInt fields are passed by value in the pointer argument.
+                    let value = match SmallInt::try_from(value_ptr as isize) {
+                        Ok(value @ 1..=NUMERIC_MAX_PRECISION)
+                            if field == Desc::SQL_DESC_PRECISION =>
+                        {
+                            value
+                        }
+                        Ok(value @ 0..=NUMERIC_MAX_PRECISION) if field == Desc::SQL_DESC_SCALE => {
+                            value
+                        }
+                        _ => {
+                            add_diag_info!(
+                                mongo_handle,
+                                ODBCError::InvalidAttrValue(if field == Desc::SQL_DESC_PRECISION {
+                                    "SQL_DESC_PRECISION"
+                                } else {
+                                    "SQL_DESC_SCALE"
+                                })
+                            );
+                            return SqlReturn::ERROR;
+                        }
+                    };
+                    let mut attributes = desc.attributes.write().unwrap();
+                    let record = attributes.records.entry(rec_number).or_default();
+                    match field {
+                        Desc::SQL_DESC_PRECISION => record.precision = value,
+                        _ => record.scale = value,
+                    }
+                }
+            }
+            SqlReturn::SUCCESS
+        },
+        desc_handle
+    );
 }
 
 ///
@@ -3764,25 +4343,48 @@ unsafe fn sql_set_env_attrw_helper(
         }
         EnvironmentAttribute::SQL_ATTR_CONNECTION_POOLING => {
             match FromPrimitive::from_i32(value_ptr as i32) {
-                Some(AttrConnectionPooling::SQL_CP_OFF) => SqlReturn::SUCCESS,
-                _ => {
+                Some(
+                    pooling @ (AttrConnectionPooling::SQL_CP_OFF
+                    | AttrConnectionPooling::SQL_CP_ONE_PER_DRIVER
+                    | AttrConnectionPooling::SQL_CP_ONE_PER_HENV),
+                ) => {
+                    env.attributes.write().unwrap().connection_pooling = pooling;
+                    SqlReturn::SUCCESS
+                }
+                // The driver has no connection-pool awareness, so one pool per environment is
+                // used instead.
+                Some(AttrConnectionPooling::SQL_CP_DRIVER_AWARE) => {
+                    env.attributes.write().unwrap().connection_pooling =
+                        AttrConnectionPooling::SQL_CP_ONE_PER_HENV;
                     env_handle.add_diag_info(ODBCError::OptionValueChanged(
                         "SQL_ATTR_CONNECTION_POOLING",
-                        "SQL_CP_OFF",
+                        "SQL_CP_ONE_PER_HENV",
                     ));
                     SqlReturn::SUCCESS_WITH_INFO
                 }
+                None => {
+                    add_diag_with_function!(
+                        env_handle,
+                        ODBCError::InvalidAttrValue("SQL_ATTR_CONNECTION_POOLING"),
+                        "SQLSetEnvAttrW"
+                    );
+                    SqlReturn::ERROR
+                }
             }
         }
         EnvironmentAttribute::SQL_ATTR_CP_MATCH => {
             match FromPrimitive::from_i32(value_ptr as i32) {
-                Some(AttrCpMatch::SQL_CP_STRICT_MATCH) => SqlReturn::SUCCESS,
-                _ => {
-                    env_handle.add_diag_info(ODBCError::OptionValueChanged(
-                        "SQL_ATTR_CP_MATCH",
-                        "SQL_CP_STRICT_MATCH",
-                    ));
-                    SqlReturn::SUCCESS_WITH_INFO
+                Some(cp_match) => {
+                    env.attributes.write().unwrap().cp_match = cp_match;
+                    SqlReturn::SUCCESS
+                }
+                None => {
+                    add_diag_with_function!(
+                        env_handle,
+                        ODBCError::InvalidAttrValue("SQL_ATTR_CP_MATCH"),
+                        "SQLSetEnvAttrW"
+                    );
+                    SqlReturn::ERROR
                 }
             }
         }
@@ -4041,7 +4643,66 @@ unsafe fn sql_set_stmt_attrw_helper(
             SqlReturn::ERROR
         }
         StatementAttribute::SQL_ATTR_METADATA_ID => {
-            todo!()
+            match FromPrimitive::from_i32(value_ptr as i32) {
+                Some(metadata_id) => {
+                    stmt.attributes.write().unwrap().metadata_id = metadata_id;
+                    SqlReturn::SUCCESS
+                }
+                None => {
+                    stmt_handle
+                        .add_diag_info(ODBCError::InvalidAttrValue("SQL_ATTR_METADATA_ID"));
+                    SqlReturn::ERROR
+                }
+            }
+        }
+        // The schema drift count is read-only
+        StatementAttribute::SQL_ATTR_MONGODB_SCHEMA_DRIFT_COUNT => {
+            add_diag_with_function!(stmt_handle, ODBCError::InvalidAttrIdentifier(attribute as i32), "SQLSetStmtAttrW");
+            SqlReturn::ERROR
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_READ_PREFERENCE => {
+            match FromPrimitive::from_usize(value_ptr as usize) {
+                Some(read_preference) => {
+                    stmt.attributes.write().unwrap().read_options.read_preference =
+                        read_preference;
+                    SqlReturn::SUCCESS
+                }
+                None => {
+                    stmt_handle.add_diag_info(ODBCError::InvalidAttrValue(
+                        "SQL_ATTR_MONGODB_READ_PREFERENCE",
+                    ));
+                    SqlReturn::ERROR
+                }
+            }
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_READ_CONCERN_LEVEL => {
+            match FromPrimitive::from_usize(value_ptr as usize) {
+                Some(read_concern_level) => {
+                    stmt.attributes.write().unwrap().read_options.read_concern_level =
+                        read_concern_level;
+                    SqlReturn::SUCCESS
+                }
+                None => {
+                    stmt_handle.add_diag_info(ODBCError::InvalidAttrValue(
+                        "SQL_ATTR_MONGODB_READ_CONCERN_LEVEL",
+                    ));
+                    SqlReturn::ERROR
+                }
+            }
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_JSON_FORMAT => {
+            match FromPrimitive::from_usize(value_ptr as usize) {
+                Some(json_format) => {
+                    stmt.attributes.write().unwrap().json_format = json_format;
+                    SqlReturn::SUCCESS
+                }
+                None => {
+                    stmt_handle.add_diag_info(ODBCError::InvalidAttrValue(
+                        "SQL_ATTR_MONGODB_JSON_FORMAT",
+                    ));
+                    SqlReturn::ERROR
+                }
+            }
         }
         // leave SQL_GET_BOOKMARK as unsupported since it is for ODBC < 3.0 drivers
         StatementAttribute::SQL_GET_BOOKMARK
@@ -4139,6 +4800,32 @@ pub unsafe extern "C" fn SQLTablePrivilegesW(
     unimpl!(statement_handle);
 }
 
+// With SQL_ATTR_METADATA_ID set to SQL_TRUE, the arguments of catalog functions are identifiers
+// and cannot be null pointers. Only the catalog or the schema argument is checked, depending on
+// which one the databases are reported as.
+fn check_identifier_arguments(
+    metadata_id: bool,
+    database_as: DatabaseAs,
+    catalog_name: *const WideChar,
+    schema_name: *const WideChar,
+    other_arguments: &[(&'static str, *const WideChar)],
+) -> Result<()> {
+    if !metadata_id {
+        return Ok(());
+    }
+    let database_argument = match database_as {
+        DatabaseAs::Catalog => ("CatalogName", catalog_name),
+        DatabaseAs::Schema => ("SchemaName", schema_name),
+    };
+    match std::iter::once(&database_argument)
+        .chain(other_arguments)
+        .find(|(_, argument)| argument.is_null())
+    {
+        Some((name, _)) => Err(ODBCError::InvalidUseOfNullPointer(name)),
+        None => Ok(()),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn sql_tables(
     mongo_connection: &MongoConnection,
@@ -4149,23 +4836,42 @@ fn sql_tables(
     table_t: &str,
     odbc_3_behavior: bool,
     max_string_length: Option<u16>,
+    database_as: DatabaseAs,
+    metadata_id: bool,
 ) -> Result<Box<dyn MongoStatement>> {
-    match (catalog, schema, table, table_t) {
-        (SQL_ALL_CATALOGS, "", "", "") => Ok(Box::new(MongoDatabases::list_all_catalogs(
-            mongo_connection,
-            Some(query_timeout),
-        ))),
-        ("", SQL_ALL_SCHEMAS, "", "") => {
+    match (catalog, schema, table, table_t, database_as) {
+        (SQL_ALL_CATALOGS, "", "", "", DatabaseAs::Catalog) => Ok(Box::new(
+            MongoDatabases::list_all_catalogs(mongo_connection, Some(query_timeout)),
+        )),
+        // There are no catalogs when databases are reported as schemas.
+        (SQL_ALL_CATALOGS, "", "", "", DatabaseAs::Schema) => Ok(Box::new(MongoDatabases::empty())),
+        ("", SQL_ALL_SCHEMAS, "", "", DatabaseAs::Catalog) => {
             Ok(Box::new(MongoCollections::all_schemas(max_string_length)))
         }
-        ("", "", "", SQL_ALL_TABLE_TYPES) => Ok(Box::new(MongoTableTypes::all_table_types())),
+        ("", SQL_ALL_SCHEMAS, "", "", DatabaseAs::Schema) => Ok(Box::new(
+            MongoDatabases::list_all_schemas(mongo_connection, Some(query_timeout)),
+        )),
+        ("", "", "", SQL_ALL_TABLE_TYPES, _) => Ok(Box::new(MongoTableTypes::all_table_types())),
         _ => Ok(Box::new(MongoCollections::list_tables(
             mongo_connection,
             Some(query_timeout),
-            catalog,
-            table,
+            // ODBC 2 applications pass the catalog as an ordinary argument.
+            &catalog_argument_value(
+                match database_as {
+                    DatabaseAs::Catalog => catalog,
+                    DatabaseAs::Schema => schema,
+                },
+                if odbc_3_behavior {
+                    CatalogArgument::PatternValue
+                } else {
+                    CatalogArgument::Ordinary
+                },
+                metadata_id,
+            ),
+            &catalog_argument_value(table, CatalogArgument::PatternValue, metadata_id),
             table_t,
             odbc_3_behavior,
+            database_as,
         ))),
     }
 }
@@ -4197,12 +4903,24 @@ pub unsafe extern "C" fn SQLTablesW(
             let mongo_handle = try_mongo_handle!(statement_handle);
             let odbc_behavior = has_odbc_3_behavior!(mongo_handle);
             let stmt = must_be_valid!((*mongo_handle).as_statement());
+            let connection = (*stmt.connection).as_connection().unwrap();
+            let max_string_length = *connection.max_string_length.read().unwrap();
+            let database_as = *connection.database_as.read().unwrap();
+            let metadata_id = stmt.attributes.read().unwrap().metadata_id == SqlBool::SQL_TRUE;
+            odbc_unwrap!(
+                check_identifier_arguments(
+                    metadata_id,
+                    database_as,
+                    catalog_name,
+                    schema_name,
+                    &[("TableName", table_name)],
+                ),
+                mongo_handle
+            );
             let catalog = input_text_to_string_w(catalog_name, name_length_1.into());
             let schema = input_text_to_string_w_allow_null(schema_name, name_length_2.into());
             let table = input_text_to_string_w_allow_null(table_name, name_length_3.into());
             let table_t = input_text_to_string_w(table_type, name_length_4.into());
-            let connection = (*stmt.connection).as_connection().unwrap();
-            let max_string_length = *connection.max_string_length.read().unwrap();
             let mongo_statement = sql_tables(
                 connection
                     .mongo_connection
@@ -4222,6 +4940,8 @@ pub unsafe extern "C" fn SQLTablesW(
                 &table_t,
                 odbc_behavior,
                 max_string_length,
+                database_as,
+                metadata_id,
             );
             let mongo_statement = odbc_unwrap!(mongo_statement, mongo_handle);
             *stmt.mongo_statement.write().unwrap() = Some(mongo_statement);
@@ -20,6 +20,7 @@ macro_rules! test_get_info {
     $(expected_length = $expected_length:expr,)?
     $(expected_value = $expected_value:expr,)?
     $(actual_value_modifier = $actual_value_modifier:ident,)?
+    $(database_as = $database_as:expr,)?
     ) => {
         #[test]
         fn $func_name() {
@@ -29,6 +30,7 @@ macro_rules! test_get_info {
                 #[allow(unused_mut, unused_assignments)]
                 let mut conn =
                     Connection::with_state(std::ptr::null_mut(), ConnectionState::Connected);
+                $(*conn.database_as.write().unwrap() = $database_as;)?
                 let mongo_handle: *mut _ = &mut MongoHandle::Connection(conn);
 
                 let value_ptr: *mut std::ffi::c_void = Box::into_raw(Box::new([0u8; 900])) as *mut _;
@@ -119,6 +121,7 @@ mod unit {
     use super::*;
     use constants::{DBMS_NAME, DRIVER_NAME, DRIVER_ODBC_VERSION, ODBC_VERSION};
     use cstr::WideChar;
+    use mongo_odbc_core::DatabaseAs;
     use std::mem::size_of;
 
     test_get_info!(
@@ -161,6 +164,24 @@ mod unit {
         actual_value_modifier = modify_string_value,
     );
 
+    test_get_info!(
+        identifier_case,
+        info_type = InfoType::SQL_IDENTIFIER_CASE as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        expected_length = std::mem::size_of::<u16>() as i16,
+        expected_value = SQL_IC_SENSITIVE,
+        actual_value_modifier = modify_u16_value,
+    );
+
+    test_get_info!(
+        quoted_identifier_case,
+        info_type = InfoType::SQL_QUOTED_IDENTIFIER_CASE as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        expected_length = std::mem::size_of::<u16>() as i16,
+        expected_value = SQL_IC_SENSITIVE,
+        actual_value_modifier = modify_u16_value,
+    );
+
     test_get_info!(
         dbms_name,
         info_type = InfoType::SQL_DBMS_NAME as u16,
@@ -565,6 +586,21 @@ mod unit {
 
     test_get_info_expect_u32_sql_all!(convert_guid, info_type = InfoType::SQL_CONVERT_GUID as u16);
 
+    test_get_info_expect_u32_zero!(
+        convert_interval_year_month,
+        info_type = InfoType::SQL_CONVERT_INTERVAL_YEAR_MONTH as u16
+    );
+
+    test_get_info_expect_u32_zero!(
+        convert_interval_day_time,
+        info_type = InfoType::SQL_CONVERT_INTERVAL_DAY_TIME as u16
+    );
+
+    test_get_info_expect_u32_zero!(
+        datetime_literals,
+        info_type = InfoType::SQL_DATETIME_LITERALS as u16
+    );
+
     test_get_info!(
         getdata_extensions,
         info_type = InfoType::SQL_GETDATA_EXTENSIONS as u16,
@@ -772,6 +808,69 @@ mod unit {
         actual_value_modifier = modify_string_value,
     );
 
+    test_get_info!(
+        catalog_name_database_as_schema,
+        info_type = InfoType::SQL_CATALOG_NAME as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        buffer_length = 2 * size_of::<WideChar>() as i16,
+        expected_length = size_of::<WideChar>() as i16,
+        expected_value = "N",
+        actual_value_modifier = modify_string_value,
+        database_as = DatabaseAs::Schema,
+    );
+
+    test_get_info!(
+        catalog_term_database_as_schema,
+        info_type = InfoType::SQL_CATALOG_TERM as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        buffer_length = size_of::<WideChar>() as i16,
+        expected_length = 0,
+        expected_value = "",
+        actual_value_modifier = modify_string_value,
+        database_as = DatabaseAs::Schema,
+    );
+
+    test_get_info!(
+        owner_term_database_as_schema,
+        info_type = InfoType::SQL_OWNER_TERM as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        buffer_length = 9 * size_of::<WideChar>() as i16,
+        expected_length = 8 * size_of::<WideChar>() as i16,
+        expected_value = "database",
+        actual_value_modifier = modify_string_value,
+        database_as = DatabaseAs::Schema,
+    );
+
+    test_get_info!(
+        catalog_usage_database_as_schema,
+        info_type = InfoType::SQL_CATALOG_USAGE as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        expected_length = std::mem::size_of::<u32>() as i16,
+        expected_value = 0u32,
+        actual_value_modifier = modify_u32_value,
+        database_as = DatabaseAs::Schema,
+    );
+
+    test_get_info!(
+        owner_usage_database_as_schema,
+        info_type = InfoType::SQL_OWNER_USAGE as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        expected_length = std::mem::size_of::<u32>() as i16,
+        expected_value = 1u32,
+        actual_value_modifier = modify_u32_value,
+        database_as = DatabaseAs::Schema,
+    );
+
+    test_get_info!(
+        catalog_location_database_as_schema,
+        info_type = InfoType::SQL_CATALOG_LOCATION as u16,
+        expected_sql_return = SqlReturn::SUCCESS,
+        expected_length = std::mem::size_of::<u16>() as i16,
+        expected_value = 0u16,
+        actual_value_modifier = modify_u16_value,
+        database_as = DatabaseAs::Schema,
+    );
+
     test_get_info!(
         max_identifier_len,
         info_type = InfoType::SQL_MAX_IDENTIFIER_LEN as u16,
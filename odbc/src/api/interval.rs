@@ -0,0 +1,504 @@
+use crate::api::decimal::{Decimal, Rounding};
+use definitions::{
+    CDataType, DaySecond, Interval, IntervalStruct, IntervalUnion, SmallInt, YearMonth,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::os::raw::c_int;
+
+// The number of fractional second digits kept in SQL_DAY_SECOND_STRUCT, which is the default
+// interval seconds precision.
+const SECONDS_PRECISION: u32 = 6;
+
+lazy_static! {
+    static ref INTERVAL_LITERAL_REGEX: Regex =
+        Regex::new(r"(?i)^INTERVAL\s*([+-]?)\s*'([^']*)'\s*(.*)$").unwrap();
+    static ref QUALIFIER_PRECISION_REGEX: Regex =
+        Regex::new(r"\(\s*\d+\s*(,\s*\d+\s*)?\)").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl Field {
+    // The largest value of the field when it is not the leading field of an interval.
+    fn max_trailing_value(self) -> u32 {
+        match self {
+            Field::Month => 11,
+            Field::Hour => 23,
+            Field::Minute | Field::Second => 59,
+            Field::Year | Field::Day => u32::MAX,
+        }
+    }
+
+    // The character separating the field from the previous field of an interval value.
+    fn separator(self) -> char {
+        match self {
+            Field::Month => '-',
+            Field::Hour => ' ',
+            _ => ':',
+        }
+    }
+}
+
+// The fields of an interval type, leading field first.
+fn fields(interval: Interval) -> &'static [Field] {
+    match interval {
+        Interval::SQL_IS_YEAR => &[Field::Year],
+        Interval::SQL_IS_MONTH => &[Field::Month],
+        Interval::SQL_IS_DAY => &[Field::Day],
+        Interval::SQL_IS_HOUR => &[Field::Hour],
+        Interval::SQL_IS_MINUTE => &[Field::Minute],
+        Interval::SQL_IS_SECOND => &[Field::Second],
+        Interval::SQL_IS_YEAR_TO_MONTH => &[Field::Year, Field::Month],
+        Interval::SQL_IS_DAY_TO_HOUR => &[Field::Day, Field::Hour],
+        Interval::SQL_IS_DAY_TO_MINUTE => &[Field::Day, Field::Hour, Field::Minute],
+        Interval::SQL_IS_DAY_TO_SECOND => &[Field::Day, Field::Hour, Field::Minute, Field::Second],
+        Interval::SQL_IS_HOUR_TO_MINUTE => &[Field::Hour, Field::Minute],
+        Interval::SQL_IS_HOUR_TO_SECOND => &[Field::Hour, Field::Minute, Field::Second],
+        Interval::SQL_IS_MINUTE_TO_SECOND => &[Field::Minute, Field::Second],
+    }
+}
+
+// The interval qualifier of an interval type, as written in an interval literal.
+fn qualifier(interval: Interval) -> &'static str {
+    match interval {
+        Interval::SQL_IS_YEAR => "YEAR",
+        Interval::SQL_IS_MONTH => "MONTH",
+        Interval::SQL_IS_DAY => "DAY",
+        Interval::SQL_IS_HOUR => "HOUR",
+        Interval::SQL_IS_MINUTE => "MINUTE",
+        Interval::SQL_IS_SECOND => "SECOND",
+        Interval::SQL_IS_YEAR_TO_MONTH => "YEAR TO MONTH",
+        Interval::SQL_IS_DAY_TO_HOUR => "DAY TO HOUR",
+        Interval::SQL_IS_DAY_TO_MINUTE => "DAY TO MINUTE",
+        Interval::SQL_IS_DAY_TO_SECOND => "DAY TO SECOND",
+        Interval::SQL_IS_HOUR_TO_MINUTE => "HOUR TO MINUTE",
+        Interval::SQL_IS_HOUR_TO_SECOND => "HOUR TO SECOND",
+        Interval::SQL_IS_MINUTE_TO_SECOND => "MINUTE TO SECOND",
+    }
+}
+
+/// Returns the interval type of an interval C data type, or None for other C data types.
+pub(crate) fn interval_type(c_data_type: CDataType) -> Option<Interval> {
+    Some(match c_data_type {
+        CDataType::SQL_C_INTERVAL_YEAR => Interval::SQL_IS_YEAR,
+        CDataType::SQL_C_INTERVAL_MONTH => Interval::SQL_IS_MONTH,
+        CDataType::SQL_C_INTERVAL_DAY => Interval::SQL_IS_DAY,
+        CDataType::SQL_C_INTERVAL_HOUR => Interval::SQL_IS_HOUR,
+        CDataType::SQL_C_INTERVAL_MINUTE => Interval::SQL_IS_MINUTE,
+        CDataType::SQL_C_INTERVAL_SECOND => Interval::SQL_IS_SECOND,
+        CDataType::SQL_C_INTERVAL_YEAR_TO_MONTH => Interval::SQL_IS_YEAR_TO_MONTH,
+        CDataType::SQL_C_INTERVAL_DAY_TO_HOUR => Interval::SQL_IS_DAY_TO_HOUR,
+        CDataType::SQL_C_INTERVAL_DAY_TO_MINUTE => Interval::SQL_IS_DAY_TO_MINUTE,
+        CDataType::SQL_C_INTERVAL_DAY_TO_SECOND => Interval::SQL_IS_DAY_TO_SECOND,
+        CDataType::SQL_C_INTERVAL_HOUR_TO_MINUTE => Interval::SQL_IS_HOUR_TO_MINUTE,
+        CDataType::SQL_C_INTERVAL_HOUR_TO_SECOND => Interval::SQL_IS_HOUR_TO_SECOND,
+        CDataType::SQL_C_INTERVAL_MINUTE_TO_SECOND => Interval::SQL_IS_MINUTE_TO_SECOND,
+        _ => return None,
+    })
+}
+
+/// The reason a value cannot be converted to an interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IntervalError {
+    /// A field value does not fit in the interval.
+    Overflow,
+    /// A string is neither a valid interval value nor a valid interval literal.
+    InvalidCharacterValue,
+    /// Numbers only convert to intervals with a single field.
+    RestrictedDataType,
+}
+
+/// IntervalValue is the content of a SQL_INTERVAL_STRUCT. The fields that are not part of the
+/// interval type are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IntervalValue {
+    pub interval: Interval,
+    pub negative: bool,
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Fractional seconds, in millionths of a second.
+    pub fraction: u32,
+}
+
+impl IntervalValue {
+    fn new(interval: Interval, negative: bool) -> Self {
+        IntervalValue {
+            interval,
+            negative,
+            year: 0,
+            month: 0,
+            day: 0,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            fraction: 0,
+        }
+    }
+
+    fn set(&mut self, field: Field, value: u32) {
+        match field {
+            Field::Year => self.year = value,
+            Field::Month => self.month = value,
+            Field::Day => self.day = value,
+            Field::Hour => self.hour = value,
+            Field::Minute => self.minute = value,
+            Field::Second => self.second = value,
+        }
+    }
+
+    /// Converts a number to a single field interval, whose field holds the number. The fraction
+    /// is kept for a SECOND interval and truncated otherwise; the second element of the result is
+    /// true if nonzero digits were truncated.
+    pub(crate) fn from_decimal(
+        interval: Interval,
+        decimal: Decimal,
+    ) -> Result<(Self, bool), IntervalError> {
+        let [field] = fields(interval) else {
+            return Err(IntervalError::RestrictedDataType);
+        };
+        let scale = if *field == Field::Second {
+            SECONDS_PRECISION
+        } else {
+            0
+        };
+        #[allow(clippy::cast_possible_wrap)]
+        let rescaled = decimal
+            .rescale(scale as i32, Rounding::Truncate)
+            .ok_or(IntervalError::Overflow)?;
+        let divisor = 10u128.pow(scale);
+        let mut value = IntervalValue::new(interval, rescaled.negative);
+        value.set(
+            *field,
+            u32::try_from(rescaled.unscaled / divisor).map_err(|_| IntervalError::Overflow)?,
+        );
+        // The remainder is less than 10^SECONDS_PRECISION.
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            value.fraction = (rescaled.unscaled % divisor) as u32;
+        }
+        Ok((value, rescaled.inexact))
+    }
+
+    /// Parses an interval value, such as `-1 02:03:04.5` for a DAY TO SECOND interval, or an
+    /// interval literal, such as `INTERVAL '-1-2' YEAR TO MONTH`, whose qualifier must match
+    /// `interval`. The second element of the result is true if nonzero fractional second digits
+    /// were truncated.
+    pub(crate) fn parse(interval: Interval, s: &str) -> Result<(Self, bool), IntervalError> {
+        let s = s.trim();
+        let Some(captures) = INTERVAL_LITERAL_REGEX.captures(s) else {
+            return parse_value(interval, s);
+        };
+        // The qualifier may include the leading field and seconds precisions, which do not
+        // change the value.
+        let literal_qualifier = QUALIFIER_PRECISION_REGEX
+            .replace_all(&captures[3], " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_uppercase();
+        if literal_qualifier != qualifier(interval) {
+            return Err(IntervalError::InvalidCharacterValue);
+        }
+        let (mut value, inexact) = parse_value(interval, captures[2].trim())?;
+        if &captures[1] == "-" {
+            value.negative = !value.negative;
+        }
+        Ok((value, inexact))
+    }
+}
+
+// Parses the value of an interval literal: an optional sign followed by the fields of the
+// interval, each separated from the previous one by the field's separator.
+fn parse_value(interval: Interval, s: &str) -> Result<(IntervalValue, bool), IntervalError> {
+    let (negative, mut rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut value = IntervalValue::new(interval, negative);
+    let mut inexact = false;
+    for (i, field) in fields(interval).iter().enumerate() {
+        if i > 0 {
+            rest = rest
+                .strip_prefix(field.separator())
+                .ok_or(IntervalError::InvalidCharacterValue)?;
+        }
+        let digits;
+        (digits, rest) = split_digits(rest);
+        if digits.is_empty() {
+            return Err(IntervalError::InvalidCharacterValue);
+        }
+        // The digits are all ASCII digits, so the only possible error is overflow.
+        let field_value = digits.parse::<u32>().map_err(|_| IntervalError::Overflow)?;
+        if i > 0 && field_value > field.max_trailing_value() {
+            return Err(IntervalError::Overflow);
+        }
+        value.set(*field, field_value);
+        if *field == Field::Second {
+            if let Some(fraction) = rest.strip_prefix('.') {
+                let digits;
+                (digits, rest) = split_digits(fraction);
+                let (kept, dropped) = digits.split_at(digits.len().min(SECONDS_PRECISION as usize));
+                value.fraction = kept
+                    .bytes()
+                    .chain(std::iter::repeat(b'0'))
+                    .take(SECONDS_PRECISION as usize)
+                    .fold(0, |fraction, digit| fraction * 10 + u32::from(digit - b'0'));
+                inexact = dropped.bytes().any(|digit| digit != b'0');
+            }
+        }
+    }
+    if !rest.is_empty() {
+        return Err(IntervalError::InvalidCharacterValue);
+    }
+    Ok((value, inexact))
+}
+
+// Splits s after its leading ASCII digits.
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+impl From<IntervalValue> for IntervalStruct {
+    fn from(value: IntervalValue) -> Self {
+        let interval_value = match value.interval {
+            Interval::SQL_IS_YEAR | Interval::SQL_IS_MONTH | Interval::SQL_IS_YEAR_TO_MONTH => {
+                IntervalUnion {
+                    year_month: YearMonth {
+                        year: value.year,
+                        month: value.month,
+                    },
+                }
+            }
+            _ => IntervalUnion {
+                day_second: DaySecond {
+                    day: value.day,
+                    hour: value.hour,
+                    minute: value.minute,
+                    second: value.second,
+                    fraction: value.fraction,
+                },
+            },
+        };
+        IntervalStruct {
+            interval_type: value.interval as c_int,
+            interval_sign: SmallInt::from(value.negative),
+            interval_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use mongodb::bson::Decimal128;
+    use std::str::FromStr;
+
+    fn decimal(s: &str) -> Decimal {
+        Decimal::from(&Decimal128::from_str(s).unwrap())
+    }
+
+    fn interval(interval: Interval, negative: bool) -> IntervalValue {
+        IntervalValue::new(interval, negative)
+    }
+
+    #[test]
+    fn numbers_convert_to_single_field_intervals() {
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    day: 12,
+                    ..interval(Interval::SQL_IS_DAY, true)
+                },
+                true
+            )),
+            IntervalValue::from_decimal(Interval::SQL_IS_DAY, decimal("-12.5"))
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    second: 90,
+                    fraction: 250_000,
+                    ..interval(Interval::SQL_IS_SECOND, false)
+                },
+                false
+            )),
+            IntervalValue::from_decimal(Interval::SQL_IS_SECOND, decimal("90.25"))
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    fraction: 123_456,
+                    ..interval(Interval::SQL_IS_SECOND, false)
+                },
+                true
+            )),
+            IntervalValue::from_decimal(Interval::SQL_IS_SECOND, decimal("0.1234567"))
+        );
+        assert_eq!(
+            Err(IntervalError::Overflow),
+            IntervalValue::from_decimal(Interval::SQL_IS_YEAR, decimal("4294967296"))
+        );
+        assert_eq!(
+            Err(IntervalError::Overflow),
+            IntervalValue::from_decimal(Interval::SQL_IS_YEAR, decimal("Infinity"))
+        );
+        assert_eq!(
+            Err(IntervalError::RestrictedDataType),
+            IntervalValue::from_decimal(Interval::SQL_IS_DAY_TO_HOUR, decimal("1"))
+        );
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    year: 1,
+                    month: 2,
+                    ..interval(Interval::SQL_IS_YEAR_TO_MONTH, true)
+                },
+                false
+            )),
+            IntervalValue::parse(Interval::SQL_IS_YEAR_TO_MONTH, "-1-2")
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    day: 400,
+                    hour: 2,
+                    minute: 3,
+                    second: 4,
+                    fraction: 500_000,
+                    ..interval(Interval::SQL_IS_DAY_TO_SECOND, false)
+                },
+                false
+            )),
+            IntervalValue::parse(Interval::SQL_IS_DAY_TO_SECOND, " +400 02:03:04.5 ")
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    minute: 75,
+                    second: 1,
+                    fraction: 999_999,
+                    ..interval(Interval::SQL_IS_MINUTE_TO_SECOND, false)
+                },
+                true
+            )),
+            IntervalValue::parse(Interval::SQL_IS_MINUTE_TO_SECOND, "75:01.9999999")
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    hour: 3,
+                    ..interval(Interval::SQL_IS_HOUR, false)
+                },
+                false
+            )),
+            IntervalValue::parse(Interval::SQL_IS_HOUR, "3")
+        );
+    }
+
+    #[test]
+    fn parse_literals() {
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    year: 1,
+                    month: 2,
+                    ..interval(Interval::SQL_IS_YEAR_TO_MONTH, true)
+                },
+                false
+            )),
+            IntervalValue::parse(
+                Interval::SQL_IS_YEAR_TO_MONTH,
+                "INTERVAL '-1-2' YEAR TO MONTH"
+            )
+        );
+        assert_eq!(
+            Ok((
+                IntervalValue {
+                    day: 5,
+                    hour: 6,
+                    ..interval(Interval::SQL_IS_DAY_TO_HOUR, false)
+                },
+                false
+            )),
+            IntervalValue::parse(
+                Interval::SQL_IS_DAY_TO_HOUR,
+                "interval -'-5 06' day(3)  to hour"
+            )
+        );
+        assert_eq!(
+            Err(IntervalError::InvalidCharacterValue),
+            IntervalValue::parse(Interval::SQL_IS_DAY, "INTERVAL '5' HOUR")
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Err(IntervalError::Overflow),
+            IntervalValue::parse(Interval::SQL_IS_YEAR_TO_MONTH, "1-12")
+        );
+        assert_eq!(
+            Err(IntervalError::Overflow),
+            IntervalValue::parse(Interval::SQL_IS_HOUR_TO_MINUTE, "1:60")
+        );
+        assert_eq!(
+            Err(IntervalError::Overflow),
+            IntervalValue::parse(Interval::SQL_IS_DAY, "4294967296")
+        );
+        for s in ["", "1.5", "1:2", "1 2", "a", "--1", "1 02:03"] {
+            assert_eq!(
+                Err(IntervalError::InvalidCharacterValue),
+                IntervalValue::parse(Interval::SQL_IS_DAY, s),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn into_interval_struct() {
+        let interval_struct = IntervalStruct::from(IntervalValue {
+            hour: 1,
+            minute: 2,
+            ..interval(Interval::SQL_IS_HOUR_TO_MINUTE, true)
+        });
+        assert_eq!(
+            Interval::SQL_IS_HOUR_TO_MINUTE as c_int,
+            interval_struct.interval_type
+        );
+        assert_eq!(1, interval_struct.interval_sign);
+        assert_eq!(
+            DaySecond {
+                hour: 1,
+                minute: 2,
+                ..Default::default()
+            },
+            unsafe { interval_struct.interval_value.day_second }
+        );
+
+        let interval_struct = IntervalStruct::from(IntervalValue {
+            month: 7,
+            ..interval(Interval::SQL_IS_MONTH, false)
+        });
+        assert_eq!(0, interval_struct.interval_sign);
+        assert_eq!(YearMonth { year: 0, month: 7 }, unsafe {
+            interval_struct.interval_value.year_month
+        });
+    }
+}
@@ -0,0 +1,160 @@
+use crate::api::timezone::SessionTimeZone;
+use chrono::SecondsFormat;
+use definitions::AttrMongoDbJsonFormat;
+use mongo_odbc_core::Error;
+use mongodb::bson::Bson;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// JsonFormat is how documents, arrays and the BSON types without a character representation
+/// are rendered as JSON when they are converted to character or binary data. It comes from the
+/// json_format connection string/DSN option. UUIDs are rendered with the UuidRepresentation of
+/// the connection in every format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonFormat {
+    /// Relaxed Extended JSON v2, with dates in the session time zone.
+    #[default]
+    Relaxed,
+    /// Canonical Extended JSON v2, which preserves the BSON type of every value.
+    Canonical,
+    /// Plain JSON: ObjectIds, dates, decimals and binaries are rendered as strings.
+    Simplified,
+}
+
+impl FromStr for JsonFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "relaxed" => Ok(JsonFormat::Relaxed),
+            "canonical" => Ok(JsonFormat::Canonical),
+            "simplified" => Ok(JsonFormat::Simplified),
+            _ => Err(Error::InvalidUriFormat(format!(
+                "invalid json_format value '{s}', expected 'relaxed', 'canonical' or 'simplified'"
+            ))),
+        }
+    }
+}
+
+impl JsonFormat {
+    /// Returns the format set by the SQL_ATTR_MONGODB_JSON_FORMAT statement attribute, or self
+    /// if the statement uses the format of the connection.
+    pub(crate) fn for_statement(self, json_format: AttrMongoDbJsonFormat) -> JsonFormat {
+        match json_format {
+            AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_DEFAULT => self,
+            AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_RELAXED => JsonFormat::Relaxed,
+            AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_CANONICAL => JsonFormat::Canonical,
+            AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_SIMPLIFIED => JsonFormat::Simplified,
+        }
+    }
+}
+
+/// Renders a BSON value other than a document, an array or a UUID as plain JSON.
+pub(crate) fn to_simplified_json_val(data: Bson, timezone: SessionTimeZone) -> Value {
+    match data {
+        Bson::Double(f) if f.is_nan() => Value::String("NaN".to_string()),
+        Bson::Double(f) if f.is_infinite() => {
+            Value::String(if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string())
+        }
+        Bson::Double(f) => json!(f),
+        Bson::Decimal128(d) => Value::String(d.to_string()),
+        Bson::ObjectId(oid) => Value::String(oid.to_hex()),
+        Bson::DateTime(d) => Value::String(
+            timezone
+                .to_session_time(d.to_chrono())
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        ),
+        Bson::Binary(b) => Bson::Binary(b).into_relaxed_extjson()["$binary"]["base64"].clone(),
+        Bson::RegularExpression(regex) => {
+            Value::String(format!("/{}/{}", regex.pattern, regex.options))
+        }
+        Bson::JavaScriptCode(code) => Value::String(code),
+        Bson::JavaScriptCodeWithScope(code_with_scope) => Value::String(code_with_scope.code),
+        Bson::Symbol(s) => Value::String(s),
+        Bson::Timestamp(ts) => json!({"t": ts.time, "i": ts.increment}),
+        Bson::Undefined => Value::Null,
+        data => data.into_relaxed_extjson(),
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use chrono_tz::Tz;
+    use mongodb::bson::{
+        oid::ObjectId, spec::BinarySubtype, Binary, DateTime, Decimal128, Regex, Timestamp,
+    };
+
+    #[test]
+    fn parse() {
+        assert_eq!(JsonFormat::Relaxed, "relaxed".parse().unwrap());
+        assert_eq!(JsonFormat::Canonical, "CANONICAL".parse().unwrap());
+        assert_eq!(JsonFormat::Simplified, "Simplified".parse().unwrap());
+        assert!("legacy".parse::<JsonFormat>().is_err());
+    }
+
+    #[test]
+    fn simplified_json() {
+        let utc = SessionTimeZone::Utc;
+        assert_eq!(json!(1.5), to_simplified_json_val(Bson::Double(1.5), utc));
+        assert_eq!(
+            json!("-Infinity"),
+            to_simplified_json_val(Bson::Double(f64::NEG_INFINITY), utc)
+        );
+        assert_eq!(json!(7), to_simplified_json_val(Bson::Int64(7), utc));
+        assert_eq!(
+            json!("1.30"),
+            to_simplified_json_val(Bson::Decimal128(Decimal128::from_str("1.30").unwrap()), utc)
+        );
+        assert_eq!(
+            json!("63448dfed38427a35d534e40"),
+            to_simplified_json_val(
+                Bson::ObjectId(ObjectId::parse_str("63448dfed38427a35d534e40").unwrap()),
+                utc
+            )
+        );
+        let date = Bson::DateTime(DateTime::from_millis(1_417_176_009_123));
+        assert_eq!(
+            json!("2014-11-28T12:00:09.123Z"),
+            to_simplified_json_val(date.clone(), utc)
+        );
+        assert_eq!(
+            json!("2014-11-28T17:30:09.123+05:30"),
+            to_simplified_json_val(date, SessionTimeZone::Named(Tz::Asia__Kolkata))
+        );
+        assert_eq!(
+            json!("BQYq"),
+            to_simplified_json_val(
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: vec![5, 6, 42],
+                }),
+                utc
+            )
+        );
+        assert_eq!(
+            json!("/a.*b/i"),
+            to_simplified_json_val(
+                Bson::RegularExpression(Regex {
+                    pattern: "a.*b".to_string(),
+                    options: "i".to_string(),
+                }),
+                utc
+            )
+        );
+        assert_eq!(
+            json!({"t": 1, "i": 2}),
+            to_simplified_json_val(
+                Bson::Timestamp(Timestamp {
+                    time: 1,
+                    increment: 2
+                }),
+                utc
+            )
+        );
+        assert_eq!(
+            json!({"$maxKey": 1}),
+            to_simplified_json_val(Bson::MaxKey, utc)
+        );
+    }
+}
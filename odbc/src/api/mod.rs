@@ -13,6 +13,8 @@ mod connect_attr_tests;
 pub(crate) mod data;
 #[cfg(test)]
 mod data_tests;
+pub(crate) mod date_range;
+pub(crate) mod decimal;
 #[cfg(test)]
 mod env_attr_tests;
 #[cfg(test)]
@@ -25,10 +27,13 @@ mod get_diag_rec_tests;
 mod get_info_tests;
 #[cfg(test)]
 mod get_type_info_tests;
+pub(crate) mod interval;
+pub(crate) mod json_format;
 #[cfg(test)]
 mod panic_safe_exec_tests;
 #[cfg(test)]
 mod stmt_attr_tests;
+pub(crate) mod timezone;
 pub(crate) mod util;
 
 #[macro_export]
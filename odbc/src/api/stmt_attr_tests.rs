@@ -9,8 +9,9 @@ use crate::{
     map, SQLGetStmtAttrW, SQLSetStmtAttrW,
 };
 use definitions::{
-    AsyncEnable, BindType, CursorScrollable, CursorSensitivity, CursorType, HStmt, Integer, NoScan,
-    Pointer, RetrieveData, SqlBool, SqlReturn, StatementAttribute, ULen, USmallInt, UseBookmarks,
+    AsyncEnable, AttrMongoDbJsonFormat, AttrMongoDbReadConcernLevel, AttrMongoDbReadPreference,
+    BindType, CursorScrollable, CursorSensitivity, CursorType, HStmt, Integer, NoScan, Pointer,
+    RetrieveData, SqlBool, SqlReturn, StatementAttribute, ULen, USmallInt, UseBookmarks,
 };
 use std::{collections::BTreeMap, mem::size_of};
 
@@ -308,6 +309,45 @@ mod unit {
             },
             UseBookmarks::Off as usize,
         );
+        get_set_stmt_attr(
+            stmt_handle,
+            StatementAttribute::SQL_ATTR_METADATA_ID,
+            map! {
+                SqlBool::SQL_FALSE as i32 => SqlReturn::SUCCESS,
+                SqlBool::SQL_TRUE as i32 => SqlReturn::SUCCESS,
+            },
+            SqlBool::SQL_FALSE as usize,
+        );
+        get_set_stmt_attr(
+            stmt_handle,
+            StatementAttribute::SQL_ATTR_MONGODB_READ_PREFERENCE,
+            map! {
+                -1 => SqlReturn::ERROR,
+                AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_SECONDARY as i32 => SqlReturn::SUCCESS,
+                AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_PRIMARY as i32 => SqlReturn::SUCCESS,
+            },
+            AttrMongoDbReadPreference::SQL_MONGODB_READ_PREFERENCE_DEFAULT as usize,
+        );
+        get_set_stmt_attr(
+            stmt_handle,
+            StatementAttribute::SQL_ATTR_MONGODB_READ_CONCERN_LEVEL,
+            map! {
+                -1 => SqlReturn::ERROR,
+                AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_MAJORITY as i32 => SqlReturn::SUCCESS,
+                AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_DEFAULT as i32 => SqlReturn::SUCCESS,
+            },
+            AttrMongoDbReadConcernLevel::SQL_MONGODB_READ_CONCERN_DEFAULT as usize,
+        );
+        get_set_stmt_attr(
+            stmt_handle,
+            StatementAttribute::SQL_ATTR_MONGODB_JSON_FORMAT,
+            map! {
+                -1 => SqlReturn::ERROR,
+                AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_CANONICAL as i32 => SqlReturn::SUCCESS,
+                AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_DEFAULT as i32 => SqlReturn::SUCCESS,
+            },
+            AttrMongoDbJsonFormat::SQL_MONGODB_JSON_FORMAT_DEFAULT as usize,
+        );
     }
 
     // test_unsupported_attributes tests SQLGetStmtAttr and SQLSetStmtAttr with every
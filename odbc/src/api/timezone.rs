@@ -0,0 +1,239 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use mongo_odbc_core::Error;
+use mongodb::bson::{self, Bson};
+use regex::Regex;
+use std::{fmt, str::FromStr};
+
+lazy_static! {
+    // A timestamp literal, `TIMESTAMP '...'` or the `{ts '...'}` escape sequence, capturing its
+    // value.
+    static ref TIMESTAMP_LITERAL_REGEX: Regex = Regex::new(
+        r"^(?i:TIMESTAMP\s*|\{\s*ts\s+)'(\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:\.\d+)?)'"
+    )
+    .unwrap();
+}
+
+/// SessionTimeZone is the time zone BSON dates are presented in, and timestamp literals are
+/// written in. It comes from the SQL_ATTR_MONGODB_TIMEZONE connection attribute or the timezone
+/// connection string/DSN option. The driver does not support parameters, so no parameter value
+/// is converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionTimeZone {
+    #[default]
+    Utc,
+    /// The time zone of the machine the driver runs on.
+    Local,
+    /// An IANA time zone, such as America/New_York.
+    Named(Tz),
+}
+
+impl FromStr for SessionTimeZone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" => Ok(SessionTimeZone::Utc),
+            "local" => Ok(SessionTimeZone::Local),
+            _ => Tz::from_str(s).map(SessionTimeZone::Named).map_err(|_| {
+                Error::InvalidUriFormat(format!(
+                    "invalid timezone value '{s}', expected an IANA time zone name, 'local' or 'utc'"
+                ))
+            }),
+        }
+    }
+}
+
+impl fmt::Display for SessionTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionTimeZone::Utc => write!(f, "UTC"),
+            SessionTimeZone::Local => write!(f, "local"),
+            SessionTimeZone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl SessionTimeZone {
+    /// Returns `date` in this time zone.
+    pub(crate) fn to_session_time(self, date: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            SessionTimeZone::Utc => date.fixed_offset(),
+            SessionTimeZone::Local => date.with_timezone(&Local).fixed_offset(),
+            SessionTimeZone::Named(tz) => date.with_timezone(&tz).fixed_offset(),
+        }
+    }
+
+    /// Returns the offset from UTC of this time zone at `date`, in milliseconds.
+    pub(crate) fn offset_millis(self, date: bson::DateTime) -> i64 {
+        i64::from(
+            self.to_session_time(date.to_chrono())
+                .offset()
+                .local_minus_utc(),
+        ) * 1000
+    }
+
+    /// Replaces a BSON date with the date whose UTC date and time are the date and time of the
+    /// original date in this time zone. The date, time and timestamp conversions read the UTC
+    /// fields of a date, so they then produce the local date and time. Other values are returned
+    /// unchanged.
+    pub(crate) fn localize(&self, data: Bson) -> Bson {
+        match (self, data) {
+            (SessionTimeZone::Utc, data) => data,
+            (_, Bson::DateTime(d)) => Bson::DateTime(bson::DateTime::from_millis(
+                d.timestamp_millis().saturating_add(self.offset_millis(d)),
+            )),
+            (_, data) => data,
+        }
+    }
+
+    /// Rewrites the timestamp literals of a query, `TIMESTAMP '...'` and `{ts '...'}`, from this
+    /// time zone to UTC. Literals in strings, quoted identifiers and comments are left unchanged,
+    /// as are the local times skipped by a daylight saving time change. Date and time literals
+    /// are not an instant, so they are not converted.
+    pub(crate) fn literals_to_utc(&self, query: &str) -> String {
+        if *self == SessionTimeZone::Utc {
+            return query.to_string();
+        }
+        let mut converted = String::with_capacity(query.len());
+        let mut rest = query;
+        let mut previous = None;
+        while let Some(c) = rest.chars().next() {
+            let skipped = match c {
+                '\'' | '"' | '`' => quoted_len(rest, c),
+                '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+                '/' if rest.starts_with("/*") => rest[2..].find("*/").map_or(rest.len(), |i| i + 4),
+                _ => 0,
+            };
+            if skipped > 0 {
+                converted.push_str(&rest[..skipped]);
+                previous = rest[..skipped].chars().last();
+                rest = &rest[skipped..];
+                continue;
+            }
+            // A literal cannot be the end of an identifier, like my_timestamp.
+            let literal = match previous {
+                Some(p) if p.is_alphanumeric() || p == '_' => None,
+                _ => TIMESTAMP_LITERAL_REGEX.captures(rest),
+            };
+            if let Some(literal) = literal {
+                let (whole, value) = (literal.get(0).unwrap(), literal.get(1).unwrap());
+                converted.push_str(&rest[..value.start()]);
+                match self.local_to_utc(value.as_str()) {
+                    Some(utc) => converted.push_str(&utc),
+                    None => converted.push_str(value.as_str()),
+                }
+                converted.push_str(&rest[value.end()..whole.end()]);
+                previous = whole.as_str().chars().last();
+                rest = &rest[whole.end()..];
+                continue;
+            }
+            converted.push(c);
+            previous = Some(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        converted
+    }
+
+    // Returns the UTC date and time of a date and time in this time zone, or None if it does not
+    // exist in this time zone.
+    fn local_to_utc(&self, value: &str) -> Option<String> {
+        let local =
+            NaiveDateTime::parse_from_str(&value.replacen('T', " ", 1), "%Y-%m-%d %H:%M:%S%.f")
+                .ok()?;
+        let utc = match self {
+            SessionTimeZone::Utc => return Some(value.to_string()),
+            SessionTimeZone::Local => Local.from_local_datetime(&local).earliest()?.naive_utc(),
+            SessionTimeZone::Named(tz) => tz.from_local_datetime(&local).earliest()?.naive_utc(),
+        };
+        Some(utc.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+    }
+}
+
+// quoted_len returns the length of the quoted text at the start of `s`, including its quotes.
+// A quote is escaped by doubling it.
+fn quoted_len(s: &str, quote: char) -> usize {
+    let mut start = quote.len_utf8();
+    while let Some(i) = s[start..].find(quote) {
+        let end = start + i + quote.len_utf8();
+        if !s[end..].starts_with(quote) {
+            return end;
+        }
+        start = end + quote.len_utf8();
+    }
+    s.len()
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    fn date(s: &str) -> Bson {
+        Bson::DateTime(bson::DateTime::from_chrono(
+            s.parse::<DateTime<Utc>>().unwrap(),
+        ))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(SessionTimeZone::Utc, "UTC".parse().unwrap());
+        assert_eq!(SessionTimeZone::Local, "Local".parse().unwrap());
+        assert_eq!(
+            SessionTimeZone::Named(Tz::America__New_York),
+            "America/New_York".parse().unwrap()
+        );
+        assert!("Mars/Olympus_Mons".parse::<SessionTimeZone>().is_err());
+        assert_eq!(
+            "America/New_York",
+            SessionTimeZone::Named(Tz::America__New_York).to_string()
+        );
+    }
+
+    #[test]
+    fn literals_to_utc() {
+        let new_york = SessionTimeZone::Named(Tz::America__New_York);
+        assert_eq!(
+            "SELECT * FROM foo WHERE d > TIMESTAMP '2024-01-15 15:30:00' AND d < {ts '2024-07-15 06:30:00.500'}",
+            new_york.literals_to_utc(
+                "SELECT * FROM foo WHERE d > TIMESTAMP '2024-01-15 10:30:00' AND d < {ts '2024-07-15 02:30:00.5'}"
+            )
+        );
+        // Strings, quoted identifiers, comments, dates and identifiers are not converted.
+        let unchanged = "SELECT 'it''s TIMESTAMP ''2024-01-15 10:30:00'' here', \"TIMESTAMP '2024-01-15 10:30:00'\" \
+            FROM foo -- TIMESTAMP '2024-01-15 10:30:00'
+            WHERE d = DATE '2024-01-15' AND my_timestamp '2024-01-15 10:30:00' = 1 /* {ts '2024-01-15 10:30:00'} */";
+        assert_eq!(unchanged, new_york.literals_to_utc(unchanged));
+        // 02:30 does not exist on the day daylight saving time starts.
+        assert_eq!(
+            "TIMESTAMP '2024-03-10 02:30:00'",
+            new_york.literals_to_utc("TIMESTAMP '2024-03-10 02:30:00'")
+        );
+        assert_eq!(
+            "TIMESTAMP '2024-01-15 10:30:00'",
+            SessionTimeZone::Utc.literals_to_utc("TIMESTAMP '2024-01-15 10:30:00'")
+        );
+    }
+
+    #[test]
+    fn localize() {
+        let new_york = SessionTimeZone::Named(Tz::America__New_York);
+        assert_eq!(
+            date("2024-01-15T10:30:00.123Z"),
+            SessionTimeZone::Utc.localize(date("2024-01-15T10:30:00.123Z"))
+        );
+        // New York is 5 hours behind UTC in winter, and 4 hours behind in summer.
+        assert_eq!(
+            date("2024-01-15T05:30:00.123Z"),
+            new_york.localize(date("2024-01-15T10:30:00.123Z"))
+        );
+        assert_eq!(
+            date("2024-07-15T06:30:00Z"),
+            new_york.localize(date("2024-07-15T10:30:00Z"))
+        );
+        assert_eq!(
+            Bson::String("2024-01-15T10:30:00Z".to_string()),
+            new_york.localize(Bson::String("2024-01-15T10:30:00Z".to_string()))
+        );
+    }
+}
@@ -27,6 +27,13 @@ pub(crate) fn connection_attribute_to_string(attr: ConnectionAttribute) -> Strin
         ConnectionAttribute::SQL_ATTR_AUTO_IPD => "AUTO_IPD".to_string(),
         ConnectionAttribute::SQL_ATTR_METADATA_ID => "METADATA_ID".to_string(),
         ConnectionAttribute::SQL_ATTR_APP_WCHAR_TYPE => "APP_WCHAR_TYPE".to_string(),
+        ConnectionAttribute::SQL_ATTR_MONGODB_TIMEZONE => "MONGODB_TIMEZONE".to_string(),
+        ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK => {
+            "MONGODB_OIDC_NOTICE_CALLBACK".to_string()
+        }
+        ConnectionAttribute::SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT => {
+            "MONGODB_OIDC_NOTICE_CONTEXT".to_string()
+        }
     }
 }
 
@@ -76,6 +83,16 @@ pub(crate) fn statement_attribute_to_string(attr: StatementAttribute) -> String
             "LENGTH_EXCEPTION_BEHAVIOR".to_string()
         }
         StatementAttribute::SQL_ATTR_METADATA_ID => "METADATA_ID".to_string(),
+        StatementAttribute::SQL_ATTR_MONGODB_SCHEMA_DRIFT_COUNT => {
+            "MONGODB_SCHEMA_DRIFT_COUNT".to_string()
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_READ_PREFERENCE => {
+            "MONGODB_READ_PREFERENCE".to_string()
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_READ_CONCERN_LEVEL => {
+            "MONGODB_READ_CONCERN_LEVEL".to_string()
+        }
+        StatementAttribute::SQL_ATTR_MONGODB_JSON_FORMAT => "MONGODB_JSON_FORMAT".to_string(),
     }
 }
 
@@ -1,12 +1,16 @@
-use crate::api::errors::ODBCError;
+use crate::api::{
+    date_range::DateOutOfRange, errors::ODBCError, json_format::JsonFormat,
+    timezone::SessionTimeZone,
+};
 
 use cstr::{Charset, WideChar};
 use definitions::{
-    AsyncEnable, AttrConnectionPooling, AttrCpMatch, AttrOdbcVersion, BindType, Concurrency,
-    CursorScrollable, CursorSensitivity, CursorType, HDbc, HDesc, HEnv, HStmt, Handle, Len, NoScan,
-    Pointer, RetrieveData, SimulateCursor, SmallInt, SqlBool, ULen, USmallInt, UseBookmarks,
+    AsyncEnable, AttrConnectionPooling, AttrCpMatch, AttrMongoDbJsonFormat, AttrOdbcVersion,
+    BindType, CDataType, Concurrency, CursorScrollable, CursorSensitivity, CursorType, HDbc, HDesc,
+    HEnv, HStmt, Handle, Len, NoScan, OidcNoticeCallback, Pointer, RetrieveData, SimulateCursor,
+    SmallInt, SqlBool, ULen, USmallInt, UseBookmarks,
 };
-use mongo_odbc_core::TypeMode;
+use mongo_odbc_core::{DatabaseAs, ReadOptions, TypeMode};
 use mongodb::bson::{Bson, Uuid};
 use std::{
     borrow::BorrowMut,
@@ -295,10 +299,19 @@ pub struct Connection {
     pub statements: RwLock<HashSet<*mut MongoHandle>>,
     pub errors: RwLock<Vec<ODBCError>>,
     // type_mode indicates if BsonTypeInfo.simple_type_info will be
-    // utilized in place of standard BsonTypeInfo fields
+    // utilized in place of standard BsonTypeInfo fields, and how deep nested documents are
+    // flattened into dotted columns
     pub type_mode: RwLock<TypeMode>,
     // max_string_length is the maximum character length of string data.
     pub max_string_length: RwLock<Option<u16>>,
+    // database_as indicates if MongoDB databases are reported as ODBC catalogs or schemas
+    pub database_as: RwLock<DatabaseAs>,
+    // timezone is the time zone BSON dates are converted to
+    pub timezone: RwLock<SessionTimeZone>,
+    // date_out_of_range is how BSON dates outside of the years 0001 to 9999 are converted
+    pub date_out_of_range: RwLock<DateOutOfRange>,
+    // json_format is how documents, arrays and other BSON values are rendered as JSON
+    pub json_format: RwLock<JsonFormat>,
 }
 
 #[derive(Debug, Default)]
@@ -314,6 +327,15 @@ pub struct ConnectionAttributes {
     // to wait for any operation on a connection to timeout (other than
     // initial login).
     pub connection_timeout: Option<u32>,
+    // SQL_ATTR_MONGODB_TIMEZONE: the session time zone, which takes
+    // precedence over the timezone connection string/DSN option.
+    pub timezone: Option<SessionTimeZone>,
+    // SQL_ATTR_MONGODB_OIDC_NOTICE_CALLBACK: called with the OIDC notices
+    // while SQLDriverConnect waits on the user.
+    pub oidc_notice_callback: Option<OidcNoticeCallback>,
+    // SQL_ATTR_MONGODB_OIDC_NOTICE_CONTEXT: the context pointer passed to
+    // the OIDC notice callback.
+    pub oidc_notice_context: Option<Pointer>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -337,6 +359,10 @@ impl Connection {
             errors: RwLock::new(vec![]),
             type_mode: RwLock::new(TypeMode::Simple),
             max_string_length: RwLock::new(None),
+            database_as: RwLock::new(DatabaseAs::Catalog),
+            timezone: RwLock::new(SessionTimeZone::Utc),
+            date_out_of_range: RwLock::new(DateOutOfRange::Error),
+            json_format: RwLock::new(JsonFormat::Relaxed),
         }
     }
 }
@@ -398,8 +424,12 @@ pub struct StatementAttributes {
     pub fetch_bookmark_ptr: *mut Len,
     pub imp_row_desc: *mut MongoHandle,
     pub imp_param_desc: *mut MongoHandle,
+    // SQL_ATTR_MONGODB_JSON_FORMAT: the JSON format overriding that of the connection.
+    pub json_format: AttrMongoDbJsonFormat,
     pub max_length: ULen,
     pub max_rows: ULen,
+    // SQL_ATTR_METADATA_ID: whether the arguments of catalog functions are identifiers.
+    pub metadata_id: SqlBool,
     pub no_scan: NoScan,
     pub param_bind_offset_ptr: *mut ULen,
     pub param_bind_type: ULen,
@@ -408,6 +438,9 @@ pub struct StatementAttributes {
     pub param_status_ptr: *mut USmallInt,
     pub paramset_size: ULen,
     pub query_timeout: ULen,
+    // SQL_ATTR_MONGODB_READ_PREFERENCE and SQL_ATTR_MONGODB_READ_CONCERN_LEVEL: the read options
+    // overriding those of the connection.
+    pub read_options: ReadOptions,
     pub retrieve_data: RetrieveData,
     pub row_array_size: ULen,
     pub row_bind_offset_ptr: *mut ULen,
@@ -490,8 +523,10 @@ impl Statement {
                 imp_param_desc: Box::into_raw(Box::new(MongoHandle::Descriptor(
                     implicit_param_imp_desc,
                 ))),
+                json_format: AttrMongoDbJsonFormat::default(),
                 max_length: 0,
                 max_rows: 0,
+                metadata_id: SqlBool::SQL_FALSE,
                 no_scan: NoScan::SQL_NOSCAN_OFF,
                 param_bind_offset_ptr: null_mut(),
                 param_bind_type: BindType::SQL_BIND_BY_COLUMN as usize,
@@ -500,6 +535,7 @@ impl Statement {
                 param_status_ptr: null_mut(),
                 paramset_size: 0,
                 query_timeout: 0,
+                read_options: ReadOptions::default(),
                 retrieve_data: RetrieveData::Off,
                 row_array_size: 1,
                 row_bind_offset_ptr: null_mut(),
@@ -540,6 +576,15 @@ impl Statement {
             .unwrap()
             .insert(col, data);
     }
+
+    /// Returns the application row descriptor record for `col`.
+    pub(crate) fn get_ard_record(&self, col: u16) -> DescriptorRecord {
+        let ard = self.attributes.read().unwrap().app_row_desc;
+        unsafe { ard.as_ref() }
+            .and_then(MongoHandle::as_descriptor)
+            .and_then(|desc| desc.attributes.read().unwrap().records.get(&col).copied())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug)]
@@ -562,7 +607,36 @@ pub enum DescriptorState {
 
 #[derive(Debug, Default)]
 #[repr(C)]
-pub struct DescriptorAttributes {}
+pub struct DescriptorAttributes {
+    // Descriptor records keyed by record number. Records that were never set use the defaults.
+    pub records: HashMap<USmallInt, DescriptorRecord>,
+}
+
+/// The descriptor record fields the driver honors. The SQL_DESC_CONCISE_TYPE of an application
+/// row descriptor record is the C type of its column, which SQLGetData uses for SQL_ARD_TYPE.
+/// Its SQL_DESC_PRECISION and SQL_DESC_SCALE are used when retrieving SQL_C_NUMERIC data into a
+/// bound column, or with SQL_ARD_TYPE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct DescriptorRecord {
+    pub concise_type: CDataType,
+    pub precision: SmallInt,
+    pub scale: SmallInt,
+}
+
+/// The largest precision a SQL_NUMERIC_STRUCT can hold, which is also the driver-defined default
+/// precision of SQL_C_NUMERIC data. The default scale is 0.
+pub const NUMERIC_MAX_PRECISION: SmallInt = 38;
+
+impl Default for DescriptorRecord {
+    fn default() -> Self {
+        Self {
+            concise_type: CDataType::SQL_C_DEFAULT,
+            precision: NUMERIC_MAX_PRECISION,
+            scale: 0,
+        }
+    }
+}
 
 impl Descriptor {
     pub fn with_state(connection: *mut MongoHandle, state: DescriptorState) -> Self {
@@ -573,4 +647,15 @@ impl Descriptor {
             errors: RwLock::new(vec![]),
         }
     }
+
+    /// Returns the statement of the connection which uses the descriptor `handle` as its
+    /// application row descriptor, if any.
+    pub(crate) unsafe fn ard_statement(&self, handle: *mut MongoHandle) -> Option<&Statement> {
+        let conn = self.connection.as_ref()?.as_connection()?;
+        let statements = conn.statements.read().unwrap();
+        statements
+            .iter()
+            .filter_map(|stmt| stmt.as_ref()?.as_statement())
+            .find(|stmt| stmt.attributes.read().unwrap().app_row_desc == handle)
+    }
 }
@@ -1,9 +1,10 @@
-use rfc8252_http_server::start;
+use rfc8252_http_server::{start, RedirectOptions};
 
 // This is only for testing the appearance of the web pages
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
-    let (server, mut oidc_params) = start().await;
+    let (server, redirect_uri, mut oidc_params) = start(&RedirectOptions::default()).await.unwrap();
+    println!("listening on {redirect_uri}");
     let res = oidc_params.recv().await.unwrap();
     server.stop(true).await;
     println!("server result: {:?}", res);
@@ -1,12 +1,17 @@
 use actix_web::{
-    self, dev::ServerHandle, http, web, App, HttpRequest, HttpResponse, HttpServer, Result,
+    self,
+    dev::{Server, ServerHandle},
+    http, web, App, HttpRequest, HttpResponse, HttpServer, Result,
 };
 use askama::Template;
 use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpListener, ToSocketAddrs};
 use std::result::Result as StdResult;
 use tokio::sync::mpsc;
 
 // server constants
+const DEFAULT_REDIRECT_HOST: &str = "localhost";
 const DEFAULT_REDIRECT_PORT: u16 = 27097;
 #[cfg(test)]
 const DEFAULT_REDIRECT_URI: &str = "http://localhost:27097/redirect";
@@ -60,6 +65,84 @@ pub struct RFC8252HttpServerOptions {
     pub oidc_state_param: String,
 }
 
+/// RedirectHost is the loopback host the server listens on, as it appears in the redirect URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedirectHost {
+    /// The addresses localhost resolves to.
+    #[default]
+    Localhost,
+    /// An IPv4 or IPv6 loopback literal.
+    Ip(IpAddr),
+}
+
+/// RedirectOptions are the host, port and path of the redirect URI the server listens on. A port of
+/// 0 binds an ephemeral port, which the redirect URI is built from. When the port is in use, an
+/// ephemeral port is bound instead, as loopback redirect URIs may use any port (RFC 8252 7.3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectOptions {
+    pub host: RedirectHost,
+    pub port: u16,
+    pub path: String,
+}
+
+impl Default for RedirectOptions {
+    fn default() -> Self {
+        RedirectOptions {
+            host: RedirectHost::Localhost,
+            port: DEFAULT_REDIRECT_PORT,
+            path: REDIRECT_ENDPOINT.to_string(),
+        }
+    }
+}
+
+impl RedirectOptions {
+    // Returns the redirect URI of a server bound to the given port.
+    fn redirect_uri(&self, port: u16) -> String {
+        match self.host {
+            RedirectHost::Localhost => {
+                format!("http://{DEFAULT_REDIRECT_HOST}:{port}{}", self.path)
+            }
+            // SocketAddr puts IPv6 addresses in brackets.
+            RedirectHost::Ip(ip) => format!("http://{}{}", SocketAddr::new(ip, port), self.path),
+        }
+    }
+
+    // Binds the listeners of the server, returning them with the port actually bound, which is
+    // an ephemeral one when the configured port is in use.
+    fn bind(&self) -> io::Result<(Vec<TcpListener>, u16)> {
+        match self.bind_port(self.port) {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse && self.port != 0 => self.bind_port(0),
+            bound => bound,
+        }
+    }
+
+    // Binds the listeners of the server on the given port. localhost may resolve to both an IPv4
+    // and an IPv6 address: the first one must be bound, the others are bound on the same port
+    // when possible.
+    fn bind_port(&self, port: u16) -> io::Result<(Vec<TcpListener>, u16)> {
+        let addrs = match self.host {
+            RedirectHost::Localhost => (DEFAULT_REDIRECT_HOST, port)
+                .to_socket_addrs()?
+                .collect::<Vec<_>>(),
+            RedirectHost::Ip(ip) => vec![SocketAddr::new(ip, port)],
+        };
+        let (first, rest) = addrs.split_first().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{DEFAULT_REDIRECT_HOST} did not resolve to any address"),
+            )
+        })?;
+        let listener = TcpListener::bind(first)?;
+        let port = listener.local_addr()?.port();
+        let mut listeners = vec![listener];
+        listeners.extend(
+            rest.iter()
+                .filter_map(|addr| TcpListener::bind(SocketAddr::new(addr.ip(), port)).ok()),
+        );
+        Ok((listeners, port))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OidcResponseParams {
     pub code: String,
@@ -181,13 +264,14 @@ async fn not_found() -> Result<HttpResponse> {
         ))
 }
 
-// The main runner for the server
-async fn run_app(
-    sender: mpsc::Sender<ServerHandle>,
+// Builds the server on the given listeners. The HttpServer builder is not Send, so it must not
+// live in the server runner.
+fn build_server(
+    listeners: Vec<TcpListener>,
+    redirect_path: String,
     oidc_params_sender: mpsc::Sender<StdResult<OidcResponseParams, String>>,
-) -> std::io::Result<()> {
-    // srv is server controller type, `dev::Server`
-    let server = HttpServer::new(move || {
+) -> std::io::Result<Server> {
+    let mut server = HttpServer::new(move || {
         let oidc_params_sender1 = oidc_params_sender.clone();
         let oidc_params_sender2 = oidc_params_sender.clone();
         App::new()
@@ -196,39 +280,54 @@ async fn run_app(
                     .to(move |r| callback(oidc_params_sender1.clone(), r)),
             )
             .service(
-                web::resource(REDIRECT_ENDPOINT)
+                web::resource(redirect_path.as_str())
                     .to(move |r| callback(oidc_params_sender2.clone(), r)),
             )
             .service(web::resource(ACCEPTED_ENDPOINT).to(accepted))
             .default_service(web::route().to(not_found))
-    })
-    .bind(("localhost", DEFAULT_REDIRECT_PORT))?
-    .workers(1)
-    .run();
+    });
+    for listener in listeners {
+        server = server.listen(listener)?;
+    }
+    Ok(server.workers(1).run())
+}
 
+// The main runner for the server
+async fn run_app(server: Server, sender: mpsc::Sender<ServerHandle>) -> std::io::Result<()> {
     // Send server handle back to the main thread
     let _ = sender.send(server.handle()).await;
 
     server.await
 }
 
-// The start function runs the main server runner in a tokio task and returns the server handle and
-// a receiver channel for the OIDC response parameters/errors
-pub async fn start() -> (
+// The start function binds the server to the redirect URI described by the options, runs the main
+// server runner in a tokio task and returns the server handle, the redirect URI and a receiver
+// channel for the OIDC response parameters/errors
+pub async fn start(
+    options: &RedirectOptions,
+) -> io::Result<(
     ServerHandle,
+    String,
     mpsc::Receiver<StdResult<OidcResponseParams, String>>,
-) {
+)> {
+    let (listeners, port) = options.bind()?;
+    let redirect_uri = options.redirect_uri(port);
     let (sender, mut receiver) = mpsc::channel(1);
     let (oidc_params_sender, oidc_params_receiver) = mpsc::channel(1);
+    let server = build_server(listeners, options.path.clone(), oidc_params_sender)?;
 
     tokio::spawn(async move {
-        let server_future = run_app(sender, oidc_params_sender);
+        let server_future = run_app(server, sender);
         server_future.await
     });
 
-    let server_handle = receiver.recv().await.unwrap();
+    let server_handle = receiver.recv().await.ok_or_else(|| {
+        io::Error::other(format!(
+            "the server listening on {redirect_uri} did not start"
+        ))
+    })?;
 
-    (server_handle, oidc_params_receiver)
+    Ok((server_handle, redirect_uri, oidc_params_receiver))
 }
 
 #[cfg(test)]
@@ -244,7 +343,8 @@ mod unit {
     #[tokio::test(flavor = "current_thread")]
     async fn rfc8252_http_server_accepted() {
         let _lock = TEST_SEQUENTIAL.lock().await;
-        let (server_handle, mut oidc_params_receiver) = start().await;
+        let (server_handle, _, mut oidc_params_receiver) =
+            start(&RedirectOptions::default()).await.unwrap();
         let _ = reqwest::get(format!(
             "{}{}",
             DEFAULT_REDIRECT_URI, "?code=1234&state=foo"
@@ -260,7 +360,8 @@ mod unit {
     #[tokio::test(flavor = "current_thread")]
     async fn rfc8252_http_server_error() {
         let _lock = TEST_SEQUENTIAL.lock().await;
-        let (server_handle, mut oidc_params_receiver) = start().await;
+        let (server_handle, _, mut oidc_params_receiver) =
+            start(&RedirectOptions::default()).await.unwrap();
         let _ = reqwest::get(format!(
             "{}{}",
             DEFAULT_REDIRECT_URI, "?error=1234&error_description=foo"
@@ -275,7 +376,8 @@ mod unit {
     #[tokio::test(flavor = "current_thread")]
     async fn rfc8252_http_server_no_params() {
         let _lock = TEST_SEQUENTIAL.lock().await;
-        let (server_handle, mut oidc_params_receiver) = start().await;
+        let (server_handle, _, mut oidc_params_receiver) =
+            start(&RedirectOptions::default()).await.unwrap();
         let _ = reqwest::get(DEFAULT_REDIRECT_URI).await.unwrap();
         let oidc_params = oidc_params_receiver.recv().await.unwrap();
         server_handle.stop(true).await;
@@ -284,4 +386,61 @@ mod unit {
             Err("parameters error: response parameters are missing".to_string())
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rfc8252_http_server_ephemeral_port() {
+        let options = RedirectOptions {
+            host: RedirectHost::Ip(IpAddr::from([127, 0, 0, 1])),
+            port: 0,
+            path: "/oidc/callback".to_string(),
+        };
+        let (server_handle, redirect_uri, mut oidc_params_receiver) =
+            start(&options).await.unwrap();
+        assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+        assert!(redirect_uri.ends_with("/oidc/callback"));
+        assert!(!redirect_uri.contains(":0/"));
+        let _ = reqwest::get(format!("{redirect_uri}?code=1234"))
+            .await
+            .unwrap();
+        let oidc_params = oidc_params_receiver.recv().await.unwrap().unwrap();
+        server_handle.stop(true).await;
+        assert_eq!(oidc_params.code, "1234");
+        assert_eq!(oidc_params.state, None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rfc8252_http_server_port_in_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let busy_port = listener.local_addr().unwrap().port();
+        let options = RedirectOptions {
+            host: RedirectHost::Ip(IpAddr::from([127, 0, 0, 1])),
+            port: busy_port,
+            path: REDIRECT_ENDPOINT.to_string(),
+        };
+        // The server falls back to an ephemeral port, which the redirect URI has.
+        let (server_handle, redirect_uri, mut oidc_params_receiver) =
+            start(&options).await.unwrap();
+        assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+        assert!(!redirect_uri.contains(&format!(":{busy_port}/")));
+        let _ = reqwest::get(format!("{redirect_uri}?code=1234"))
+            .await
+            .unwrap();
+        let oidc_params = oidc_params_receiver.recv().await.unwrap().unwrap();
+        server_handle.stop(true).await;
+        assert_eq!(oidc_params.code, "1234");
+    }
+
+    #[test]
+    fn redirect_uri() {
+        assert_eq!(
+            DEFAULT_REDIRECT_URI,
+            RedirectOptions::default().redirect_uri(DEFAULT_REDIRECT_PORT)
+        );
+        let options = RedirectOptions {
+            host: RedirectHost::Ip("::1".parse().unwrap()),
+            port: 0,
+            path: "/cb".to_string(),
+        };
+        assert_eq!("http://[::1]:12345/cb", options.redirect_uri(12345));
+    }
 }
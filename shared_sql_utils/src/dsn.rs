@@ -15,6 +15,38 @@ const URI: &str = "uri";
 const USER: &str = "user";
 const SIMPLE_TYPES_ONLY: &str = "simple_types_only";
 const ENABLE_MAX_STRING_LENGTH: &str = "enable_max_string_length";
+const INCLUDE_DATABASES: &str = "include_databases";
+const EXCLUDE_DATABASES: &str = "exclude_databases";
+const INCLUDE_COLLECTIONS: &str = "include_collections";
+const EXCLUDE_COLLECTIONS: &str = "exclude_collections";
+const DATABASE_AS: &str = "database_as";
+const TIMEZONE: &str = "timezone";
+const DATE_OUT_OF_RANGE: &str = "date_out_of_range";
+const FLATTEN_DEPTH: &str = "flatten_depth";
+const ENABLE_ARRAY_TABLES: &str = "enable_array_tables";
+const JSON_FORMAT: &str = "json_format";
+const POLYMORPHIC_COLUMNS: &str = "polymorphic_columns";
+const SCHEMA_DRIFT: &str = "schema_drift";
+const OIDC_FLOW: &str = "oidc_flow";
+const OIDC_REDIRECT_URI: &str = "oidc_redirect_uri";
+const OIDC_TOKEN_CACHE: &str = "oidc_token_cache";
+const OIDC_CLIENT_ID: &str = "oidc_client_id";
+const OIDC_CLIENT_SECRET: &str = "oidc_client_secret";
+const OIDC_TOKEN_ENDPOINT: &str = "oidc_token_endpoint";
+const OIDC_SCOPES: &str = "oidc_scopes";
+const OIDC_TOKEN_FILE: &str = "oidc_token_file";
+const CREDENTIAL_COMMAND: &str = "credential_command";
+const TLS: &str = "tls";
+const TLS_CA_FILE: &str = "tls_ca_file";
+const TLS_CERTIFICATE_KEY_FILE: &str = "tls_certificate_key_file";
+const TLS_ALLOW_INVALID_HOSTNAMES: &str = "tls_allow_invalid_hostnames";
+const COMPRESSORS: &str = "compressors";
+const READ_PREFERENCE: &str = "read_preference";
+const READ_PREFERENCE_TAGS: &str = "read_preference_tags";
+const MAX_STALENESS_SECONDS: &str = "max_staleness_seconds";
+const READ_CONCERN_LEVEL: &str = "read_concern_level";
+const TRANSIENT_ERROR_RETRIES: &str = "transient_error_retries";
+const TRANSIENT_ERROR_BACKOFF_MS: &str = "transient_error_backoff_ms";
 // SQL-1281
 // const LOGPATH: &str = "LOGPATH";
 
@@ -46,6 +78,38 @@ pub struct DsnArgs<S: Into<String> + Copy> {
     pub driver_name: S,
     pub simple_types_only: S,
     pub enable_max_string_length: S,
+    pub include_databases: S,
+    pub exclude_databases: S,
+    pub include_collections: S,
+    pub exclude_collections: S,
+    pub database_as: S,
+    pub timezone: S,
+    pub date_out_of_range: S,
+    pub flatten_depth: S,
+    pub enable_array_tables: S,
+    pub json_format: S,
+    pub polymorphic_columns: S,
+    pub schema_drift: S,
+    pub oidc_flow: S,
+    pub oidc_redirect_uri: S,
+    pub oidc_token_cache: S,
+    pub oidc_client_id: S,
+    pub oidc_client_secret: S,
+    pub oidc_token_endpoint: S,
+    pub oidc_scopes: S,
+    pub oidc_token_file: S,
+    pub credential_command: S,
+    pub tls: S,
+    pub tls_ca_file: S,
+    pub tls_certificate_key_file: S,
+    pub tls_allow_invalid_hostnames: S,
+    pub compressors: S,
+    pub read_preference: S,
+    pub read_preference_tags: S,
+    pub max_staleness_seconds: S,
+    pub read_concern_level: S,
+    pub transient_error_retries: S,
+    pub transient_error_backoff_ms: S,
 }
 
 #[derive(Debug, Default)]
@@ -59,6 +123,38 @@ pub struct Dsn {
     pub driver_name: String,
     pub simple_types_only: String,
     pub enable_max_string_length: String,
+    pub include_databases: String,
+    pub exclude_databases: String,
+    pub include_collections: String,
+    pub exclude_collections: String,
+    pub database_as: String,
+    pub timezone: String,
+    pub date_out_of_range: String,
+    pub flatten_depth: String,
+    pub enable_array_tables: String,
+    pub json_format: String,
+    pub polymorphic_columns: String,
+    pub schema_drift: String,
+    pub oidc_flow: String,
+    pub oidc_redirect_uri: String,
+    pub oidc_token_cache: String,
+    pub oidc_client_id: String,
+    pub oidc_client_secret: String,
+    pub oidc_token_endpoint: String,
+    pub oidc_scopes: String,
+    pub oidc_token_file: String,
+    pub credential_command: String,
+    pub tls: String,
+    pub tls_ca_file: String,
+    pub tls_certificate_key_file: String,
+    pub tls_allow_invalid_hostnames: String,
+    pub compressors: String,
+    pub read_preference: String,
+    pub read_preference_tags: String,
+    pub max_staleness_seconds: String,
+    pub read_concern_level: String,
+    pub transient_error_retries: String,
+    pub transient_error_backoff_ms: String,
 }
 
 impl Dsn {
@@ -72,6 +168,38 @@ impl Dsn {
             Dsn::check_value_length(&args.user.into()),
             Dsn::check_value_length(&args.server.into()),
             Dsn::check_value_length(&args.driver_name.into()),
+            Dsn::check_value_length(&args.include_databases.into()),
+            Dsn::check_value_length(&args.exclude_databases.into()),
+            Dsn::check_value_length(&args.include_collections.into()),
+            Dsn::check_value_length(&args.exclude_collections.into()),
+            Dsn::check_value_length(&args.database_as.into()),
+            Dsn::check_value_length(&args.timezone.into()),
+            Dsn::check_value_length(&args.date_out_of_range.into()),
+            Dsn::check_value_length(&args.flatten_depth.into()),
+            Dsn::check_value_length(&args.enable_array_tables.into()),
+            Dsn::check_value_length(&args.json_format.into()),
+            Dsn::check_value_length(&args.polymorphic_columns.into()),
+            Dsn::check_value_length(&args.schema_drift.into()),
+            Dsn::check_value_length(&args.oidc_flow.into()),
+            Dsn::check_value_length(&args.oidc_redirect_uri.into()),
+            Dsn::check_value_length(&args.oidc_token_cache.into()),
+            Dsn::check_value_length(&args.oidc_client_id.into()),
+            Dsn::check_value_length(&args.oidc_client_secret.into()),
+            Dsn::check_value_length(&args.oidc_token_endpoint.into()),
+            Dsn::check_value_length(&args.oidc_scopes.into()),
+            Dsn::check_value_length(&args.oidc_token_file.into()),
+            Dsn::check_value_length(&args.credential_command.into()),
+            Dsn::check_value_length(&args.tls.into()),
+            Dsn::check_value_length(&args.tls_ca_file.into()),
+            Dsn::check_value_length(&args.tls_certificate_key_file.into()),
+            Dsn::check_value_length(&args.tls_allow_invalid_hostnames.into()),
+            Dsn::check_value_length(&args.compressors.into()),
+            Dsn::check_value_length(&args.read_preference.into()),
+            Dsn::check_value_length(&args.read_preference_tags.into()),
+            Dsn::check_value_length(&args.max_staleness_seconds.into()),
+            Dsn::check_value_length(&args.read_concern_level.into()),
+            Dsn::check_value_length(&args.transient_error_retries.into()),
+            Dsn::check_value_length(&args.transient_error_backoff_ms.into()),
         ];
         if validation.iter().all(|&b| b) {
             Ok(Self {
@@ -84,6 +212,38 @@ impl Dsn {
                 driver_name: args.driver_name.into(),
                 simple_types_only: args.simple_types_only.into(),
                 enable_max_string_length: args.enable_max_string_length.into(),
+                include_databases: args.include_databases.into(),
+                exclude_databases: args.exclude_databases.into(),
+                include_collections: args.include_collections.into(),
+                exclude_collections: args.exclude_collections.into(),
+                database_as: args.database_as.into(),
+                timezone: args.timezone.into(),
+                date_out_of_range: args.date_out_of_range.into(),
+                flatten_depth: args.flatten_depth.into(),
+                enable_array_tables: args.enable_array_tables.into(),
+                json_format: args.json_format.into(),
+                polymorphic_columns: args.polymorphic_columns.into(),
+                schema_drift: args.schema_drift.into(),
+                oidc_flow: args.oidc_flow.into(),
+                oidc_redirect_uri: args.oidc_redirect_uri.into(),
+                oidc_token_cache: args.oidc_token_cache.into(),
+                oidc_client_id: args.oidc_client_id.into(),
+                oidc_client_secret: args.oidc_client_secret.into(),
+                oidc_token_endpoint: args.oidc_token_endpoint.into(),
+                oidc_scopes: args.oidc_scopes.into(),
+                oidc_token_file: args.oidc_token_file.into(),
+                credential_command: args.credential_command.into(),
+                tls: args.tls.into(),
+                tls_ca_file: args.tls_ca_file.into(),
+                tls_certificate_key_file: args.tls_certificate_key_file.into(),
+                tls_allow_invalid_hostnames: args.tls_allow_invalid_hostnames.into(),
+                compressors: args.compressors.into(),
+                read_preference: args.read_preference.into(),
+                read_preference_tags: args.read_preference_tags.into(),
+                max_staleness_seconds: args.max_staleness_seconds.into(),
+                read_concern_level: args.read_concern_level.into(),
+                transient_error_retries: args.transient_error_retries.into(),
+                transient_error_backoff_ms: args.transient_error_backoff_ms.into(),
             })
         } else if !validation[1] {
             Err(DsnError::Dsn(args.dsn.into()))
@@ -220,6 +380,38 @@ impl Dsn {
             UID => self.user = value.to_string(),
             SIMPLE_TYPES_ONLY => self.simple_types_only = value.to_string(),
             ENABLE_MAX_STRING_LENGTH => self.enable_max_string_length = value.to_string(),
+            INCLUDE_DATABASES => self.include_databases = value.to_string(),
+            EXCLUDE_DATABASES => self.exclude_databases = value.to_string(),
+            INCLUDE_COLLECTIONS => self.include_collections = value.to_string(),
+            EXCLUDE_COLLECTIONS => self.exclude_collections = value.to_string(),
+            DATABASE_AS => self.database_as = value.to_string(),
+            TIMEZONE => self.timezone = value.to_string(),
+            DATE_OUT_OF_RANGE => self.date_out_of_range = value.to_string(),
+            FLATTEN_DEPTH => self.flatten_depth = value.to_string(),
+            ENABLE_ARRAY_TABLES => self.enable_array_tables = value.to_string(),
+            JSON_FORMAT => self.json_format = value.to_string(),
+            POLYMORPHIC_COLUMNS => self.polymorphic_columns = value.to_string(),
+            SCHEMA_DRIFT => self.schema_drift = value.to_string(),
+            OIDC_FLOW => self.oidc_flow = value.to_string(),
+            OIDC_REDIRECT_URI => self.oidc_redirect_uri = value.to_string(),
+            OIDC_TOKEN_CACHE => self.oidc_token_cache = value.to_string(),
+            OIDC_CLIENT_ID => self.oidc_client_id = value.to_string(),
+            OIDC_CLIENT_SECRET => self.oidc_client_secret = value.to_string(),
+            OIDC_TOKEN_ENDPOINT => self.oidc_token_endpoint = value.to_string(),
+            OIDC_SCOPES => self.oidc_scopes = value.to_string(),
+            OIDC_TOKEN_FILE => self.oidc_token_file = value.to_string(),
+            CREDENTIAL_COMMAND => self.credential_command = value.to_string(),
+            TLS => self.tls = value.to_string(),
+            TLS_CA_FILE => self.tls_ca_file = value.to_string(),
+            TLS_CERTIFICATE_KEY_FILE => self.tls_certificate_key_file = value.to_string(),
+            TLS_ALLOW_INVALID_HOSTNAMES => self.tls_allow_invalid_hostnames = value.to_string(),
+            COMPRESSORS => self.compressors = value.to_string(),
+            READ_PREFERENCE => self.read_preference = value.to_string(),
+            READ_PREFERENCE_TAGS => self.read_preference_tags = value.to_string(),
+            MAX_STALENESS_SECONDS => self.max_staleness_seconds = value.to_string(),
+            READ_CONCERN_LEVEL => self.read_concern_level = value.to_string(),
+            TRANSIENT_ERROR_RETRIES => self.transient_error_retries = value.to_string(),
+            TRANSIENT_ERROR_BACKOFF_MS => self.transient_error_backoff_ms = value.to_string(),
             // SQL-1281
             // LOGPATH => self.logpath = value.to_string(),
             _ => {}
@@ -241,9 +433,15 @@ impl Dsn {
     pub fn to_connection_string(&self) -> String {
         self.iter().fold(String::new(), |acc, (key, value)| {
             if value.is_empty() {
-                return String::new();
+                return acc;
+            }
+            // Values which contain the attribute delimiter, or would be read as braced, must be
+            // enclosed in braces.
+            if value.contains(';') || value.starts_with('{') {
+                format!("{acc};{key}={{{value}}};")
+            } else {
+                format!("{acc};{key}={value};")
             }
-            format!("{acc};{key}={value};")
         })
     }
 }
@@ -265,6 +463,47 @@ impl<'a> DSNIterator<'a> {
                     "enable_max_string_length",
                     &dsn_opts.enable_max_string_length,
                 ),
+                ("include_databases", &dsn_opts.include_databases),
+                ("exclude_databases", &dsn_opts.exclude_databases),
+                ("include_collections", &dsn_opts.include_collections),
+                ("exclude_collections", &dsn_opts.exclude_collections),
+                ("database_as", &dsn_opts.database_as),
+                ("timezone", &dsn_opts.timezone),
+                ("date_out_of_range", &dsn_opts.date_out_of_range),
+                ("flatten_depth", &dsn_opts.flatten_depth),
+                ("enable_array_tables", &dsn_opts.enable_array_tables),
+                ("json_format", &dsn_opts.json_format),
+                ("polymorphic_columns", &dsn_opts.polymorphic_columns),
+                ("schema_drift", &dsn_opts.schema_drift),
+                ("oidc_flow", &dsn_opts.oidc_flow),
+                ("oidc_redirect_uri", &dsn_opts.oidc_redirect_uri),
+                ("oidc_token_cache", &dsn_opts.oidc_token_cache),
+                ("oidc_client_id", &dsn_opts.oidc_client_id),
+                ("oidc_client_secret", &dsn_opts.oidc_client_secret),
+                ("oidc_token_endpoint", &dsn_opts.oidc_token_endpoint),
+                ("oidc_scopes", &dsn_opts.oidc_scopes),
+                ("oidc_token_file", &dsn_opts.oidc_token_file),
+                ("credential_command", &dsn_opts.credential_command),
+                ("tls", &dsn_opts.tls),
+                ("tls_ca_file", &dsn_opts.tls_ca_file),
+                (
+                    "tls_certificate_key_file",
+                    &dsn_opts.tls_certificate_key_file,
+                ),
+                (
+                    "tls_allow_invalid_hostnames",
+                    &dsn_opts.tls_allow_invalid_hostnames,
+                ),
+                ("compressors", &dsn_opts.compressors),
+                ("read_preference", &dsn_opts.read_preference),
+                ("read_preference_tags", &dsn_opts.read_preference_tags),
+                ("max_staleness_seconds", &dsn_opts.max_staleness_seconds),
+                ("read_concern_level", &dsn_opts.read_concern_level),
+                ("transient_error_retries", &dsn_opts.transient_error_retries),
+                (
+                    "transient_error_backoff_ms",
+                    &dsn_opts.transient_error_backoff_ms,
+                ),
                 // SQL-1281
                 // ("Logpath", &dsn_opts.logpath),
             ],
@@ -297,6 +536,7 @@ mod test {
             driver_name: "test",
             simple_types_only: "0",
             enable_max_string_length: "0",
+            ..Default::default()
         });
         assert!(dsn_opts.is_err());
     }
@@ -390,5 +630,79 @@ mod test {
         assert_eq!(dsn_opts.simple_types_only, "1");
         dsn_opts.set_field("enable_max_string_length", "1");
         assert_eq!(dsn_opts.enable_max_string_length, "1");
+        dsn_opts.set_field("INCLUDE_DATABASES", "sales,hr%");
+        assert_eq!(dsn_opts.include_databases, "sales,hr%");
+        dsn_opts.set_field("exclude_databases", "staging");
+        assert_eq!(dsn_opts.exclude_databases, "staging");
+        dsn_opts.set_field("include_collections", "orders");
+        assert_eq!(dsn_opts.include_collections, "orders");
+        dsn_opts.set_field("exclude_collections", "tmp%");
+        assert_eq!(dsn_opts.exclude_collections, "tmp%");
+        dsn_opts.set_field("Database_As", "schema");
+        assert_eq!(dsn_opts.database_as, "schema");
+        dsn_opts.set_field("TimeZone", "Europe/Paris");
+        assert_eq!(dsn_opts.timezone, "Europe/Paris");
+        dsn_opts.set_field("date_out_of_range", "clamp");
+        assert_eq!(dsn_opts.date_out_of_range, "clamp");
+        dsn_opts.set_field("FLATTEN_DEPTH", "2");
+        assert_eq!(dsn_opts.flatten_depth, "2");
+        dsn_opts.set_field("enable_array_tables", "1");
+        assert_eq!(dsn_opts.enable_array_tables, "1");
+        dsn_opts.set_field("json_format", "canonical");
+        assert_eq!(dsn_opts.json_format, "canonical");
+        dsn_opts.set_field("polymorphic_columns", "widest");
+        assert_eq!(dsn_opts.polymorphic_columns, "widest");
+        dsn_opts.set_field("schema_drift", "null");
+        assert_eq!(dsn_opts.schema_drift, "null");
+        dsn_opts.set_field("oidc_flow", "device");
+        assert_eq!(dsn_opts.oidc_flow, "device");
+        dsn_opts.set_field("oidc_redirect_uri", "http://127.0.0.1:0/redirect");
+        assert_eq!(dsn_opts.oidc_redirect_uri, "http://127.0.0.1:0/redirect");
+        dsn_opts.set_field("oidc_token_cache", "off");
+        assert_eq!(dsn_opts.oidc_token_cache, "off");
+        dsn_opts.set_field("oidc_client_id", "machine");
+        assert_eq!(dsn_opts.oidc_client_id, "machine");
+        dsn_opts.set_field("oidc_token_file", "/var/run/secrets/token");
+        assert_eq!(dsn_opts.oidc_token_file, "/var/run/secrets/token");
+        dsn_opts.set_field("credential_command", "vault read -field=password secret/db");
+        assert_eq!(
+            dsn_opts.credential_command,
+            "vault read -field=password secret/db"
+        );
+        dsn_opts.set_field("tls_ca_file", "/etc/ssl/ca.pem");
+        assert_eq!(dsn_opts.tls_ca_file, "/etc/ssl/ca.pem");
+        dsn_opts.set_field("compressors", "zstd,snappy,zlib");
+        assert_eq!(dsn_opts.compressors, "zstd,snappy,zlib");
+        dsn_opts.set_field("read_preference", "secondaryPreferred");
+        assert_eq!(dsn_opts.read_preference, "secondaryPreferred");
+        dsn_opts.set_field("read_concern_level", "majority");
+        assert_eq!(dsn_opts.read_concern_level, "majority");
+        dsn_opts.set_field("transient_error_retries", "5");
+        assert_eq!(dsn_opts.transient_error_retries, "5");
+    }
+
+    #[test]
+    fn to_connection_string_skips_empty_values() {
+        let dsn_opts = Dsn {
+            user: "user1".to_string(),
+            exclude_databases: "staging".to_string(),
+            ..Default::default()
+        };
+        let conn_str = dsn_opts.to_connection_string();
+        assert!(conn_str.contains(";User=user1;"));
+        assert!(conn_str.contains(";exclude_databases=staging;"));
+        assert!(!conn_str.contains("Database"));
+    }
+
+    #[test]
+    fn to_connection_string_braces_values_with_delimiters() {
+        let dsn_opts = Dsn {
+            password: "pass;word".to_string(),
+            read_preference_tags: "dc:ny,rack:1|dc:ny".to_string(),
+            ..Default::default()
+        };
+        let conn_str = dsn_opts.to_connection_string();
+        assert!(conn_str.contains(";Password={pass;word};"));
+        assert!(conn_str.contains(";read_preference_tags=dc:ny,rack:1|dc:ny;"));
     }
 }
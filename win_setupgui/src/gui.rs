@@ -159,6 +159,7 @@ impl ConfigGui {
                 nwg::CheckBoxState::Checked => "1",
                 _ => "0",
             },
+            ..Default::default()
         }) {
             Err(e) => {
                 nwg::modal_error_message(&self.window, "Error", &e.to_string());